@@ -10,3 +10,201 @@ kg = kg
 docena = dozen
 per = per
 total = Total:
+subtotal = Subtotal:
+tax = Tax:
+tax_rate = Tax rate
+category_tax_rates = Tax rate by category
+price_includes_tax = incl. tax
+notes = Notes
+notes_placeholder = Brand, size, "only if on sale"...
+product_photo = Product photo
+must_have = Must have
+normal = Normal
+nice_to_have = Nice to have
+only_must_have = Only must-have items
+filter_text = Filter
+filter_all = All
+filter_pending = Pending
+filter_bought = Bought
+filter_any_unit = Any unit
+filter_min_price = Min
+filter_max_price = Max
+
+# Search View Messages
+search_placeholder = Search products in every list
+search_no_results = No products found
+
+# Export Messages
+export = Export
+format_json = JSON
+format_csv = CSV
+format_markdown = Markdown checklist
+format_text = Plain text
+format_shared_code = Shared list code
+copy_to_clipboard = Copy to clipboard
+
+# Import View Messages
+import = Import
+import_placeholder = Paste a list, one product per line: "2 kg tomatoes", "1 dozen eggs", "milk"...
+import_recognized = { $count } products recognized
+import_several_lists = Each of the { $count } lists will be imported as a new list
+import_line_not_understood = Line { $line } could not be understood: { $text }
+import_new_list = New list
+import_default_list_name = Imported list
+
+# Backup View Messages
+backups = Backups
+backup_now = Back up now
+backup_restore_from_file = Restore from file
+backup_created = Backup { $name } created
+backup_restored = Backup restored
+backup_error = Error: { $error }
+
+# Sync View Messages
+sync_this_device = This device: { $device }
+sync_visible = Visible to other devices on the network
+sync_search_devices = Search devices
+sync_no_devices = No devices found on the network
+sync_address = Device address (e.g. 192.168.1.20)
+sync_invalid_address = Invalid address
+sync_done = Synced with { $device }: { $sent } changes sent, { $received } received
+sync_error = Sync error: { $error }
+sync_pairing_code = Pairing code: { $code }
+sync_pair_with = Pairing code of another device
+sync_paired = Paired. Enter the same code on every device you want to sync
+sync_invalid_code = Invalid pairing code
+sync_local_unavailable = Devices sync through the server, so direct sync on the local network is off
+
+# Server Messages
+server_title = Own server
+server_url = Server address (e.g. http://192.168.1.10:8080)
+server_token = Household token
+server_saved = Saved. Restart the app to apply the change
+server_offline = No connection to the server: { $pending } changes pending. Tap to retry
+server_rejected = The server rejected { $count } changes made offline (for example, because the list was deleted). Tap to dismiss
+
+# Share Dialog Messages
+share = Share
+share_instructions = Scan the code from another phone, or copy the text and paste it in the import screen as a shared list code.
+share_too_long = The list is too long for a QR code, but the text can still be shared.
+
+# Household View Messages
+member_name = Member name
+member_this_device = This device is used by
+member_nobody = Nobody
+list_owner = Owner
+list_assignees = Assigned to:
+added_by = Added by { $name }
+bought_by = Bought by { $name }
+
+# Split Shopping Messages
+category = Category
+item_unassigned = Unassigned
+filter_my_items = My items
+split_between = Split between:
+split_alternating = Alternating
+split_by_category = By category
+split_assign = Assign these items
+
+# Cost Splitting Messages
+split_cost = Split the cost
+expense_description = Description
+expense_paid_by = Paid by
+expense_paid_by_name = Paid by { $name }
+expense_record = Record expense
+division_equal = Equal shares
+division_percentages = By percentage
+division_assignment = By item assignment
+balances = Balances
+settle_up = Settle up
+settle_up_transfer = { $from } pays { $to } { $amount }
+settle_up_nothing = Everyone is even
+settle_up_payment = { $from } paid { $to }
+expenses = Expenses
+member_unknown = Former member
+
+# Pantry View Messages
+pantry_product = Product
+pantry_quantity = At home
+pantry_minimum = Minimum
+pantry_generate_list = Add what's missing
+pantry_list_generated = { $count ->
+    [one] 1 product added to the list
+   *[other] { $count } products added to the list
+}
+
+# Expiration Messages
+best_before = Best before
+expiration_readd = Add it back when it expires
+expiration_saved = Reminder saved for { $date }
+expiring_soon = Use soon
+expires_in = { $days ->
+    [0] Expires today ({ $date })
+    [1] Expires tomorrow ({ $date })
+   *[other] Expires in { $days } days ({ $date })
+}
+expired = Expired on { $date }
+expiration_readd_now = Add it back to its list
+expiration_done = Used up
+
+# Recipe Messages
+recipe_import_placeholder = Paste a recipe: its title, "Servings: 4" and one ingredient per line ("2 kg potatoes", "6 eggs", "salt")
+recipe_default_name = New recipe
+recipe_save_new = Save recipe
+recipe_save = Save changes
+recipe_servings = Servings
+recipe_add_to_list = Add to list
+recipe_added_to_list = { $count ->
+    [one] 1 product added or updated in the list
+   *[other] { $count } products added or updated in the list
+}
+
+# Meal Plan Messages
+meal_plan_week = Week of { $from } to { $to }
+meal_plan_no_recipes = Add some recipes to plan your meals
+meal_plan_add = Add to the menu
+meal_plan_generate_list = Shopping list for this week
+meal_plan_nothing_needed = You already have everything for this week
+breakfast = Breakfast
+lunch = Lunch
+dinner = Dinner
+monday = Monday
+tuesday = Tuesday
+wednesday = Wednesday
+thursday = Thursday
+friday = Friday
+saturday = Saturday
+sunday = Sunday
+
+# Suggestion Messages
+suggestions_running_out = Probably running out:
+suggestion_detail = Usually bought every { $interval } days, last time { $days } days ago
+
+# Duplicate Messages
+duplicate_of = Already on the list as "{ $name }"
+merge_duplicate = Merge
+clean_up_duplicates = Merge repeated products
+
+# Synonym Messages
+synonyms_help = Other names for the same product. They are used when searching, finding repeated products and suggesting names.
+synonym_alias = Also called
+synonym_canonical = Product
+
+# Barcode Messages
+barcode = Barcode
+barcode_add = Add the product with this barcode
+barcode_scan = Read the barcode from a photo
+barcode_invalid = The barcode is not a valid EAN-8, EAN-13 or UPC-A
+barcode_unknown = Unknown barcode: name the new item to remember it
+barcode_not_found = No barcode found in the photo
+
+# Receipt Messages
+receipt_import = Fill in prices from a receipt
+receipt_title = Receipt for { $name }
+receipt_load_file = Load the receipt from a text file
+receipt_placeholder = Paste the text of the receipt here, one product per line
+receipt_summary = { $matched } lines matched, { $review } to check
+receipt_not_on_list = Not on the list
+receipt_check_line = Several products look alike: choose the right one
+receipt_ignored_lines = { $count } lines are not products
+receipt_apply = Mark as bought with these prices
@@ -10,3 +10,201 @@ kg = kg
 docena = docena
 per = por
 total = Total:
+subtotal = Subtotal:
+tax = Impuesto:
+tax_rate = Tasa de impuesto
+category_tax_rates = Tasa de impuesto por categoría
+price_includes_tax = con impuesto
+notes = Notas
+notes_placeholder = Marca, tamaño, "sólo si está en oferta"...
+product_photo = Foto del producto
+must_have = Imprescindible
+normal = Normal
+nice_to_have = Opcional
+only_must_have = Sólo imprescindibles
+filter_text = Filtrar
+filter_all = Todos
+filter_pending = Pendientes
+filter_bought = Comprados
+filter_any_unit = Cualquier unidad
+filter_min_price = Mín
+filter_max_price = Máx
+
+# Mensajes de la vista de búsqueda
+search_placeholder = Buscar productos en todas las listas
+search_no_results = No se encontraron productos
+
+# Mensajes de exportación
+export = Exportar
+format_json = JSON
+format_csv = CSV
+format_markdown = Lista Markdown
+format_text = Texto simple
+format_shared_code = Código de lista compartida
+copy_to_clipboard = Copiar al portapapeles
+
+# Mensajes de la vista de importación
+import = Importar
+import_placeholder = Pega una lista, un producto por línea: "2 kg tomates", "1 docena huevos", "leche"...
+import_recognized = { $count } productos reconocidos
+import_several_lists = Cada una de las { $count } listas se importará como una lista nueva
+import_line_not_understood = No se entendió la línea { $line }: { $text }
+import_new_list = Lista nueva
+import_default_list_name = Lista importada
+
+# Mensajes de la vista de respaldos
+backups = Respaldos
+backup_now = Respaldar ahora
+backup_restore_from_file = Restaurar desde archivo
+backup_created = Se creó el respaldo { $name }
+backup_restored = Respaldo restaurado
+backup_error = Error: { $error }
+
+# Mensajes de la vista de sincronización
+sync_this_device = Este dispositivo: { $device }
+sync_visible = Visible para otros dispositivos de la red
+sync_search_devices = Buscar dispositivos
+sync_no_devices = No se encontraron dispositivos en la red
+sync_address = Dirección del dispositivo (ej. 192.168.1.20)
+sync_invalid_address = Dirección no válida
+sync_done = Sincronizado con { $device }: { $sent } cambios enviados, { $received } recibidos
+sync_error = Error al sincronizar: { $error }
+sync_pairing_code = Código de emparejamiento: { $code }
+sync_pair_with = Código de emparejamiento de otro dispositivo
+sync_paired = Emparejado. Usa el mismo código en todos los dispositivos que quieras sincronizar
+sync_invalid_code = Código de emparejamiento inválido
+sync_local_unavailable = Los dispositivos se sincronizan a través del servidor, así que la sincronización directa en la red local está desactivada
+
+# Mensajes del servidor
+server_title = Servidor propio
+server_url = Dirección del servidor (ej. http://192.168.1.10:8080)
+server_token = Token del hogar
+server_saved = Guardado. Reinicia la app para aplicar el cambio
+server_offline = Sin conexión con el servidor: { $pending } cambios pendientes. Toca para reintentar
+server_rejected = El servidor rechazó { $count } cambios hechos sin conexión (por ejemplo, porque la lista se borró). Toca para ocultar el aviso
+
+# Mensajes del diálogo para compartir
+share = Compartir
+share_instructions = Escanea el código desde otro teléfono, o copia el texto y pégalo en la pantalla de importación como código de lista compartida.
+share_too_long = La lista es demasiado larga para un código QR, pero el texto se puede compartir igual.
+
+# Mensajes de la vista del hogar
+member_name = Nombre del miembro
+member_this_device = Este dispositivo lo usa
+member_nobody = Nadie
+list_owner = Dueño
+list_assignees = Asignada a:
+added_by = Agregado por { $name }
+bought_by = Comprado por { $name }
+
+# Mensajes de reparto de la compra
+category = Categoría
+item_unassigned = Sin asignar
+filter_my_items = Mis items
+split_between = Repartir entre:
+split_alternating = Alternado
+split_by_category = Por categoría
+split_assign = Asignar estos items
+
+# Mensajes de división de gastos
+split_cost = Dividir el gasto
+expense_description = Descripción
+expense_paid_by = Pagó
+expense_paid_by_name = Pagó { $name }
+expense_record = Registrar gasto
+division_equal = Partes iguales
+division_percentages = Por porcentaje
+division_assignment = Según la asignación de los items
+balances = Saldos
+settle_up = Saldar cuentas
+settle_up_transfer = { $from } le paga { $amount } a { $to }
+settle_up_nothing = Nadie le debe nada a nadie
+settle_up_payment = { $from } le pagó a { $to }
+expenses = Gastos
+member_unknown = Ex miembro
+
+# Mensajes de la despensa
+pantry_product = Producto
+pantry_quantity = En casa
+pantry_minimum = Mínimo
+pantry_generate_list = Agregar lo que falta
+pantry_list_generated = { $count ->
+    [one] Se agregó 1 producto a la lista
+   *[other] Se agregaron { $count } productos a la lista
+}
+
+# Mensajes de vencimientos
+best_before = Consumir antes del
+expiration_readd = Volver a agregarlo cuando venza
+expiration_saved = Recordatorio guardado para el { $date }
+expiring_soon = Usar pronto
+expires_in = { $days ->
+    [0] Vence hoy ({ $date })
+    [1] Vence mañana ({ $date })
+   *[other] Vence en { $days } días ({ $date })
+}
+expired = Venció el { $date }
+expiration_readd_now = Volver a agregarlo a su lista
+expiration_done = Ya se usó
+
+# Mensajes de recetas
+recipe_import_placeholder = Pega una receta: su título, "Porciones: 4" y un ingrediente por línea ("2 kg papas", "6 huevos", "sal")
+recipe_default_name = Receta nueva
+recipe_save_new = Guardar receta
+recipe_save = Guardar cambios
+recipe_servings = Porciones
+recipe_add_to_list = Agregar a la lista
+recipe_added_to_list = { $count ->
+    [one] Se agregó o actualizó 1 producto en la lista
+   *[other] Se agregaron o actualizaron { $count } productos en la lista
+}
+
+# Mensajes del menú semanal
+meal_plan_week = Semana del { $from } al { $to }
+meal_plan_no_recipes = Agrega algunas recetas para planificar las comidas
+meal_plan_add = Agregar al menú
+meal_plan_generate_list = Lista de compras de la semana
+meal_plan_nothing_needed = Ya tienes todo lo necesario para esta semana
+breakfast = Desayuno
+lunch = Almuerzo
+dinner = Cena
+monday = Lunes
+tuesday = Martes
+wednesday = Miércoles
+thursday = Jueves
+friday = Viernes
+saturday = Sábado
+sunday = Domingo
+
+# Mensajes de sugerencias
+suggestions_running_out = Probablemente se está acabando:
+suggestion_detail = Se suele comprar cada { $interval } días, la última vez hace { $days } días
+
+# Mensajes de repetidos
+duplicate_of = Ya está en la lista como "{ $name }"
+merge_duplicate = Juntar
+clean_up_duplicates = Juntar los productos repetidos
+
+# Mensajes de sinónimos
+synonyms_help = Otros nombres del mismo producto. Se usan al buscar, al encontrar productos repetidos y al proponer nombres.
+synonym_alias = También se llama
+synonym_canonical = Producto
+
+# Mensajes de códigos de barras
+barcode = Código de barras
+barcode_add = Agregar el producto con este código
+barcode_scan = Leer el código de barras de una foto
+barcode_invalid = El código de barras no es un EAN-8, EAN-13 o UPC-A válido
+barcode_unknown = Código desconocido: ponle nombre al item nuevo para recordarlo
+barcode_not_found = No se encontró un código de barras en la foto
+
+# Mensajes de recibos
+receipt_import = Completar los precios con un recibo
+receipt_title = Recibo de { $name }
+receipt_load_file = Cargar el recibo de un archivo de texto
+receipt_placeholder = Pega aquí el texto del recibo, un producto por línea
+receipt_summary = { $matched } líneas emparejadas, { $review } por revisar
+receipt_not_on_list = No está en la lista
+receipt_check_line = Varios productos se parecen: elige el correcto
+receipt_ignored_lines = { $count } líneas no son productos
+receipt_apply = Marcar como comprado con estos precios
@@ -0,0 +1,45 @@
+use shopping_list::servidor::Servidor;
+use std::path::PathBuf;
+
+const USO: &str = "Usage: shopping_list_server [--data DIR] [--address ADDRESS] <command>
+
+Commands:
+  serve                  Serve the API (default address 0.0.0.0:8080)
+  add-household NAME     Create a household and print its token
+  list-households        List households and their tokens";
+
+fn main() -> Result<(), anyhow::Error> {
+    let mut directorio = PathBuf::from("server_data");
+    let mut direccion = String::from("0.0.0.0:8080");
+    let mut argumentos = Vec::new();
+    let mut entrada = std::env::args().skip(1);
+    while let Some(argumento) = entrada.next() {
+        match argumento.as_str() {
+            "--data" => directorio = PathBuf::from(entrada.next().unwrap_or_default()),
+            "--address" => direccion = entrada.next().unwrap_or_default(),
+            _ => argumentos.push(argumento),
+        }
+    }
+
+    let servidor = Servidor::new(&directorio)?;
+    match argumentos.iter().map(String::as_str).collect::<Vec<&str>>()[..] {
+        ["serve"] => {
+            let direccion = servidor.iniciar(&direccion)?;
+            println!("Listening on http://{direccion}");
+            loop {
+                std::thread::park();
+            }
+        }
+        ["add-household", ref nombre @ ..] if !nombre.is_empty() => {
+            let hogar = servidor.crear_hogar(&nombre.join(" "))?;
+            println!("{}\t{}\t{}", hogar.id, hogar.nombre, hogar.token);
+        }
+        ["list-households"] => {
+            for hogar in servidor.hogares()? {
+                println!("{}\t{}\t{}", hogar.id, hogar.nombre, hogar.token);
+            }
+        }
+        _ => println!("{USO}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::model::Lista;
+use crate::model::export::{FormatoExportacion, exportar};
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct ExportDialogProps {
+    listas: Vec<Lista>,
+    onclose: EventHandler<()>,
+}
+
+#[component]
+pub fn ExportDialog(props: ExportDialogProps) -> Element {
+    let mut formato = use_signal(|| FormatoExportacion::Texto);
+    let contenido = exportar(&props.listas, formato()).unwrap_or_default();
+    let contenido_portapapeles = contenido.clone();
+
+    rsx! {
+        div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/60 p-2",
+            div { class: "flex flex-col gap-y-2 w-full max-w-xl rounded-lg bg-white p-3",
+                div { class: "flex justify-between items-center",
+                    h2 { class: "text-lg font-bold", {tid!("export")} }
+                    button {
+                        r#type: "button",
+                        class: "text-red-600 rounded-full text-center",
+                        onclick: move |_| props.onclose.call(()),
+                        MaterialIcon { name: "close" }
+                    }
+                }
+                select {
+                    class: "bg-gray-50 border border-gray-300 rounded-lg p-2",
+                    onchange: move |event| {
+                        if let Some(nuevo_formato) = FormatoExportacion::TODOS
+                            .into_iter()
+                            .find(|formato| formato.clave() == event.value())
+                        {
+                            formato.set(nuevo_formato);
+                        }
+                    },
+                    for opcion in FormatoExportacion::TODOS {
+                        option {
+                            value: opcion.clave(),
+                            selected: opcion == formato(),
+                            {tid!(opcion.clave())}
+                        }
+                    }
+                }
+                textarea {
+                    class: "h-64 w-full bg-gray-50 border border-gray-300 rounded-lg p-2 font-mono text-sm",
+                    readonly: true,
+                    value: contenido,
+                }
+                button {
+                    r#type: "button",
+                    class: "text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                    onclick: move |_| {
+                        let texto = serde_json::to_string(&contenido_portapapeles).unwrap_or_default();
+                        document::eval(&format!("navigator.clipboard.writeText({texto});"));
+                    },
+                    {tid!("copy_to_clipboard")}
+                }
+            }
+        }
+    }
+}
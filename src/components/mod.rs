@@ -1,3 +1,9 @@
+mod export_dialog;
+pub use export_dialog::ExportDialog;
+mod share_dialog;
+pub use share_dialog::ShareDialog;
+mod split_cost_dialog;
+pub use split_cost_dialog::SplitCostDialog;
 mod navbar;
 pub use navbar::Navbar;
 mod toggle;
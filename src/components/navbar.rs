@@ -1,10 +1,25 @@
+use crate::DATABASE;
 use crate::Route;
+use crate::model::http::EstadoConexion;
+use crate::model::ServerConnection;
 use dioxus::prelude::*;
+use dioxus_i18n::tid;
 use dioxus_material_icons::{MaterialIcon, MaterialIconStylesheet};
 
+// Ejecuta una acción con la conexión al servidor propio, si hay uno configurado
+fn con_servidor<T>(accion: impl FnOnce(&dyn ServerConnection) -> T) -> Option<T> {
+    DATABASE.with(|f| f.server_connection().map(accion))
+}
+
 #[component]
 pub fn Navbar() -> Element {
     let current_route: Route = use_route();
+    let mut estado = use_signal(|| con_servidor(|servidor| servidor.connection_status()));
+    // Al cambiar de vista se revisa si quedan cambios por enviar al servidor
+    use_effect(use_reactive!(|current_route| {
+        _ = current_route;
+        estado.set(con_servidor(|servidor| servidor.connection_status()));
+    }));
     rsx! {
         MaterialIconStylesheet {}
         div {
@@ -12,11 +27,61 @@ pub fn Navbar() -> Element {
             class: "sticky top-0 flex text-white bg-blue-600 text-2xl p-3 justify-between items-center",
             MaterialIcon { name: "shopping_cart" }
             "Shopping Lists"
-            if current_route.to_string() == "/" {
-                MaterialIcon { name: "home" }
-            } else {
-                Link { to: Route::Home {},
+            div { class: "flex gap-x-3",
+                match estado() {
+                    None => rsx! {},
+                    Some(EstadoConexion::EnLinea) => rsx! {
+                        MaterialIcon { name: "cloud_done" }
+                    },
+                    Some(EstadoConexion::SinConexion { pendientes }) => rsx! {
+                        button {
+                            r#type: "button",
+                            class: "flex items-center",
+                            title: tid!("server_offline", pending: pendientes),
+                            onclick: move |_| {
+                                _ = con_servidor(|servidor| servidor.retry_pending());
+                                estado.set(con_servidor(|servidor| servidor.connection_status()));
+                            },
+                            MaterialIcon { name: "cloud_off" }
+                            if pendientes > 0 {
+                                span { class: "text-sm", "{pendientes}" }
+                            }
+                        }
+                    },
+                    Some(EstadoConexion::Rechazados { rechazados }) => rsx! {
+                        button {
+                            r#type: "button",
+                            class: "flex items-center",
+                            title: tid!("server_rejected", count: rechazados),
+                            onclick: move |_| {
+                                con_servidor(|servidor| servidor.dismiss_rejected());
+                                estado.set(con_servidor(|servidor| servidor.connection_status()));
+                            },
+                            MaterialIcon { name: "sync_problem" }
+                            span { class: "text-sm", "{rechazados}" }
+                        }
+                    },
+                }
+                if current_route == (Route::Sincronizacion {}) {
+                    MaterialIcon { name: "sync" }
+                } else {
+                    Link { to: Route::Sincronizacion {},
+                        MaterialIcon { name: "sync" }
+                    }
+                }
+                if current_route == (Route::Busqueda {}) {
+                    MaterialIcon { name: "search" }
+                } else {
+                    Link { to: Route::Busqueda {},
+                        MaterialIcon { name: "search" }
+                    }
+                }
+                if current_route.to_string() == "/" {
                     MaterialIcon { name: "home" }
+                } else {
+                    Link { to: Route::Home {},
+                        MaterialIcon { name: "home" }
+                    }
                 }
             }
         }
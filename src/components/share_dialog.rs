@@ -0,0 +1,64 @@
+use crate::model::compartir::{codificar, codigo_qr_svg};
+use crate::model::{Imagen, Lista};
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct ShareDialogProps {
+    lista: Lista,
+    onclose: EventHandler<()>,
+}
+
+#[component]
+pub fn ShareDialog(props: ShareDialogProps) -> Element {
+    let codigo = codificar(&props.lista).unwrap_or_default();
+    let qr = codigo_qr_svg(&codigo).map(|svg| {
+        Imagen {
+            tipo: "image/svg+xml".to_string(),
+            datos: svg.into_bytes(),
+        }
+        .data_url()
+    });
+    let codigo_portapapeles = codigo.clone();
+
+    rsx! {
+        div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/60 p-2",
+            div { class: "flex flex-col gap-y-2 w-full max-w-xl rounded-lg bg-white p-3",
+                div { class: "flex justify-between items-center",
+                    h2 { class: "text-lg font-bold", {tid!("share")} }
+                    button {
+                        r#type: "button",
+                        class: "text-red-600 rounded-full text-center",
+                        onclick: move |_| props.onclose.call(()),
+                        MaterialIcon { name: "close" }
+                    }
+                }
+                match qr {
+                    Ok(url) => rsx! {
+                        img { class: "self-center w-64 h-64", src: url }
+                    },
+                    // La lista no cabe en un código QR, pero el texto se puede compartir igual
+                    Err(_) => rsx! {
+                        p { class: "text-sm text-red-600", {tid!("share_too_long")} }
+                    },
+                }
+                p { class: "text-sm", {tid!("share_instructions")} }
+                textarea {
+                    class: "h-24 w-full bg-gray-50 border border-gray-300 rounded-lg p-2 font-mono text-sm break-all",
+                    readonly: true,
+                    value: codigo,
+                }
+                button {
+                    r#type: "button",
+                    class: "text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                    onclick: move |_| {
+                        let texto = serde_json::to_string(&codigo_portapapeles).unwrap_or_default();
+                        document::eval(&format!("navigator.clipboard.writeText({texto});"));
+                    },
+                    {tid!("copy_to_clipboard")}
+                }
+            }
+        }
+    }
+}
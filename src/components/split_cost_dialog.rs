@@ -0,0 +1,193 @@
+use crate::DATABASE;
+use crate::model::cuentas::{Division, Gasto, dividir};
+use crate::model::database::CLAVE_MIEMBRO_ACTUAL;
+use crate::model::fecha::ahora;
+use crate::model::{Lista, Miembro};
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct SplitCostDialogProps {
+    lista: Lista,
+    miembros: Vec<Miembro>,
+    onclose: EventHandler<()>,
+}
+
+fn porcentaje_de(porcentajes: &[(usize, f32)], id_miembro: usize) -> f32 {
+    porcentajes
+        .iter()
+        .find(|(id, _)| *id == id_miembro)
+        .map(|(_, porcentaje)| *porcentaje)
+        .unwrap_or_default()
+}
+
+// Registra lo comprado de una lista como un gasto compartido entre los miembros
+#[component]
+pub fn SplitCostDialog(props: SplitCostDialogProps) -> Element {
+    let miembros = props.miembros.clone();
+    let mut descripcion = use_signal(|| props.lista.nombre.clone());
+    let mut pagado_por = use_signal(|| {
+        DATABASE
+            .with(|f| f.get_setting(CLAVE_MIEMBRO_ACTUAL))
+            .unwrap_or_default()
+            .and_then(|valor| valor.parse::<usize>().ok())
+            .unwrap_or_default()
+    });
+    let mut participantes: Signal<Vec<usize>> =
+        use_signal(|| miembros.iter().map(|miembro| miembro.id).collect());
+    let cantidad = miembros.len().max(1) as f32;
+    let mut porcentajes: Signal<Vec<(usize, f32)>> = use_signal(|| {
+        miembros
+            .iter()
+            .map(|miembro| (miembro.id, 100.0 / cantidad))
+            .collect()
+    });
+    let mut modo = use_signal(|| "iguales".to_string());
+    let mut error = use_signal(|| "".to_string());
+
+    let division = match modo().as_str() {
+        "porcentajes" => Division::Porcentajes(porcentajes()),
+        "asignacion" => Division::PorAsignacion,
+        _ => Division::Iguales,
+    };
+    let cuotas = dividir(&props.lista, &participantes(), &division);
+    let total = props.lista.total;
+    let lista = props.lista.clone();
+
+    rsx! {
+        div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/60 p-2",
+            div { class: "flex flex-col gap-y-2 w-full max-w-xl rounded-lg bg-white p-3 text-sm",
+                div { class: "flex justify-between items-center",
+                    h2 { class: "text-lg font-bold", {tid!("split_cost")} }
+                    button {
+                        r#type: "button",
+                        class: "text-red-600 rounded-full text-center",
+                        onclick: move |_| props.onclose.call(()),
+                        MaterialIcon { name: "close" }
+                    }
+                }
+                input {
+                    r#type: "text",
+                    class: "bg-gray-50 border border-gray-300 rounded-lg p-2",
+                    placeholder: tid!("expense_description"),
+                    value: descripcion,
+                    oninput: move |event| descripcion.set(event.value()),
+                }
+                label { class: "flex items-center justify-between gap-x-2",
+                    {tid!("expense_paid_by")}
+                    select {
+                        class: "bg-gray-50 border border-gray-300 rounded-lg p-1",
+                        onchange: move |event| pagado_por.set(event.value().parse().unwrap_or_default()),
+                        option { value: "0", selected: pagado_por() == 0, {tid!("member_nobody")} }
+                        for miembro in miembros.clone() {
+                            option {
+                                key: "{miembro.id}",
+                                value: "{miembro.id}",
+                                selected: pagado_por() == miembro.id,
+                                "{miembro.nombre}"
+                            }
+                        }
+                    }
+                }
+                select {
+                    class: "bg-gray-50 border border-gray-300 rounded-lg p-1",
+                    onchange: move |event| modo.set(event.value()),
+                    option { value: "iguales", selected: modo() == "iguales", {tid!(Division::Iguales.clave())} }
+                    option {
+                        value: "porcentajes",
+                        selected: modo() == "porcentajes",
+                        {tid!(Division::Porcentajes(Vec::new()).clave())}
+                    }
+                    option {
+                        value: "asignacion",
+                        selected: modo() == "asignacion",
+                        {tid!(Division::PorAsignacion.clave())}
+                    }
+                }
+                for miembro in miembros.clone() {
+                    div { key: "{miembro.id}", class: "flex items-center justify-between gap-x-2",
+                        if modo() == "porcentajes" {
+                            span { "{miembro.nombre}" }
+                            div { class: "flex items-center gap-x-1",
+                                input {
+                                    r#type: "number",
+                                    class: "w-[8ch] bg-gray-50 border border-gray-300 rounded-lg px-1",
+                                    value: format!("{:.2}", porcentaje_de(&porcentajes(), miembro.id)),
+                                    onchange: move |event| {
+                                        let porcentaje = event.value().parse().unwrap_or_default();
+                                        for (id, valor) in porcentajes.write().iter_mut() {
+                                            if *id == miembro.id {
+                                                *valor = porcentaje;
+                                            }
+                                        }
+                                    },
+                                }
+                                "%"
+                            }
+                        } else {
+                            label { class: "flex items-center gap-x-1",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: participantes().contains(&miembro.id),
+                                    onchange: move |event| {
+                                        participantes.write().retain(|id| *id != miembro.id);
+                                        if event.checked() {
+                                            participantes.write().push(miembro.id);
+                                        }
+                                    },
+                                }
+                                "{miembro.nombre}"
+                            }
+                        }
+                        if let Ok(cuotas) = &cuotas {
+                            span {
+                                {
+                                    format!(
+                                        "{:.2}",
+                                        cuotas
+                                            .iter()
+                                            .find(|cuota| cuota.miembro == miembro.id)
+                                            .map(|cuota| cuota.monto)
+                                            .unwrap_or_default(),
+                                    )
+                                }
+                            }
+                        }
+                    }
+                }
+                span { class: "font-bold", {format!("{} {:.2}", tid!("grand_total"), total)} }
+                if let Err(error_division) = &cuotas {
+                    p { class: "text-red-600", "{error_division}" }
+                }
+                if !error().is_empty() {
+                    p { class: "text-red-600", "{error}" }
+                }
+                button {
+                    r#type: "button",
+                    class: "text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                    disabled: cuotas.is_err(),
+                    onclick: move |_| {
+                        let Ok(cuotas) = dividir(&lista, &participantes(), &division) else {
+                            return;
+                        };
+                        let gasto = Gasto {
+                            id: 0,
+                            id_lista: Some(lista.id),
+                            descripcion: descripcion(),
+                            fecha: ahora(),
+                            pagado_por: pagado_por(),
+                            cuotas,
+                            liquidacion: false,
+                        };
+                        match DATABASE.with(|f| f.record_expense(gasto)) {
+                            Ok(_) => props.onclose.call(()),
+                            Err(error_guardado) => error.set(error_guardado.to_string()),
+                        }
+                    },
+                    {tid!("expense_record")}
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,14 @@
 use dioxus::prelude::*;
 use dioxus_i18n::prelude::*;
+use shopping_list::model;
 use sys_locale::get_locale;
 use unic_langid::{LanguageIdentifier, langid};
-use views::{Home, ListaView};
+use views::{
+    Busqueda, Cuentas, Despensa, EditorSinonimos, Hogar, Home, Importar, ImportarRecibo, ListaView,
+    MenuSemanal, Recetas, Respaldos, Sincronizacion,
+};
 
 mod components;
-mod model;
 mod views;
 
 #[derive(Debug, Clone, Routable, PartialEq)]
@@ -15,11 +18,33 @@ enum Route {
     Home {},
     #[route("/lista/:id")]
     ListaView { id: usize },
+    #[route("/lista/:id/recibo")]
+    ImportarRecibo { id: usize },
+    #[route("/buscar")]
+    Busqueda {},
+    #[route("/importar")]
+    Importar {},
+    #[route("/respaldos")]
+    Respaldos {},
+    #[route("/sincronizar")]
+    Sincronizacion {},
+    #[route("/hogar")]
+    Hogar {},
+    #[route("/cuentas")]
+    Cuentas {},
+    #[route("/despensa")]
+    Despensa {},
+    #[route("/recetas")]
+    Recetas {},
+    #[route("/menu")]
+    MenuSemanal {},
+    #[route("/sinonimos")]
+    EditorSinonimos {},
 }
 
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
 
-thread_local! {static DATABASE: Box<dyn model::DBConnector> = Box::new(model::SQLiteConnector::new());}
+thread_local! {static DATABASE: Box<dyn model::DBConnector> = model::http::conector_configurado();}
 
 fn main() {
     dioxus::launch(App);
@@ -36,6 +61,19 @@ fn App() -> Element {
             .with_locale((langid!("en"), include_str!("../assets/i18n/en.ftl")))
             .with_locale((langid!("es"), include_str!("../assets/i18n/es.ftl")))
     });
+
+    // Si el usuario lo pidió, el dispositivo queda visible para sincronizar en la red local
+    use_hook(|| {
+        DATABASE.with(|f| {
+            let visible = f
+                .get_setting(views::CLAVE_VISIBLE_EN_RED)
+                .unwrap_or_default()
+                == Some("1".to_string());
+            if let Some(sincronizacion) = f.local_sync().filter(|_| visible) {
+                _ = sincronizacion.start_sync_server();
+            }
+        })
+    });
     rsx! {
         // Global app resources
         document::Link { rel: "stylesheet", href: TAILWIND_CSS }
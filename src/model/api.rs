@@ -0,0 +1,92 @@
+use crate::model::Imagen;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+
+// Cuerpos de las peticiones y respuestas de la API HTTP del servidor de sincronización
+pub const PREFIJO_API: &str = "/api";
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct NuevaLista {
+    pub nombre: String,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct CambioLista {
+    pub nombre: String,
+    pub modo_simple: usize,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct CambioImpuesto {
+    pub tasa_impuesto: f32,
+}
+
+// Con None la categoría vuelve a usar la tasa de la lista
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct CambioImpuestoCategoria {
+    pub categoria: String,
+    pub tasa_impuesto: Option<f32>,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct CambioPropietario {
+    pub propietario: Option<usize>,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct CambioAsignados {
+    pub asignados: Vec<usize>,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct NombreMiembro {
+    pub nombre: String,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Creado {
+    pub id: usize,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Busqueda {
+    pub texto: String,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Valor {
+    pub valor: Option<String>,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct ErrorApi {
+    pub error: String,
+}
+
+// Las imágenes viajan en base64
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct ImagenApi {
+    pub tipo: String,
+    pub datos: String,
+}
+
+impl From<Imagen> for ImagenApi {
+    fn from(imagen: Imagen) -> Self {
+        ImagenApi {
+            tipo: imagen.tipo,
+            datos: STANDARD.encode(imagen.datos),
+        }
+    }
+}
+
+impl TryFrom<ImagenApi> for Imagen {
+    type Error = anyhow::Error;
+
+    fn try_from(imagen: ImagenApi) -> Result<Self, Self::Error> {
+        Ok(Imagen {
+            tipo: imagen.tipo,
+            datos: STANDARD.decode(imagen.datos)?,
+        })
+    }
+}
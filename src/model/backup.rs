@@ -0,0 +1,53 @@
+use crate::model::fecha::formatear_fecha_hora;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const PREFIJO_RESPALDO: &str = "shopping_list-";
+const EXTENSION_RESPALDO: &str = ".db";
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Respaldo {
+    pub nombre: String,
+    pub fecha: u64,
+    pub tamano: u64,
+}
+
+impl Respaldo {
+    pub fn fecha_legible(&self) -> String {
+        formatear_fecha_hora(self.fecha)
+    }
+}
+
+pub fn directorio_respaldos(directorio_base: &Path) -> PathBuf {
+    directorio_base.join("backups")
+}
+
+pub fn nombre_respaldo(fecha: u64) -> String {
+    format!("{PREFIJO_RESPALDO}{fecha}{EXTENSION_RESPALDO}")
+}
+
+// Lista los respaldos de un directorio, del más reciente al más antiguo
+pub fn listar_respaldos(directorio: &Path) -> Result<Vec<Respaldo>, anyhow::Error> {
+    if !directorio.exists() {
+        return Ok(Vec::new());
+    }
+    let mut respaldos = Vec::new();
+    for entrada in std::fs::read_dir(directorio)? {
+        let entrada = entrada?;
+        let nombre = entrada.file_name().to_string_lossy().to_string();
+        let Some(fecha) = nombre
+            .strip_prefix(PREFIJO_RESPALDO)
+            .and_then(|resto| resto.strip_suffix(EXTENSION_RESPALDO))
+            .and_then(|fecha| fecha.parse().ok())
+        else {
+            continue;
+        };
+        respaldos.push(Respaldo {
+            nombre,
+            fecha,
+            tamano: entrada.metadata()?.len(),
+        });
+    }
+    respaldos.sort_by_key(|respaldo| std::cmp::Reverse(respaldo.fecha));
+    Ok(respaldos)
+}
@@ -0,0 +1,242 @@
+use crate::model::{DBConnector, Item};
+
+// Anchos en módulos de los cuatro elementos de cada dígito en el código L (empieza con un
+// espacio). El código R tiene los mismos anchos empezando con una barra, y el G los tiene al revés.
+const ANCHOS: [[f32; 4]; 10] = [
+    [3.0, 2.0, 1.0, 1.0],
+    [2.0, 2.0, 2.0, 1.0],
+    [2.0, 1.0, 2.0, 2.0],
+    [1.0, 4.0, 1.0, 1.0],
+    [1.0, 1.0, 3.0, 2.0],
+    [1.0, 2.0, 3.0, 1.0],
+    [1.0, 1.0, 1.0, 4.0],
+    [1.0, 3.0, 1.0, 2.0],
+    [1.0, 2.0, 1.0, 3.0],
+    [3.0, 1.0, 1.0, 2.0],
+];
+
+// Qué dígitos de la izquierda de un EAN-13 van en código G (bit 5 para el primero), según el
+// primer dígito, que no se dibuja
+const PARIDADES: [u8; 10] = [
+    0b000000, 0b001011, 0b001101, 0b001110, 0b010011, 0b011001, 0b011100, 0b010100, 0b010110,
+    0b011010,
+];
+
+// Diferencia máxima, en módulos, entre los anchos leídos de un dígito y los del código
+const ERROR_MAXIMO: f32 = 1.5;
+
+// Deja sólo los dígitos. Un UPC-A es un EAN-13 que empieza con 0, así que se guarda como tal
+pub fn normalizar_codigo(texto: &str) -> String {
+    let digitos: String = texto.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digitos.len() == 12 {
+        format!("0{digitos}")
+    } else {
+        digitos
+    }
+}
+
+// Dígito de control de los dígitos que lo preceden. Desde la derecha pesan 3, 1, 3, 1...
+fn digito_de_control(digitos: &[u32]) -> u32 {
+    let suma: u32 = digitos
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digito)| if i % 2 == 0 { digito * 3 } else { *digito })
+        .sum();
+    (10 - suma % 10) % 10
+}
+
+// Si es un EAN-8, UPC-A o EAN-13 con el dígito de control correcto
+pub fn es_valido(codigo: &str) -> bool {
+    let Some(digitos) = codigo
+        .trim()
+        .chars()
+        .map(|c| c.to_digit(10))
+        .collect::<Option<Vec<u32>>>()
+    else {
+        return false;
+    };
+    match digitos.split_last() {
+        Some((control, resto)) if [8, 12, 13].contains(&digitos.len()) => {
+            digito_de_control(resto) == *control
+        }
+        _ => false,
+    }
+}
+
+// Agrega a la lista el producto que tuvo ese código en cualquier lista, con su nombre, unidad,
+// cantidad, precio y categoría. Si ya está pendiente en la lista se pide otra vez esa cantidad.
+// Devuelve el item, o None si el código no se ha usado antes.
+pub fn agregar_por_codigo(
+    conector: &dyn DBConnector,
+    id_lista: usize,
+    codigo: &str,
+) -> Result<Option<Item>, anyhow::Error> {
+    let codigo = normalizar_codigo(codigo);
+    if !es_valido(&codigo) {
+        anyhow::bail!("Invalid barcode: {codigo}");
+    }
+    let Some(conocido) = conector.find_item_by_barcode(&codigo)? else {
+        return Ok(None);
+    };
+    let pendiente = conector
+        .get_list(id_lista)?
+        .items
+        .unwrap_or_default()
+        .into_iter()
+        .find(|item| item.codigo_barras == codigo && !item.comprado());
+    let item = match pendiente {
+        Some(pendiente) => {
+            let item = Item {
+                cantidad_requerida: pendiente.cantidad_requerida + conocido.cantidad_requerida,
+                ..pendiente
+            };
+            conector.update_list_item(item.clone())?;
+            item
+        }
+        None => {
+            let item = Item {
+                id_lista,
+                nombre: conocido.nombre,
+                unidad: conocido.unidad,
+                cantidad_requerida: conocido.cantidad_requerida,
+                precio: conocido.precio,
+                precio_incluye_impuesto: conocido.precio_incluye_impuesto,
+                categoria: conocido.categoria,
+                codigo_barras: codigo,
+                ..Default::default()
+            };
+            conector.create_new_list_item(id_lista, item.clone())?;
+            item
+        }
+    };
+    Ok(Some(item))
+}
+
+// Lee un código EAN-13, UPC-A o EAN-8 de una foto. Se prueban varias líneas horizontales y
+// verticales, en ambos sentidos, hasta que alguna tenga un código con el dígito de control
+// correcto. Los UPC-A se devuelven como EAN-13.
+pub fn leer_codigo_de_barras(datos: &[u8]) -> Result<String, anyhow::Error> {
+    let imagen = image::load_from_memory(datos)?.to_luma8();
+    let (ancho, alto) = imagen.dimensions();
+    let mut lineas: Vec<Vec<u8>> = Vec::new();
+    for parte in 1..16 {
+        let y = alto * parte / 16;
+        lineas.push((0..ancho).map(|x| imagen.get_pixel(x, y).0[0]).collect());
+    }
+    for parte in 1..16 {
+        let x = ancho * parte / 16;
+        lineas.push((0..alto).map(|y| imagen.get_pixel(x, y).0[0]).collect());
+    }
+    for linea in lineas {
+        let mut tramos = tramos(&linea);
+        if let Some(codigo) = leer_tramos(&tramos) {
+            return Ok(codigo);
+        }
+        tramos.reverse();
+        if let Some(codigo) = leer_tramos(&tramos) {
+            return Ok(codigo);
+        }
+    }
+    anyhow::bail!("No barcode found in the image")
+}
+
+// Largo de cada tramo de barras (oscuro) o espacios (claro) de la línea
+fn tramos(linea: &[u8]) -> Vec<(bool, f32)> {
+    let (Some(minimo), Some(maximo)) = (linea.iter().min(), linea.iter().max()) else {
+        return Vec::new();
+    };
+    // Sin contraste no hay barras
+    if maximo - minimo < 40 {
+        return Vec::new();
+    }
+    let umbral = (*minimo as u16 + *maximo as u16) / 2;
+    let mut tramos: Vec<(bool, f32)> = Vec::new();
+    for valor in linea {
+        let oscuro = (*valor as u16) < umbral;
+        match tramos.last_mut() {
+            Some((color, largo)) if *color == oscuro => *largo += 1.0,
+            _ => tramos.push((oscuro, 1.0)),
+        }
+    }
+    tramos
+}
+
+fn leer_tramos(tramos: &[(bool, f32)]) -> Option<String> {
+    let anchos: Vec<f32> = tramos.iter().map(|(_, largo)| *largo).collect();
+    (0..tramos.len())
+        .filter(|inicio| tramos[*inicio].0)
+        .find_map(|inicio| {
+            leer_ean(&anchos, inicio, 6)
+                .or_else(|| leer_ean(&anchos, inicio, 4))
+                .filter(|codigo| es_valido(codigo))
+        })
+}
+
+// El dígito cuyos anchos se parecen más a los leídos, y si está en código G
+fn leer_digito(anchos: &[f32], con_g: bool) -> Option<(u32, bool)> {
+    let total: f32 = anchos.iter().sum();
+    let modulos: Vec<f32> = anchos.iter().map(|ancho| ancho * 7.0 / total).collect();
+    let mut mejor: Option<(u32, bool, f32)> = None;
+    for (digito, patron) in ANCHOS.iter().enumerate() {
+        let mut candidatos = vec![(false, *patron)];
+        if con_g {
+            let mut invertido = *patron;
+            invertido.reverse();
+            candidatos.push((true, invertido));
+        }
+        for (es_g, patron) in candidatos {
+            let error: f32 = modulos
+                .iter()
+                .zip(patron)
+                .map(|(leido, esperado)| (leido - esperado).abs())
+                .sum();
+            if mejor.is_none_or(|(_, _, menor)| error < menor) {
+                mejor = Some((digito as u32, es_g, error));
+            }
+        }
+    }
+    mejor
+        .filter(|(_, _, error)| *error < ERROR_MAXIMO)
+        .map(|(digito, es_g, _)| (digito, es_g))
+}
+
+// Lee un EAN-13 (6 dígitos por lado) o un EAN-8 (4 por lado) cuya primera barra es el tramo
+// `inicio`
+fn leer_ean(anchos: &[f32], inicio: usize, por_lado: usize) -> Option<String> {
+    // Guardas de 3 tramos a los lados y de 5 al medio
+    let total_tramos = 3 + por_lado * 4 + 5 + por_lado * 4 + 3;
+    let tramos = anchos.get(inicio..inicio + total_tramos)?;
+    let modulo = tramos.iter().sum::<f32>() / (3 + por_lado * 7 + 5 + por_lado * 7 + 3) as f32;
+    let medio = 3 + por_lado * 4;
+    if tramos[..3]
+        .iter()
+        .chain(&tramos[medio..medio + 5])
+        .chain(&tramos[total_tramos - 3..])
+        .any(|ancho| (ancho / modulo - 1.0).abs() > 0.5)
+    {
+        return None;
+    }
+    // Antes del código tiene que haber un espacio claro
+    if inicio > 0 && anchos[inicio - 1] < modulo * 3.0 {
+        return None;
+    }
+    let mut izquierda = Vec::new();
+    let mut paridad = 0_u8;
+    for digito in tramos[3..medio].chunks(4) {
+        let (valor, es_g) = leer_digito(digito, por_lado == 6)?;
+        izquierda.push(valor);
+        paridad = (paridad << 1) | es_g as u8;
+    }
+    let mut derecha = Vec::new();
+    for digito in tramos[medio + 5..total_tramos - 3].chunks(4) {
+        derecha.push(leer_digito(digito, false)?.0);
+    }
+    let mut digitos = Vec::new();
+    if por_lado == 6 {
+        digitos.push(PARIDADES.iter().position(|otra| *otra == paridad)? as u32);
+    }
+    digitos.append(&mut izquierda);
+    digitos.append(&mut derecha);
+    Some(digitos.iter().map(|digito| digito.to_string()).collect())
+}
@@ -0,0 +1,235 @@
+use crate::model::import::Importacion;
+use crate::model::{Item, Lista};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use qrcode::QrCode;
+use qrcode::render::svg;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+// El texto compartido es "SL<versión>:" seguido del JSON comprimido en base64
+const PREFIJO_COMPARTIR: &str = "SL";
+pub const VERSION_COMPARTIR: u32 = 1;
+// Límite al descomprimir, para no aceptar textos que se inflan sin control
+const TAMANO_MAXIMO: u64 = 1 << 20;
+const LADO_QR: u32 = 256;
+
+// Nombres de campo cortos para que el código QR sea lo más chico posible
+#[derive(PartialEq, Debug, Deserialize, Serialize)]
+struct ListaCompartida {
+    #[serde(rename = "n")]
+    nombre: String,
+    // Nombre, unidad y cantidad requerida de cada item
+    #[serde(rename = "i")]
+    items: Vec<(String, String, f32)>,
+}
+
+pub fn codificar(lista: &Lista) -> Result<String, anyhow::Error> {
+    let compartida = ListaCompartida {
+        nombre: lista.nombre.clone(),
+        items: lista
+            .items
+            .iter()
+            .flatten()
+            .map(|item| {
+                (
+                    item.nombre.clone(),
+                    item.unidad.clone(),
+                    item.cantidad_requerida,
+                )
+            })
+            .collect(),
+    };
+    let mut compresor = DeflateEncoder::new(Vec::new(), Compression::best());
+    compresor.write_all(&serde_json::to_vec(&compartida)?)?;
+    let comprimido = compresor.finish()?;
+    Ok(format!(
+        "{PREFIJO_COMPARTIR}{VERSION_COMPARTIR}:{}",
+        URL_SAFE_NO_PAD.encode(comprimido)
+    ))
+}
+
+pub fn decodificar(texto: &str) -> Result<Importacion, anyhow::Error> {
+    let Some((encabezado, datos)) = texto.trim().split_once(':') else {
+        anyhow::bail!("This is not a shared list code");
+    };
+    let version = encabezado
+        .strip_prefix(PREFIJO_COMPARTIR)
+        .and_then(|version| version.parse::<u32>().ok())
+        .ok_or_else(|| anyhow::anyhow!("This is not a shared list code"))?;
+    if version > VERSION_COMPARTIR {
+        anyhow::bail!(
+            "Unsupported shared list version {version} (latest known is {VERSION_COMPARTIR})"
+        );
+    }
+    let comprimido = URL_SAFE_NO_PAD.decode(datos.trim())?;
+    let mut json = Vec::new();
+    DeflateDecoder::new(comprimido.as_slice())
+        .take(TAMANO_MAXIMO)
+        .read_to_end(&mut json)?;
+    let compartida: ListaCompartida = serde_json::from_slice(&json)?;
+    Ok(Importacion {
+        nombre_lista: Some(compartida.nombre),
+        items: compartida
+            .items
+            .into_iter()
+            .map(|(nombre, unidad, cantidad_requerida)| Item {
+                nombre,
+                unidad,
+                cantidad_requerida,
+                ..Default::default()
+            })
+            .collect(),
+        errores: Vec::new(),
+    })
+}
+
+// Código QR en SVG, para mostrarlo como imagen
+pub fn codigo_qr_svg(texto: &str) -> Result<String, anyhow::Error> {
+    Ok(QrCode::new(texto.as_bytes())?
+        .render::<svg::Color>()
+        .min_dimensions(LADO_QR, LADO_QR)
+        .build())
+}
+
+// Lee el texto del primer código QR que se encuentre en una foto
+pub fn leer_codigo_qr(datos: &[u8]) -> Result<String, anyhow::Error> {
+    let imagen = image::load_from_memory(datos)?.to_luma8();
+    let mut preparada = rqrr::PreparedImage::prepare_from_greyscale(
+        imagen.width() as usize,
+        imagen.height() as usize,
+        |x, y| imagen.get_pixel(x as u32, y as u32).0[0],
+    );
+    let Some(codigo) = preparada.detect_grids().into_iter().next() else {
+        anyhow::bail!("No QR code found in the image");
+    };
+    let (_, texto) = codigo.decode()?;
+    Ok(texto)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, ImageFormat, Luma};
+    use qrcode::Color;
+    use std::io::Cursor;
+
+    // Solo con lo que se comparte: nombre, unidad y cantidad requerida
+    fn lista() -> Lista {
+        Lista {
+            nombre: "Súper de la semana".to_string(),
+            items: Some(
+                (0..40)
+                    .map(|indice| Item {
+                        nombre: format!("Producto ñandú {indice}"),
+                        unidad: ["unidad", "kg", "docena"][indice % 3].to_string(),
+                        cantidad_requerida: indice as f32 * 0.25 + 0.5,
+                        ..Item::default()
+                    })
+                    .collect(),
+            ),
+            ..Lista::default()
+        }
+    }
+
+    fn como_lista(importacion: Importacion) -> Lista {
+        Lista {
+            nombre: importacion.nombre_lista.unwrap_or_default(),
+            items: Some(importacion.items),
+            ..Lista::default()
+        }
+    }
+
+    // Dibuja el código QR con su margen, como lo sacaría una foto
+    fn foto_del_codigo(texto: &str) -> Vec<u8> {
+        const ESCALA: u32 = 4;
+        const MARGEN: u32 = 4;
+        let codigo = QrCode::new(texto.as_bytes()).unwrap();
+        let ancho = codigo.width() as u32;
+        let colores = codigo.to_colors();
+        let lado = (ancho + 2 * MARGEN) * ESCALA;
+        let imagen = GrayImage::from_fn(lado, lado, |x, y| {
+            let (x, y) = (x / ESCALA, y / ESCALA);
+            let oscuro = (MARGEN..ancho + MARGEN).contains(&x)
+                && (MARGEN..ancho + MARGEN).contains(&y)
+                && colores[((y - MARGEN) * ancho + x - MARGEN) as usize] == Color::Dark;
+            Luma([if oscuro { 0 } else { 255 }])
+        });
+        let mut png = Cursor::new(Vec::new());
+        imagen.write_to(&mut png, ImageFormat::Png).unwrap();
+        png.into_inner()
+    }
+
+    #[test]
+    fn codificar_y_decodificar_da_la_misma_lista() {
+        let original = lista();
+        let codigo = codificar(&original).unwrap();
+        assert!(codigo.starts_with("SL1:"));
+        assert_eq!(como_lista(decodificar(&codigo).unwrap()), original);
+        // Pegado con espacios alrededor también se entiende
+        assert_eq!(
+            como_lista(decodificar(&format!("  {codigo}\n")).unwrap()),
+            original
+        );
+    }
+
+    #[test]
+    fn solo_se_comparten_nombre_unidad_y_cantidad() {
+        let mut con_todo = lista();
+        for item in con_todo.items.iter_mut().flatten() {
+            item.id = 7;
+            item.precio = 3.5;
+            item.cantidad_comprada = 1.0;
+            item.notas = "privado".to_string();
+        }
+        let codigo = codificar(&con_todo).unwrap();
+        assert_eq!(como_lista(decodificar(&codigo).unwrap()), lista());
+
+        let vacia = Lista {
+            items: None,
+            ..lista()
+        };
+        let importacion = decodificar(&codificar(&vacia).unwrap()).unwrap();
+        assert!(importacion.items.is_empty());
+    }
+
+    #[test]
+    fn codigo_qr_y_foto_dan_la_misma_lista() {
+        let original = lista();
+        let codigo = codificar(&original).unwrap();
+        assert!(codigo_qr_svg(&codigo).unwrap().starts_with("<?xml"));
+        let leido = leer_codigo_qr(&foto_del_codigo(&codigo)).unwrap();
+        assert_eq!(leido, codigo);
+        assert_eq!(como_lista(decodificar(&leido).unwrap()), original);
+    }
+
+    #[test]
+    fn decodificar_rechaza_textos_que_no_son_codigos() {
+        assert!(decodificar("hola").is_err());
+        assert!(decodificar("XX1:abc").is_err());
+        assert!(
+            decodificar("SL2:abc")
+                .unwrap_err()
+                .to_string()
+                .contains("version")
+        );
+        assert!(decodificar("SL1:!!!").is_err());
+        assert!(leer_codigo_qr(b"no es una imagen").is_err());
+        let mut en_blanco = Cursor::new(Vec::new());
+        GrayImage::from_pixel(64, 64, Luma([255]))
+            .write_to(&mut en_blanco, ImageFormat::Png)
+            .unwrap();
+        assert!(leer_codigo_qr(en_blanco.get_ref()).is_err());
+
+        // Un texto que se infla más allá del límite se corta y no se entiende
+        let mut compresor = DeflateEncoder::new(Vec::new(), Compression::best());
+        compresor
+            .write_all(&vec![b' '; TAMANO_MAXIMO as usize * 2])
+            .unwrap();
+        let bomba = URL_SAFE_NO_PAD.encode(compresor.finish().unwrap());
+        assert!(decodificar(&format!("SL1:{bomba}")).is_err());
+    }
+}
@@ -0,0 +1,251 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Registro "la última escritura gana" de un campo de una lista o de un item. Si dos escrituras
+// tienen la misma hora, el id del dispositivo (y luego el valor) desempata, para que todas las
+// réplicas elijan lo mismo. La cantidad comprada también es un registro: si dos personas marcan
+// el mismo item sin conexión, queda una de las dos cantidades en vez de sumarse.
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Registro {
+    pub uid: String,
+    pub tabla: String,
+    pub campo: String,
+    // Valor en JSON
+    pub valor: String,
+    pub modificado: i64,
+    pub dispositivo: String,
+}
+
+impl Registro {
+    pub fn fusionar(&self, otro: &Registro) -> Registro {
+        let version = |registro: &Registro| {
+            (
+                registro.modificado,
+                registro.dispositivo.clone(),
+                registro.valor.clone(),
+            )
+        };
+        if version(otro) > version(self) {
+            otro.clone()
+        } else {
+            self.clone()
+        }
+    }
+}
+
+// Una lista o item eliminado. Los uid no se reutilizan, así que la eliminación es definitiva
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Eliminado {
+    pub uid: String,
+    pub tabla: String,
+    pub modificado: i64,
+    pub dispositivo: String,
+}
+
+impl Eliminado {
+    pub fn fusionar(&self, otro: &Eliminado) -> Eliminado {
+        if (otro.modificado, &otro.dispositivo) > (self.modificado, &self.dispositivo) {
+            otro.clone()
+        } else {
+            self.clone()
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CambiosSync {
+    pub registros: Vec<Registro>,
+    pub eliminados: Vec<Eliminado>,
+}
+
+impl CambiosSync {
+    pub fn cantidad(&self) -> usize {
+        self.registros.len() + self.eliminados.len()
+    }
+
+    pub fn uids(&self) -> BTreeSet<String> {
+        self.registros
+            .iter()
+            .map(|registro| registro.uid.clone())
+            .chain(
+                self.eliminados
+                    .iter()
+                    .map(|eliminado| eliminado.uid.clone()),
+            )
+            .collect()
+    }
+}
+
+// Estado de una réplica: un conjunto de listas e items (agregados con uid únicos y eliminados
+// para siempre), con un registro por campo. Fusionar dos réplicas da el mismo resultado sin
+// importar el orden, ni cuántas veces se repita.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Replica {
+    pub registros: BTreeMap<(String, String), Registro>,
+    pub eliminados: BTreeMap<String, Eliminado>,
+}
+
+impl Replica {
+    pub fn fusionar(&mut self, cambios: &CambiosSync) {
+        for registro in &cambios.registros {
+            let clave = (registro.uid.clone(), registro.campo.clone());
+            let fusionado = match self.registros.get(&clave) {
+                Some(local) => local.fusionar(registro),
+                None => registro.clone(),
+            };
+            self.registros.insert(clave, fusionado);
+        }
+        for eliminado in &cambios.eliminados {
+            let fusionado = match self.eliminados.get(&eliminado.uid) {
+                Some(local) => local.fusionar(eliminado),
+                None => eliminado.clone(),
+            };
+            self.eliminados.insert(eliminado.uid.clone(), fusionado);
+        }
+    }
+
+    pub fn eliminado(&self, uid: &str) -> bool {
+        self.eliminados.contains_key(uid)
+    }
+
+    pub fn valor<T: DeserializeOwned>(&self, uid: &str, campo: &str) -> Option<T> {
+        self.registros
+            .get(&(uid.to_string(), campo.to_string()))
+            .and_then(|registro| serde_json::from_str(&registro.valor).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generador pseudoaleatorio (xorshift), para probar con muchos cambios sin otras dependencias
+    struct Azar(u64);
+
+    impl Azar {
+        fn hasta(&mut self, limite: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % limite
+        }
+    }
+
+    // Pocos uid, horas y dispositivos, para que los cambios choquen seguido
+    fn cambios(azar: &mut Azar) -> CambiosSync {
+        let registros = (0..azar.hasta(12))
+            .map(|_| Registro {
+                uid: format!("item{}", azar.hasta(4)),
+                tabla: "items".to_string(),
+                campo: ["nombre", "cantidad_comprada"][azar.hasta(2) as usize].to_string(),
+                valor: azar.hasta(4).to_string(),
+                modificado: azar.hasta(3) as i64,
+                dispositivo: format!("dispositivo{}", azar.hasta(3)),
+            })
+            .collect();
+        let eliminados = (0..azar.hasta(3))
+            .map(|_| Eliminado {
+                uid: format!("item{}", azar.hasta(4)),
+                tabla: "items".to_string(),
+                modificado: azar.hasta(3) as i64,
+                dispositivo: format!("dispositivo{}", azar.hasta(3)),
+            })
+            .collect();
+        CambiosSync {
+            registros,
+            eliminados,
+        }
+    }
+
+    fn fusionar(partes: &[&CambiosSync]) -> Replica {
+        let mut replica = Replica::default();
+        for parte in partes {
+            replica.fusionar(parte);
+        }
+        replica
+    }
+
+    // Todo el estado de una réplica, como lo enviaría a otro dispositivo
+    fn estado(replica: &Replica) -> CambiosSync {
+        CambiosSync {
+            registros: replica.registros.values().cloned().collect(),
+            eliminados: replica.eliminados.values().cloned().collect(),
+        }
+    }
+
+    fn casos() -> impl Iterator<Item = (CambiosSync, CambiosSync, CambiosSync)> {
+        let mut azar = Azar(0x9e37_79b9_7f4a_7c15);
+        (0..1000).map(move |_| (cambios(&mut azar), cambios(&mut azar), cambios(&mut azar)))
+    }
+
+    #[test]
+    fn fusionar_es_conmutativo() {
+        for (a, b, _) in casos() {
+            assert_eq!(fusionar(&[&a, &b]), fusionar(&[&b, &a]));
+        }
+    }
+
+    #[test]
+    fn fusionar_es_asociativo() {
+        for (a, b, c) in casos() {
+            let ab = estado(&fusionar(&[&a, &b]));
+            let bc = estado(&fusionar(&[&b, &c]));
+            assert_eq!(fusionar(&[&ab, &c]), fusionar(&[&a, &bc]));
+        }
+    }
+
+    #[test]
+    fn fusionar_es_idempotente() {
+        for (a, b, _) in casos() {
+            assert_eq!(fusionar(&[&a, &a]), fusionar(&[&a]));
+            let ab = estado(&fusionar(&[&a, &b]));
+            assert_eq!(fusionar(&[&ab, &a, &b]), fusionar(&[&ab]));
+        }
+    }
+
+    #[test]
+    fn compras_sin_conexion_no_se_suman() {
+        let compra = |modificado: i64, dispositivo: &str| Registro {
+            uid: "leche".to_string(),
+            tabla: "items".to_string(),
+            campo: "cantidad_comprada".to_string(),
+            valor: "2.0".to_string(),
+            modificado,
+            dispositivo: dispositivo.to_string(),
+        };
+        let replica = fusionar(&[
+            &CambiosSync {
+                registros: vec![compra(10, "telefono")],
+                ..Default::default()
+            },
+            &CambiosSync {
+                registros: vec![compra(12, "tableta")],
+                ..Default::default()
+            },
+        ]);
+        assert_eq!(
+            replica.valor::<f32>("leche", "cantidad_comprada"),
+            Some(2.0)
+        );
+    }
+
+    #[test]
+    fn gana_la_escritura_mas_reciente() {
+        let registro = |valor: &str, modificado: i64, dispositivo: &str| Registro {
+            uid: "leche".to_string(),
+            tabla: "items".to_string(),
+            campo: "nombre".to_string(),
+            valor: format!("\"{valor}\""),
+            modificado,
+            dispositivo: dispositivo.to_string(),
+        };
+        let antes = registro("leche", 10, "telefono");
+        let despues = registro("leche descremada", 11, "tableta");
+        assert_eq!(antes.fusionar(&despues), despues);
+        assert_eq!(despues.fusionar(&antes), despues);
+        // A la misma hora desempata el dispositivo
+        let empate = registro("leche entera", 11, "celular");
+        assert_eq!(empate.fusionar(&despues), despues);
+    }
+}
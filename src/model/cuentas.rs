@@ -0,0 +1,470 @@
+use crate::model::fecha::formatear_fecha_hora;
+use crate::model::{Item, Lista};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Con más deudores y acreedores que esto se liquida de forma aproximada, porque la búsqueda de
+// la solución mínima crece exponencialmente
+const MAXIMO_PARA_LIQUIDACION_EXACTA: usize = 16;
+
+// Lo que le corresponde pagar a un miembro de un gasto
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Cuota {
+    pub miembro: usize,
+    pub monto: f32,
+}
+
+// Un gasto compartido: lo pagó un miembro y se reparte en cuotas. Un pago entre miembros para
+// saldar cuentas también es un gasto, con una sola cuota para quien lo recibe.
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Gasto {
+    pub id: usize,
+    pub id_lista: Option<usize>,
+    pub descripcion: String,
+    pub fecha: u64,
+    pub pagado_por: usize,
+    pub cuotas: Vec<Cuota>,
+    #[serde(default)]
+    pub liquidacion: bool,
+}
+
+impl Gasto {
+    pub fn total(&self) -> f32 {
+        self.cuotas.iter().map(|cuota| cuota.monto).sum()
+    }
+
+    pub fn fecha_legible(&self) -> String {
+        formatear_fecha_hora(self.fecha)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Division {
+    Iguales,
+    // Porcentaje de cada miembro, deben sumar 100
+    Porcentajes(Vec<(usize, f32)>),
+    // Cada item comprado lo paga el miembro al que está asignado, y los que no están asignados a
+    // un participante se dividen en partes iguales
+    PorAsignacion,
+}
+
+impl Division {
+    pub fn clave(&self) -> &'static str {
+        match self {
+            Division::Iguales => "division_equal",
+            Division::Porcentajes(_) => "division_percentages",
+            Division::PorAsignacion => "division_assignment",
+        }
+    }
+}
+
+// Saldo de un miembro: positivo si le deben, negativo si debe
+#[derive(PartialEq, Clone, Debug)]
+pub struct Saldo {
+    pub miembro: usize,
+    pub monto: f32,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Transferencia {
+    pub de: usize,
+    pub para: usize,
+    pub monto: f32,
+}
+
+// Los cálculos se hacen en centavos para que las cuotas sumen exactamente el total
+fn a_centavos(monto: f32) -> i64 {
+    (monto as f64 * 100.0).round() as i64
+}
+
+fn a_monto(centavos: i64) -> f32 {
+    (centavos as f64 / 100.0) as f32
+}
+
+fn costo_item(item: &Item, tasa_impuesto: f32) -> i64 {
+    let (subtotal, impuesto) = item.subtotal_e_impuesto(tasa_impuesto);
+    a_centavos(subtotal + impuesto)
+}
+
+// Reparte los centavos según los pesos, dando los centavos que sobran a los restos más grandes
+fn repartir_centavos(total: i64, pesos: &[f64]) -> Vec<i64> {
+    let suma: f64 = pesos.iter().sum();
+    if pesos.is_empty() || suma <= 0.0 {
+        return vec![0; pesos.len()];
+    }
+    let exactos: Vec<f64> = pesos
+        .iter()
+        .map(|peso| total as f64 * peso / suma)
+        .collect();
+    let mut partes: Vec<i64> = exactos.iter().map(|exacto| exacto.floor() as i64).collect();
+    let mut orden: Vec<usize> = (0..pesos.len()).collect();
+    orden.sort_by(|a, b| {
+        let resto_a = exactos[*a] - partes[*a] as f64;
+        let resto_b = exactos[*b] - partes[*b] as f64;
+        resto_b.total_cmp(&resto_a).then(a.cmp(b))
+    });
+    let sobrante = total - partes.iter().sum::<i64>();
+    for indice in orden.into_iter().cycle().take(sobrante.max(0) as usize) {
+        partes[indice] += 1;
+    }
+    partes
+}
+
+// Divide lo comprado de una lista entre los participantes
+pub fn dividir(
+    lista: &Lista,
+    participantes: &[usize],
+    division: &Division,
+) -> Result<Vec<Cuota>, anyhow::Error> {
+    let comprados: Vec<&Item> = lista
+        .items
+        .iter()
+        .flatten()
+        .filter(|item| item.comprado())
+        .collect();
+    let total: i64 = comprados
+        .iter()
+        .map(|item| costo_item(item, lista.tasa_de(item)))
+        .sum();
+    let centavos: BTreeMap<usize, i64> = match division {
+        Division::Iguales => {
+            if participantes.is_empty() {
+                anyhow::bail!("There must be at least one participant");
+            }
+            let partes = repartir_centavos(total, &vec![1.0; participantes.len()]);
+            participantes.iter().copied().zip(partes).collect()
+        }
+        Division::Porcentajes(porcentajes) => {
+            let suma: f32 = porcentajes.iter().map(|(_, porcentaje)| porcentaje).sum();
+            if porcentajes.is_empty()
+                || (suma - 100.0).abs() > 0.01
+                || porcentajes.iter().any(|(_, porcentaje)| *porcentaje < 0.0)
+            {
+                anyhow::bail!("The percentages must add up to 100");
+            }
+            let pesos: Vec<f64> = porcentajes
+                .iter()
+                .map(|(_, porcentaje)| *porcentaje as f64)
+                .collect();
+            let partes = repartir_centavos(total, &pesos);
+            let mut centavos = BTreeMap::new();
+            for ((miembro, _), parte) in porcentajes.iter().zip(partes) {
+                *centavos.entry(*miembro).or_default() += parte;
+            }
+            centavos
+        }
+        Division::PorAsignacion => {
+            if participantes.is_empty() {
+                anyhow::bail!("There must be at least one participant");
+            }
+            let mut centavos: BTreeMap<usize, i64> =
+                participantes.iter().map(|miembro| (*miembro, 0)).collect();
+            let mut compartido = 0;
+            for item in comprados {
+                let costo = costo_item(item, lista.tasa_de(item));
+                match item
+                    .asignado_a
+                    .and_then(|miembro| centavos.get_mut(&miembro))
+                {
+                    Some(parte) => *parte += costo,
+                    None => compartido += costo,
+                }
+            }
+            let partes = repartir_centavos(compartido, &vec![1.0; participantes.len()]);
+            for (miembro, parte) in participantes.iter().zip(partes) {
+                *centavos.entry(*miembro).or_default() += parte;
+            }
+            centavos
+        }
+    };
+    Ok(centavos
+        .into_iter()
+        .map(|(miembro, centavos)| Cuota {
+            miembro,
+            monto: a_monto(centavos),
+        })
+        .collect())
+}
+
+fn saldos_en_centavos(gastos: &[Gasto]) -> BTreeMap<usize, i64> {
+    let mut saldos = BTreeMap::new();
+    for gasto in gastos {
+        for cuota in &gasto.cuotas {
+            let centavos = a_centavos(cuota.monto);
+            *saldos.entry(gasto.pagado_por).or_default() += centavos;
+            *saldos.entry(cuota.miembro).or_default() -= centavos;
+        }
+    }
+    saldos
+}
+
+// Saldo acumulado de cada miembro en todos los gastos, incluidos los ya saldados
+pub fn saldos(gastos: &[Gasto]) -> Vec<Saldo> {
+    saldos_en_centavos(gastos)
+        .into_iter()
+        .map(|(miembro, centavos)| Saldo {
+            miembro,
+            monto: a_monto(centavos),
+        })
+        .collect()
+}
+
+// Propone la menor cantidad de transferencias que dejan todos los saldos en cero
+pub fn liquidar(gastos: &[Gasto]) -> Vec<Transferencia> {
+    let pendientes: Vec<(usize, i64)> = saldos_en_centavos(gastos)
+        .into_iter()
+        .filter(|(_, centavos)| *centavos != 0)
+        .collect();
+    let grupos = if pendientes.len() <= MAXIMO_PARA_LIQUIDACION_EXACTA {
+        grupos_que_suman_cero(&pendientes)
+    } else {
+        vec![pendientes]
+    };
+    grupos.into_iter().flat_map(liquidar_grupo).collect()
+}
+
+// Un grupo de n saldos que suman cero se liquida con n - 1 transferencias, así que conviene
+// partir los saldos en la mayor cantidad posible de grupos que sumen cero
+fn grupos_que_suman_cero(saldos: &[(usize, i64)]) -> Vec<Vec<(usize, i64)>> {
+    let cantidad = saldos.len();
+    let conjuntos = 1usize << cantidad;
+    let mut sumas = vec![0i64; conjuntos];
+    for conjunto in 1..conjuntos {
+        let ultimo = conjunto.trailing_zeros() as usize;
+        sumas[conjunto] = sumas[conjunto & (conjunto - 1)] + saldos[ultimo].1;
+    }
+    // grupos[c]: cuántos grupos que suman cero se pueden formar con c, sacando de a un saldo
+    let mut grupos = vec![0usize; conjuntos];
+    for conjunto in 1..conjuntos {
+        let mejor = (0..cantidad)
+            .filter(|indice| conjunto & (1 << indice) != 0)
+            .map(|indice| grupos[conjunto & !(1 << indice)])
+            .max()
+            .unwrap_or_default();
+        grupos[conjunto] = mejor + usize::from(sumas[conjunto] == 0);
+    }
+    // Se recorre el camino óptimo; cada vez que el conjunto suma cero se cierra un grupo
+    let mut resultado = Vec::new();
+    let mut actual = Vec::new();
+    let mut conjunto = conjuntos - 1;
+    while conjunto != 0 {
+        let propios = grupos[conjunto] - usize::from(sumas[conjunto] == 0);
+        if sumas[conjunto] == 0 && !actual.is_empty() {
+            resultado.push(std::mem::take(&mut actual));
+        }
+        let Some(indice) = (0..cantidad).find(|indice| {
+            conjunto & (1 << indice) != 0 && grupos[conjunto & !(1 << indice)] == propios
+        }) else {
+            break;
+        };
+        actual.push(saldos[indice]);
+        conjunto &= !(1 << indice);
+    }
+    if !actual.is_empty() {
+        resultado.push(actual);
+    }
+    resultado
+}
+
+// El que más debe le paga al que más le deben, hasta que uno de los dos queda en cero
+fn liquidar_grupo(mut saldos: Vec<(usize, i64)>) -> Vec<Transferencia> {
+    let mut transferencias = Vec::new();
+    loop {
+        saldos.retain(|(_, centavos)| *centavos != 0);
+        let deudor = saldos
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (miembro, centavos))| (*centavos, *miembro))
+            .map(|(indice, _)| indice);
+        let acreedor = saldos
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (miembro, centavos))| (*centavos, std::cmp::Reverse(*miembro)))
+            .map(|(indice, _)| indice);
+        let (Some(deudor), Some(acreedor)) = (deudor, acreedor) else {
+            break;
+        };
+        if saldos[deudor].1 >= 0 || saldos[acreedor].1 <= 0 {
+            break;
+        }
+        let centavos = saldos[acreedor].1.min(-saldos[deudor].1);
+        transferencias.push(Transferencia {
+            de: saldos[deudor].0,
+            para: saldos[acreedor].0,
+            monto: a_monto(centavos),
+        });
+        saldos[deudor].1 += centavos;
+        saldos[acreedor].1 -= centavos;
+    }
+    transferencias
+}
+
+// El pago que salda una transferencia propuesta
+pub fn gasto_de_liquidacion(transferencia: &Transferencia, fecha: u64) -> Gasto {
+    Gasto {
+        id: 0,
+        id_lista: None,
+        descripcion: String::new(),
+        fecha,
+        pagado_por: transferencia.de,
+        cuotas: vec![Cuota {
+            miembro: transferencia.para,
+            monto: transferencia.monto,
+        }],
+        liquidacion: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gasto(pagado_por: usize, cuotas: &[(usize, f32)]) -> Gasto {
+        Gasto {
+            id: 0,
+            id_lista: None,
+            descripcion: String::new(),
+            fecha: 0,
+            pagado_por,
+            cuotas: cuotas
+                .iter()
+                .map(|(miembro, monto)| Cuota {
+                    miembro: *miembro,
+                    monto: *monto,
+                })
+                .collect(),
+            liquidacion: false,
+        }
+    }
+
+    // Agrega los pagos propuestos y comprueba que todos los saldos quedan en cero
+    fn queda_saldado(gastos: &[Gasto], transferencias: &[Transferencia]) {
+        let mut con_pagos = gastos.to_vec();
+        con_pagos.extend(
+            transferencias
+                .iter()
+                .map(|transferencia| gasto_de_liquidacion(transferencia, 0)),
+        );
+        assert!(
+            saldos_en_centavos(&con_pagos)
+                .values()
+                .all(|centavos| *centavos == 0)
+        );
+    }
+
+    #[test]
+    fn sin_deudas_no_hay_transferencias() {
+        let gastos = [
+            gasto(1, &[(1, 5.0), (2, 5.0)]),
+            gasto(2, &[(1, 5.0), (2, 5.0)]),
+        ];
+        assert!(liquidar(&gastos).is_empty());
+        assert!(liquidar(&[]).is_empty());
+        assert!(saldos(&gastos).iter().all(|saldo| saldo.monto == 0.0));
+    }
+
+    #[test]
+    fn un_solo_deudor_le_paga_a_cada_acreedor() {
+        let gastos = [gasto(2, &[(1, 10.0)]), gasto(3, &[(1, 2.5)])];
+        let transferencias = liquidar(&gastos);
+        assert_eq!(
+            transferencias,
+            [
+                Transferencia {
+                    de: 1,
+                    para: 2,
+                    monto: 10.0
+                },
+                Transferencia {
+                    de: 1,
+                    para: 3,
+                    monto: 2.5
+                },
+            ]
+        );
+        queda_saldado(&gastos, &transferencias);
+    }
+
+    #[test]
+    fn grupos_que_se_saldan_entre_si_no_se_mezclan() {
+        // 1 le debe 7 a 2 y 3 le debe 5 a 4: alcanzan dos transferencias, no tres
+        let gastos = [
+            gasto(2, &[(1, 7.0)]),
+            gasto(4, &[(3, 5.0)]),
+            gasto(5, &[(5, 1.0)]),
+        ];
+        let transferencias = liquidar(&gastos);
+        assert_eq!(transferencias.len(), 2);
+        assert!(transferencias.contains(&Transferencia {
+            de: 1,
+            para: 2,
+            monto: 7.0
+        }));
+        assert!(transferencias.contains(&Transferencia {
+            de: 3,
+            para: 4,
+            monto: 5.0
+        }));
+        queda_saldado(&gastos, &transferencias);
+    }
+
+    #[test]
+    fn los_centavos_se_reparten_sin_perder_ninguno() {
+        assert_eq!(repartir_centavos(1000, &[1.0; 3]), [334, 333, 333]);
+        assert_eq!(repartir_centavos(1, &[1.0, 3.0]), [0, 1]);
+        assert_eq!(repartir_centavos(500, &[]), Vec::<i64>::new());
+
+        // 10 entre tres: nadie termina debiendo fracciones de centavo
+        let lista = Lista {
+            items: Some(vec![Item {
+                precio: 10.0,
+                cantidad_comprada: 1.0,
+                ..Item::default()
+            }]),
+            ..Lista::default()
+        };
+        let cuotas = dividir(&lista, &[1, 2, 3], &Division::Iguales).unwrap();
+        let montos: Vec<f32> = cuotas.iter().map(|cuota| cuota.monto).collect();
+        assert_eq!(montos, [3.34, 3.33, 3.33]);
+        let gastos = [gasto(1, &[(1, 3.34), (2, 3.33), (3, 3.33)])];
+        let transferencias = liquidar(&gastos);
+        assert_eq!(
+            transferencias
+                .iter()
+                .map(|transferencia| a_centavos(transferencia.monto))
+                .sum::<i64>(),
+            666
+        );
+        queda_saldado(&gastos, &transferencias);
+
+        assert!(dividir(&lista, &[1], &Division::Porcentajes(vec![(1, 99.0)])).is_err());
+        assert!(dividir(&lista, &[], &Division::Iguales).is_err());
+    }
+
+    #[test]
+    fn liquidacion_exacta_con_el_maximo_de_saldos() {
+        // Ocho pares que se saldan entre sí: 16 saldos, la búsqueda exacta encuentra los 8 grupos
+        let gastos: Vec<Gasto> = (0..MAXIMO_PARA_LIQUIDACION_EXACTA / 2)
+            .map(|par| gasto(2 * par, &[(2 * par + 1, par as f32 + 1.25)]))
+            .collect();
+        let transferencias = liquidar(&gastos);
+        assert_eq!(transferencias.len(), MAXIMO_PARA_LIQUIDACION_EXACTA / 2);
+        queda_saldado(&gastos, &transferencias);
+
+        // Un solo grupo de 16 que no se puede partir: 15 transferencias
+        let cuotas: Vec<(usize, f32)> = (1..MAXIMO_PARA_LIQUIDACION_EXACTA)
+            .map(|miembro| (miembro, 0.01 * miembro as f32))
+            .collect();
+        let juntos = [gasto(0, &cuotas)];
+        let transferencias = liquidar(&juntos);
+        assert_eq!(transferencias.len(), MAXIMO_PARA_LIQUIDACION_EXACTA - 1);
+        queda_saldado(&juntos, &transferencias);
+
+        // Con uno más se liquida de forma aproximada, pero igual queda todo saldado
+        let mut gastos = gastos;
+        gastos.push(gasto(100, &[(101, 0.5)]));
+        let transferencias = liquidar(&gastos);
+        assert!(transferencias.len() < MAXIMO_PARA_LIQUIDACION_EXACTA + 2);
+        queda_saldado(&gastos, &transferencias);
+    }
+}
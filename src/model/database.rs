@@ -1,69 +1,794 @@
-use crate::model::{Item, Lista};
-use rusqlite::params;
+use crate::model::backup::{Respaldo, directorio_respaldos, listar_respaldos, nombre_respaldo};
+use crate::model::codigo_barras::normalizar_codigo;
+use crate::model::crdt::{CambiosSync, Eliminado, Registro, Replica};
+use crate::model::cuentas::{Cuota, Gasto};
+use crate::model::despensa::Producto;
+use crate::model::fecha::{ahora, hoy};
+use crate::model::http::EstadoConexion;
+use crate::model::menu::{Comida, Momento};
+use crate::model::recetas::{Ingrediente, Receta};
+use crate::model::sinonimos::{Sinonimo, Sinonimos};
+use crate::model::sugerencias::Compra;
+use crate::model::sync::{
+    CLAVE_CODIGO_SYNC, Par, ResumenSync, buscar_en_red, codigo_sync, detener_servidor,
+    iniciar_servidor, sincronizar_con,
+};
+use crate::model::vencimientos::Vencimiento;
+use crate::model::{Imagen, Item, Lista, Miembro, Prioridad, ResultadoBusqueda, validar_tasa};
+use rusqlite::{MAIN_DB, OptionalExtension, Transaction, TransactionBehavior, params};
 use rusqlite_migration::{M, Migrations};
-#[cfg(target_os = "android")]
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 
 pub trait DBConnector {
     // Operaciones con listas
-    fn create_new_list(&self, nombre: String) -> Result<(), anyhow::Error>;
+    // Devuelve el id de la lista creada, o 0 si el nombre está vacío
+    fn create_new_list(&self, nombre: String) -> Result<usize, anyhow::Error>;
     fn update_list(
         &self,
         id: usize,
         nombre: String,
         modo_simple: usize,
-    ) -> Result<(), rusqlite::Error>;
+    ) -> Result<(), anyhow::Error>;
+    fn update_list_tax_rate(&self, id: usize, tasa_impuesto: f32) -> Result<(), anyhow::Error>;
+    // Con None la categoría vuelve a usar la tasa de la lista
+    fn set_category_tax_rate(
+        &self,
+        id_lista: usize,
+        categoria: String,
+        tasa_impuesto: Option<f32>,
+    ) -> Result<(), anyhow::Error>;
     fn get_list_of_lists(&self) -> Result<Vec<Lista>, anyhow::Error>;
     fn get_list(&self, id_lista: usize) -> Result<Lista, anyhow::Error>;
     fn delete_list(&self, id_lista: usize) -> Result<(), anyhow::Error>;
     // Operaciones con items
-    fn create_new_list_item(&self, id_lista: usize, item: Item) -> Result<(), anyhow::Error>;
+    // Devuelve el id del item creado, o 0 si el nombre está vacío
+    fn create_new_list_item(&self, id_lista: usize, item: Item) -> Result<usize, anyhow::Error>;
     fn update_list_item(&self, item: Item) -> Result<(), anyhow::Error>;
     fn delete_item(&self, id: usize) -> Result<(), anyhow::Error>;
     fn clear_list_items(&self, id_lista: usize) -> Result<(), anyhow::Error>;
+    // Operaciones con imágenes de items
+    fn set_item_image(&self, id_item: usize, imagen: Imagen) -> Result<(), anyhow::Error>;
+    fn get_item_thumbnail(&self, id_item: usize) -> Result<Option<Imagen>, anyhow::Error>;
+    fn get_item_image(&self, id_item: usize) -> Result<Option<Imagen>, anyhow::Error>;
+    fn delete_item_image(&self, id_item: usize) -> Result<(), anyhow::Error>;
+    // Búsqueda de items en todas las listas
+    fn search_items(&self, texto: String) -> Result<Vec<ResultadoBusqueda>, anyhow::Error>;
+    // El último item de cualquier lista con ese código de barras
+    fn find_item_by_barcode(&self, codigo: &str) -> Result<Option<Item>, anyhow::Error>;
+    // Respaldos de la base de datos
+    fn create_backup(&self) -> Result<Respaldo, anyhow::Error>;
+    fn list_backups(&self) -> Result<Vec<Respaldo>, anyhow::Error>;
+    fn restore_backup(&self, nombre: String) -> Result<(), anyhow::Error>;
+    fn restore_backup_data(&self, datos: Vec<u8>) -> Result<(), anyhow::Error>;
+    // Configuración
+    fn get_setting(&self, clave: &str) -> Result<Option<String>, anyhow::Error>;
+    fn set_setting(&self, clave: &str, valor: &str) -> Result<(), anyhow::Error>;
+    // Miembros del hogar
+    fn get_members(&self) -> Result<Vec<Miembro>, anyhow::Error>;
+    fn create_member(&self, nombre: String) -> Result<usize, anyhow::Error>;
+    fn rename_member(&self, id: usize, nombre: String) -> Result<(), anyhow::Error>;
+    fn delete_member(&self, id: usize) -> Result<(), anyhow::Error>;
+    fn set_list_owner(
+        &self,
+        id_lista: usize,
+        propietario: Option<usize>,
+    ) -> Result<(), anyhow::Error>;
+    fn set_list_assignees(
+        &self,
+        id_lista: usize,
+        asignados: Vec<usize>,
+    ) -> Result<(), anyhow::Error>;
+
+    fn get_expenses(&self) -> Result<Vec<Gasto>, anyhow::Error>;
+    fn record_expense(&self, gasto: Gasto) -> Result<usize, anyhow::Error>;
+    fn delete_expense(&self, id: usize) -> Result<(), anyhow::Error>;
+
+    fn get_pantry(&self) -> Result<Vec<Producto>, anyhow::Error>;
+    // Con id 0 crea el producto. Devuelve su id, o 0 si el nombre está vacío
+    fn save_pantry_item(&self, producto: Producto) -> Result<usize, anyhow::Error>;
+    fn delete_pantry_item(&self, id: usize) -> Result<(), anyhow::Error>;
+
+    fn get_expirations(&self) -> Result<Vec<Vencimiento>, anyhow::Error>;
+    fn add_expiration(&self, vencimiento: Vencimiento) -> Result<usize, anyhow::Error>;
+    fn delete_expiration(&self, id: usize) -> Result<(), anyhow::Error>;
+
+    fn get_recipes(&self) -> Result<Vec<Receta>, anyhow::Error>;
+    // Con id 0 crea la receta. Devuelve su id, o 0 si el nombre está vacío
+    fn save_recipe(&self, receta: Receta) -> Result<usize, anyhow::Error>;
+    fn delete_recipe(&self, id: usize) -> Result<(), anyhow::Error>;
+
+    fn get_meals(&self) -> Result<Vec<Comida>, anyhow::Error>;
+    // Con id 0 agrega la comida al menú. Devuelve su id
+    fn save_meal(&self, comida: Comida) -> Result<usize, anyhow::Error>;
+    fn delete_meal(&self, id: usize) -> Result<(), anyhow::Error>;
+
+    fn get_purchases(&self) -> Result<Vec<Compra>, anyhow::Error>;
+
+    fn get_synonyms(&self) -> Result<Vec<Sinonimo>, anyhow::Error>;
+    // Con id 0 crea el sinónimo. Devuelve su id, o 0 si falta alguno de los nombres
+    fn save_synonym(&self, sinonimo: Sinonimo) -> Result<usize, anyhow::Error>;
+    fn delete_synonym(&self, id: usize) -> Result<(), anyhow::Error>;
+    // Nombres de productos usados en listas, despensa, historial y sinónimos, para autocompletar
+    fn get_product_names(&self) -> Result<Vec<String>, anyhow::Error>;
+    // Sincronización directa con otros dispositivos, si este conector la permite
+    fn local_sync(&self) -> Option<&dyn LocalSync>;
+    // Conexión con el servidor propio, si este conector trabaja contra uno
+    fn server_connection(&self) -> Option<&dyn ServerConnection>;
+}
+
+// Sincronización entre dispositivos de la red local. Solo la base de datos local la tiene: con
+// un servidor configurado los dispositivos se sincronizan a través de él
+pub trait LocalSync {
+    fn device_id(&self) -> Result<String, anyhow::Error>;
+    fn start_sync_server(&self) -> Result<(), anyhow::Error>;
+    fn stop_sync_server(&self);
+    fn discover_peers(&self) -> Result<Vec<Par>, anyhow::Error>;
+    fn sync_with(&self, direccion: SocketAddr) -> Result<ResumenSync, anyhow::Error>;
+}
+
+// Estado de la conexión con el servidor propio y de los cambios hechos sin conexión
+pub trait ServerConnection {
+    fn connection_status(&self) -> EstadoConexion;
+    fn retry_pending(&self) -> Result<(), anyhow::Error>;
+    fn dismiss_rejected(&self);
+}
+
+// Miembro del hogar que usa este dispositivo, al que se atribuyen las listas e items nuevos
+pub const CLAVE_MIEMBRO_ACTUAL: &str = "current_member";
+// Primer id de las listas e items creados sin conexión en la copia local de un servidor
+pub const PRIMER_ID_LOCAL: usize = 1 << 30;
+
+const COLUMNAS_ITEM: &str = "items.id, items.id_lista, items.nombre, items.unidad, items.cantidad_requerida, items.cantidad_comprada, items.precio, items.precio_incluye_impuesto, items.notas, items.prioridad, items.agregado_por, items.comprado_por, items.categoria, items.asignado_a, items.codigo_barras";
+// Las columnas que se agregan después de COLUMNAS_ITEM empiezan en este índice
+const TOTAL_COLUMNAS_ITEM: usize = 15;
+
+fn leer_item(row: &rusqlite::Row<'_>) -> Result<Item, rusqlite::Error> {
+    Ok(Item {
+        id: row.get(0)?,
+        id_lista: row.get(1)?,
+        nombre: row.get(2)?,
+        unidad: row.get(3)?,
+        cantidad_requerida: row.get(4)?,
+        cantidad_comprada: row.get(5)?,
+        precio: row.get(6)?,
+        precio_incluye_impuesto: row.get::<usize, usize>(7)? == 1,
+        notas: row.get(8)?,
+        prioridad: Prioridad::from(row.get::<usize, usize>(9)?),
+        agregado_por: row.get(10)?,
+        comprado_por: row.get(11)?,
+        categoria: row.get(12)?,
+        asignado_a: row.get(13)?,
+        codigo_barras: row.get(14)?,
+    })
+}
+
+fn leer_registro(row: &rusqlite::Row<'_>) -> Result<Registro, rusqlite::Error> {
+    Ok(Registro {
+        uid: row.get(0)?,
+        tabla: row.get(1)?,
+        campo: row.get(2)?,
+        valor: row.get(3)?,
+        modificado: row.get(4)?,
+        dispositivo: row.get(5)?,
+    })
+}
+
+fn leer_eliminado(row: &rusqlite::Row<'_>) -> Result<Eliminado, rusqlite::Error> {
+    Ok(Eliminado {
+        uid: row.get(0)?,
+        tabla: row.get(1)?,
+        modificado: row.get(2)?,
+        dispositivo: row.get(3)?,
+    })
+}
+
+// Avanza el reloj de sincronización antes de registrar un cambio hecho en este dispositivo
+macro_rules! avanzar_reloj {
+    () => {
+        "UPDATE sync_reloj SET hora = max(hora + 1, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)), secuencia = secuencia + 1;"
+    };
+}
+
+// Triggers que guardan en crdt_registros los campos sincronizados de items que cambian en este
+// dispositivo. Se recrean con todos los campos cada vez que se agrega uno
+macro_rules! triggers_crdt_items {
+    ($($campo:literal),+ $(,)?) => {
+        concat!(
+            "DROP TRIGGER IF EXISTS items_crdt_ai;
+        DROP TRIGGER IF EXISTS items_crdt_au;
+        CREATE TRIGGER IF NOT EXISTS items_crdt_ai AFTER INSERT ON items WHEN new.uid IS NULL BEGIN
+            ",
+            avanzar_reloj!(),
+            "
+            UPDATE items SET uid = lower(hex(randomblob(16))), cambio_local = new.cambio_local + 1
+            WHERE id = new.id;
+            INSERT OR REPLACE INTO crdt_registros (uid, tabla, campo, valor, modificado, dispositivo, cambio_local)
+            SELECT items.uid, 'items', campos.campo, campos.valor, (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj)
+            FROM items, (
+                SELECT 'lista' AS campo, json_quote((SELECT uid FROM listas WHERE id = new.id_lista)) AS valor",
+            $("
+                UNION ALL SELECT '", $campo, "', json_quote(new.", $campo, ")",)+
+            "
+            ) AS campos
+            WHERE items.id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS items_crdt_au AFTER UPDATE ON items WHEN new.cambio_local IS old.cambio_local BEGIN
+            ",
+            avanzar_reloj!(),
+            "
+            INSERT OR REPLACE INTO crdt_registros (uid, tabla, campo, valor, modificado, dispositivo, cambio_local)
+            SELECT new.uid, 'items', campo, valor, (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj)
+            FROM (
+                SELECT 'lista' AS campo, json_quote((SELECT uid FROM listas WHERE id = new.id_lista)) AS valor WHERE new.id_lista IS NOT old.id_lista",
+            $("
+                UNION ALL SELECT '", $campo, "', json_quote(new.", $campo, ") WHERE new.", $campo, " IS NOT old.", $campo,)+
+            "
+            );
+        END;"
+        )
+    };
+}
+
+// La tasa de una categoría se sincroniza como un campo más de la lista, con este prefijo antes
+// del nombre de la categoría. Sin tasa (null) la categoría vuelve a la tasa de la lista
+const CAMPO_TASA_CATEGORIA: &str = "tasa_categoria:";
+
+const MIGRACIONES: &[M<'_>] = &[
+    M::up(
+        "CREATE TABLE IF NOT EXISTS listas (
+            id INTEGER PRIMARY KEY,
+            nombre TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS items (
+            id INTEGER PRIMARY KEY,
+            id_lista INTEGER,
+            nombre TEXT NOT NULL,
+            unidad TEXT,
+            cantidad_requerida FLOAT,
+            cantidad_comprada FLOAT,
+            precio FLOAT
+            );",
+    ),
+    M::up("ALTER TABLE listas ADD COLUMN modo_simple INTEGER DEFAULT 0;"),
+    M::up(
+        "ALTER TABLE listas ADD COLUMN tasa_impuesto FLOAT DEFAULT 0;
+        ALTER TABLE items ADD COLUMN precio_incluye_impuesto INTEGER DEFAULT 1;",
+    ),
+    M::up("ALTER TABLE items ADD COLUMN notas TEXT NOT NULL DEFAULT '';"),
+    M::up(
+        "CREATE TABLE IF NOT EXISTS imagenes_items (
+            id_item INTEGER PRIMARY KEY,
+            tipo TEXT NOT NULL,
+            imagen BLOB NOT NULL,
+            tipo_miniatura TEXT NOT NULL,
+            miniatura BLOB NOT NULL
+        );",
+    ),
+    M::up("ALTER TABLE items ADD COLUMN prioridad INTEGER NOT NULL DEFAULT 1;"),
+    M::up(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS items_fts USING fts5(
+            nombre,
+            content='items',
+            content_rowid='id',
+            tokenize='unicode61 remove_diacritics 2'
+        );
+        INSERT INTO items_fts(items_fts) VALUES ('rebuild');
+        CREATE TRIGGER IF NOT EXISTS items_fts_ai AFTER INSERT ON items BEGIN
+            INSERT INTO items_fts(rowid, nombre) VALUES (new.id, new.nombre);
+        END;
+        CREATE TRIGGER IF NOT EXISTS items_fts_ad AFTER DELETE ON items BEGIN
+            INSERT INTO items_fts(items_fts, rowid, nombre) VALUES ('delete', old.id, old.nombre);
+        END;
+        CREATE TRIGGER IF NOT EXISTS items_fts_au AFTER UPDATE OF nombre ON items BEGIN
+            INSERT INTO items_fts(items_fts, rowid, nombre) VALUES ('delete', old.id, old.nombre);
+            INSERT INTO items_fts(rowid, nombre) VALUES (new.id, new.nombre);
+        END;",
+    ),
+    // Identificadores para la sincronización entre dispositivos. sync_reloj tiene la hora de la
+    // última modificación, que nunca es anterior a otra ya vista aunque el reloj del dispositivo
+    // atrase, y su número de secuencia, con el que se sabe qué falta enviar a cada par
+    M::up(
+        "CREATE TABLE IF NOT EXISTS configuracion (
+            clave TEXT PRIMARY KEY,
+            valor TEXT NOT NULL
+        );
+        INSERT OR IGNORE INTO configuracion (clave, valor)
+        VALUES ('id_dispositivo', lower(hex(randomblob(16))));
+        CREATE TABLE IF NOT EXISTS sync_reloj (
+            hora INTEGER NOT NULL,
+            secuencia INTEGER NOT NULL
+        );
+        INSERT INTO sync_reloj (hora, secuencia)
+        VALUES (CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER), 0);
+        ALTER TABLE listas ADD COLUMN uid TEXT;
+        ALTER TABLE listas ADD COLUMN cambio_local INTEGER NOT NULL DEFAULT 0;
+        UPDATE listas SET uid = lower(hex(randomblob(16)));
+        CREATE UNIQUE INDEX IF NOT EXISTS listas_uid ON listas (uid);
+        ALTER TABLE items ADD COLUMN uid TEXT;
+        ALTER TABLE items ADD COLUMN cambio_local INTEGER NOT NULL DEFAULT 0;
+        UPDATE items SET uid = lower(hex(randomblob(16)));
+        CREATE UNIQUE INDEX IF NOT EXISTS items_uid ON items (uid);
+        CREATE TABLE IF NOT EXISTS eliminados (
+            uid TEXT PRIMARY KEY,
+            tabla TEXT NOT NULL,
+            modificado INTEGER NOT NULL,
+            dispositivo TEXT NOT NULL,
+            cambio_local INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS sync_pares (
+            dispositivo TEXT PRIMARY KEY,
+            enviado_hasta INTEGER NOT NULL DEFAULT 0
+        );",
+    ),
+    // Estado de sincronización sin conflictos: un registro "la última escritura gana" por campo
+    M::up(concat!(
+        "CREATE TABLE IF NOT EXISTS crdt_registros (
+            uid TEXT NOT NULL,
+            tabla TEXT NOT NULL,
+            campo TEXT NOT NULL,
+            valor TEXT NOT NULL,
+            modificado INTEGER NOT NULL,
+            dispositivo TEXT NOT NULL,
+            cambio_local INTEGER NOT NULL,
+            PRIMARY KEY (uid, campo)
+        );
+        ",
+        avanzar_reloj!(),
+        "
+        INSERT OR REPLACE INTO crdt_registros (uid, tabla, campo, valor, modificado, dispositivo, cambio_local)
+        SELECT uid, 'listas', 'nombre', json_quote(nombre), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM listas
+        UNION ALL SELECT uid, 'listas', 'modo_simple', json_quote(modo_simple), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM listas
+        UNION ALL SELECT uid, 'listas', 'tasa_impuesto', json_quote(tasa_impuesto), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM listas;
+        INSERT OR REPLACE INTO crdt_registros (uid, tabla, campo, valor, modificado, dispositivo, cambio_local)
+        SELECT items.uid, 'items', 'lista', json_quote(listas.uid), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj)
+        FROM items JOIN listas ON listas.id = items.id_lista
+        UNION ALL SELECT uid, 'items', 'nombre', json_quote(nombre), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM items
+        UNION ALL SELECT uid, 'items', 'unidad', json_quote(unidad), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM items
+        UNION ALL SELECT uid, 'items', 'cantidad_requerida', json_quote(cantidad_requerida), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM items
+        UNION ALL SELECT uid, 'items', 'cantidad_comprada', json_quote(cantidad_comprada), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM items
+        UNION ALL SELECT uid, 'items', 'precio', json_quote(precio), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM items
+        UNION ALL SELECT uid, 'items', 'precio_incluye_impuesto', json_quote(precio_incluye_impuesto), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM items
+        UNION ALL SELECT uid, 'items', 'notas', json_quote(notas), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM items
+        UNION ALL SELECT uid, 'items', 'prioridad', json_quote(prioridad), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj) FROM items;
+        CREATE TRIGGER IF NOT EXISTS listas_crdt_ai AFTER INSERT ON listas WHEN new.uid IS NULL BEGIN
+            ",
+        avanzar_reloj!(),
+        "
+            UPDATE listas SET uid = lower(hex(randomblob(16))), cambio_local = new.cambio_local + 1
+            WHERE id = new.id;
+            INSERT OR REPLACE INTO crdt_registros (uid, tabla, campo, valor, modificado, dispositivo, cambio_local)
+            SELECT listas.uid, 'listas', campos.campo, campos.valor, (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj)
+            FROM listas, (
+                SELECT 'nombre' AS campo, json_quote(new.nombre) AS valor
+                UNION ALL SELECT 'modo_simple', json_quote(new.modo_simple)
+                UNION ALL SELECT 'tasa_impuesto', json_quote(new.tasa_impuesto)
+            ) AS campos
+            WHERE listas.id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS listas_crdt_au AFTER UPDATE ON listas WHEN new.cambio_local IS old.cambio_local BEGIN
+            ",
+        avanzar_reloj!(),
+        "
+            INSERT OR REPLACE INTO crdt_registros (uid, tabla, campo, valor, modificado, dispositivo, cambio_local)
+            SELECT new.uid, 'listas', campo, valor, (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj)
+            FROM (
+                SELECT 'nombre' AS campo, json_quote(new.nombre) AS valor WHERE new.nombre IS NOT old.nombre
+                UNION ALL SELECT 'modo_simple', json_quote(new.modo_simple) WHERE new.modo_simple IS NOT old.modo_simple
+                UNION ALL SELECT 'tasa_impuesto', json_quote(new.tasa_impuesto) WHERE new.tasa_impuesto IS NOT old.tasa_impuesto
+            );
+        END;
+        CREATE TRIGGER IF NOT EXISTS listas_crdt_ad AFTER DELETE ON listas BEGIN
+            ",
+        avanzar_reloj!(),
+        "
+            INSERT OR REPLACE INTO eliminados (uid, tabla, modificado, dispositivo, cambio_local)
+            VALUES (old.uid, 'listas', (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj));
+            DELETE FROM crdt_registros WHERE uid = old.uid;
+        END;
+        ",
+        triggers_crdt_items!(
+            "nombre",
+            "unidad",
+            "cantidad_requerida",
+            "cantidad_comprada",
+            "precio",
+            "precio_incluye_impuesto",
+            "notas",
+            "prioridad",
+        ),
+        "
+        CREATE TRIGGER IF NOT EXISTS items_crdt_ad AFTER DELETE ON items BEGIN
+            ",
+        avanzar_reloj!(),
+        "
+            INSERT OR REPLACE INTO eliminados (uid, tabla, modificado, dispositivo, cambio_local)
+            VALUES (old.uid, 'items', (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj));
+            DELETE FROM crdt_registros WHERE uid = old.uid;
+        END;"
+    )),
+    // Operaciones hechas sin conexión al servidor, en el orden en que se hicieron
+    M::up(
+        "CREATE TABLE IF NOT EXISTS cola_servidor (
+            id INTEGER PRIMARY KEY,
+            operacion TEXT NOT NULL
+        );",
+    ),
+    // Miembros del hogar. Son propios de cada base de datos, así que no se sincronizan por la red local
+    M::up(
+        "CREATE TABLE IF NOT EXISTS miembros (
+            id INTEGER PRIMARY KEY,
+            nombre TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS listas_asignados (
+            id_lista INTEGER NOT NULL,
+            id_miembro INTEGER NOT NULL,
+            PRIMARY KEY (id_lista, id_miembro)
+        );
+        ALTER TABLE listas ADD COLUMN id_propietario INTEGER;
+        ALTER TABLE items ADD COLUMN agregado_por INTEGER;
+        ALTER TABLE items ADD COLUMN comprado_por INTEGER;",
+    ),
+    // Categoría (se sincroniza, así que se recrean los triggers de items) y miembro asignado a cada item
+    M::up(concat!(
+        "ALTER TABLE items ADD COLUMN categoria TEXT NOT NULL DEFAULT '';
+        ALTER TABLE items ADD COLUMN asignado_a INTEGER;
+        ",
+        triggers_crdt_items!(
+            "nombre",
+            "unidad",
+            "cantidad_requerida",
+            "cantidad_comprada",
+            "precio",
+            "precio_incluye_impuesto",
+            "notas",
+            "prioridad",
+            "categoria",
+        )
+    )),
+    // Tasas de impuesto propias de algunas categorías de una lista
+    M::up(
+        "CREATE TABLE IF NOT EXISTS tasas_categoria (
+            id_lista INTEGER NOT NULL,
+            categoria TEXT NOT NULL,
+            tasa_impuesto FLOAT NOT NULL,
+            PRIMARY KEY (id_lista, categoria)
+        );",
+    ),
+    // Gastos compartidos entre los miembros del hogar, que tampoco se sincronizan por la red local
+    M::up(
+        "CREATE TABLE IF NOT EXISTS gastos (
+            id INTEGER PRIMARY KEY,
+            id_lista INTEGER,
+            descripcion TEXT NOT NULL DEFAULT '',
+            fecha INTEGER NOT NULL,
+            pagado_por INTEGER NOT NULL,
+            liquidacion INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS gastos_cuotas (
+            id_gasto INTEGER NOT NULL,
+            id_miembro INTEGER NOT NULL,
+            monto REAL NOT NULL,
+            PRIMARY KEY (id_gasto, id_miembro)
+        );",
+    ),
+    // Despensa de la casa. Tampoco se sincroniza por la red local
+    M::up(
+        "CREATE TABLE IF NOT EXISTS despensa (
+            id INTEGER PRIMARY KEY,
+            nombre TEXT NOT NULL,
+            unidad TEXT NOT NULL,
+            cantidad REAL NOT NULL DEFAULT 0,
+            minimo REAL NOT NULL DEFAULT 0
+        );",
+    ),
+    // Fechas de vencimiento de lo comprado
+    M::up(
+        "CREATE TABLE IF NOT EXISTS vencimientos (
+            id INTEGER PRIMARY KEY,
+            nombre TEXT NOT NULL,
+            unidad TEXT NOT NULL,
+            cantidad REAL NOT NULL,
+            vence INTEGER NOT NULL,
+            id_lista INTEGER,
+            reponer INTEGER NOT NULL DEFAULT 0
+        );",
+    ),
+    // Recetas, con sus ingredientes en orden
+    M::up(
+        "CREATE TABLE IF NOT EXISTS recetas (
+            id INTEGER PRIMARY KEY,
+            nombre TEXT NOT NULL,
+            porciones INTEGER NOT NULL DEFAULT 1
+        );
+        CREATE TABLE IF NOT EXISTS recetas_ingredientes (
+            id_receta INTEGER NOT NULL,
+            posicion INTEGER NOT NULL,
+            nombre TEXT NOT NULL,
+            unidad TEXT NOT NULL,
+            cantidad REAL NOT NULL,
+            PRIMARY KEY (id_receta, posicion)
+        );",
+    ),
+    // Menú: qué receta se prepara cada día
+    M::up(
+        "CREATE TABLE IF NOT EXISTS menu (
+            id INTEGER PRIMARY KEY,
+            dia INTEGER NOT NULL,
+            momento INTEGER NOT NULL DEFAULT 1,
+            id_receta INTEGER NOT NULL,
+            porciones INTEGER NOT NULL DEFAULT 1
+        );",
+    ),
+    // Historial de compras, para estimar cada cuánto se compra cada producto
+    M::up(
+        "CREATE TABLE IF NOT EXISTS compras (
+            id INTEGER PRIMARY KEY,
+            nombre TEXT NOT NULL,
+            unidad TEXT NOT NULL,
+            cantidad REAL NOT NULL,
+            dia INTEGER NOT NULL,
+            id_lista INTEGER NOT NULL
+        );",
+    ),
+    // Sinónimos de productos, con algunos comunes en inglés y español
+    M::up(
+        "CREATE TABLE IF NOT EXISTS sinonimos (
+            id INTEGER PRIMARY KEY,
+            alias TEXT NOT NULL,
+            canonico TEXT NOT NULL
+        );
+        INSERT INTO sinonimos (alias, canonico) VALUES
+            ('eggs', 'huevos'),
+            ('milk', 'leche'),
+            ('bread', 'pan'),
+            ('rice', 'arroz'),
+            ('sugar', 'azúcar'),
+            ('salt', 'sal'),
+            ('butter', 'mantequilla'),
+            ('cheese', 'queso'),
+            ('chicken', 'pollo'),
+            ('potatoes', 'papas'),
+            ('tomatoes', 'tomates'),
+            ('apples', 'manzanas'),
+            ('coffee', 'café'),
+            ('flour', 'harina');",
+    ),
+    // Código de barras de cada item (se sincroniza, así que se recrean los triggers de items)
+    M::up(concat!(
+        "ALTER TABLE items ADD COLUMN codigo_barras TEXT NOT NULL DEFAULT '';
+        CREATE INDEX IF NOT EXISTS items_codigo_barras ON items (codigo_barras);
+",
+        triggers_crdt_items!(
+            "nombre",
+            "unidad",
+            "cantidad_requerida",
+            "cantidad_comprada",
+            "precio",
+            "precio_incluye_impuesto",
+            "notas",
+            "prioridad",
+            "categoria",
+            "codigo_barras",
+        )
+    )),
+    // Las tasas por categoría también se sincronizan por la red local
+    M::up(concat!(
+        avanzar_reloj!(),
+        "
+        INSERT OR REPLACE INTO crdt_registros (uid, tabla, campo, valor, modificado, dispositivo, cambio_local)
+        SELECT listas.uid, 'listas', 'tasa_categoria:' || tasas_categoria.categoria, json_quote(tasas_categoria.tasa_impuesto), (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj)
+        FROM tasas_categoria JOIN listas ON listas.id = tasas_categoria.id_lista;"
+    )),
+];
+
+// Cantidad de respaldos automáticos que se conservan
+const MAXIMO_RESPALDOS: usize = 5;
+
+// Las imágenes serializadas de una base en modo WAL no se pueden abrir en memoria,
+// así que se marcan como de journal clásico (bytes 18 y 19 del encabezado)
+fn sin_wal(datos: &mut [u8]) {
+    if datos.len() > 19 && datos[18] == 2 && datos[19] == 2 {
+        datos[18] = 1;
+        datos[19] = 1;
+    }
 }
 
 pub struct SQLiteConnector {
     connection: rusqlite::Connection,
+    archivo: PathBuf,
+    directorio: PathBuf,
+}
+
+impl Default for SQLiteConnector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SQLiteConnector {
     pub fn new() -> Self {
-        let migrations_slice: &[M<'_>] = &[
-            M::up(
-                "CREATE TABLE IF NOT EXISTS listas (
-                id INTEGER PRIMARY KEY,
-                nombre TEXT NOT NULL
-            );
-            CREATE TABLE IF NOT EXISTS items (
-                id INTEGER PRIMARY KEY,
-                id_lista INTEGER,
-                nombre TEXT NOT NULL,
-                unidad TEXT,
-                cantidad_requerida FLOAT,
-                cantidad_comprada FLOAT,
-                precio FLOAT
-                );",
-            ),
-            M::up("ALTER TABLE listas ADD COLUMN modo_simple INTEGER DEFAULT 0;"),
-        ];
-        let migrations: Migrations<'_> = Migrations::from_slice(migrations_slice);
-
         let db_path = SQLiteConnector::get_db_path();
 
-        // Open the database from the persisted "hotdog.db" file
-        let mut conn = rusqlite::Connection::open(format!("{db_path}/shopping_list.db"))
-            .expect("Failed to open database");
+        let conector = SQLiteConnector::with_path(&Path::new(&db_path).join("shopping_list.db"));
+
+        // Take a snapshot on every startup, keeping only the most recent ones
+        _ = conector.rotate_backups();
+
+        conector
+    }
+
+    pub fn with_path(path: &Path) -> Self {
+        // Open the database from the persisted file
+        let mut conn = rusqlite::Connection::open(path).expect("Failed to open database");
 
         // Apply some PRAGMA, often better to do it outside of migrations
         conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))
             .unwrap();
 
         // Update the database schema, atomically
-        migrations.to_latest(&mut conn).unwrap();
+        Migrations::from_slice(MIGRACIONES)
+            .to_latest(&mut conn)
+            .unwrap();
 
         // Return the connection
-        SQLiteConnector { connection: conn }
+        SQLiteConnector {
+            connection: conn,
+            archivo: path.to_path_buf(),
+            directorio: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        }
+    }
+
+    // Guarda una copia completa de la base de datos en un solo archivo
+    pub fn backup_to(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let mut datos = self.connection.serialize(MAIN_DB)?.to_vec();
+        sin_wal(&mut datos);
+        if let Some(directorio) = path.parent() {
+            std::fs::create_dir_all(directorio)?;
+        }
+        let temporal = path.with_extension("tmp");
+        std::fs::write(&temporal, &datos)?;
+        std::fs::rename(&temporal, path)?;
+        Ok(())
+    }
+
+    pub fn restore_from(&self, path: &Path) -> Result<(), anyhow::Error> {
+        self.restore_from_bytes(&std::fs::read(path)?)
+    }
+
+    // Reemplaza todo el contenido de la base de datos con el de un respaldo.
+    // El respaldo se migra primero en memoria, así que puede venir de una versión anterior.
+    pub fn restore_from_bytes(&self, datos: &[u8]) -> Result<(), anyhow::Error> {
+        let mut datos = datos.to_vec();
+        sin_wal(&mut datos);
+        let mut respaldo = rusqlite::Connection::open_in_memory()?;
+        respaldo.deserialize_read_exact(MAIN_DB, &datos[..], datos.len(), false)?;
+
+        let tiene_listas: bool = respaldo.query_row(
+            "SELECT count(*) = 1 FROM sqlite_master WHERE type = 'table' AND name = 'listas';",
+            [],
+            |row| row.get(0),
+        )?;
+        if !tiene_listas {
+            anyhow::bail!("The file is not a shopping list backup");
+        }
+        let version: usize = respaldo.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > MIGRACIONES.len() {
+            anyhow::bail!(
+                "The backup was made by a newer version of the app (schema {version}, this version supports up to {})",
+                MIGRACIONES.len()
+            );
+        }
+        Migrations::from_slice(MIGRACIONES).to_latest(&mut respaldo)?;
+
+        let temporal = self.directorio.join("restore.tmp.db");
+        _ = std::fs::remove_file(&temporal);
+        respaldo.execute("VACUUM INTO ?1;", [temporal.to_string_lossy()])?;
+        drop(respaldo);
+
+        let result = self.copy_from(&temporal);
+        _ = std::fs::remove_file(&temporal);
+        result
+    }
+
+    // Copia todas las tablas de otra base de datos con el mismo esquema
+    fn copy_from(&self, path: &Path) -> Result<(), anyhow::Error> {
+        // El respaldo puede venir de otro dispositivo, pero este conserva su identidad, su
+        // emparejamiento y su reloj de sincronización
+        let dispositivo = self.device_id()?;
+        let codigo = self.get_setting(CLAVE_CODIGO_SYNC)?;
+        let (hora, secuencia): (i64, i64) =
+            self.connection
+                .query_row("SELECT hora, secuencia FROM sync_reloj;", [], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })?;
+        self.connection
+            .execute("ATTACH DATABASE ?1 AS respaldo;", [path.to_string_lossy()])?;
+        let result = (|| -> Result<(), anyhow::Error> {
+            let transaccion = self.connection.unchecked_transaction()?;
+            // La tabla de búsqueda se mantiene sola con los triggers de items
+            let tablas = transaccion
+                .prepare(
+                    "SELECT name FROM main.sqlite_master WHERE type = 'table'
+                    AND name NOT LIKE 'sqlite_%' AND name NOT LIKE 'items_fts%';",
+                )?
+                .query_map([], |row| row.get::<usize, String>(0))?
+                .collect::<Result<Vec<String>, rusqlite::Error>>()?;
+            // Primero se vacían todas, porque los triggers de eliminación escriben en otras tablas
+            for tabla in &tablas {
+                transaccion.execute(&format!("DELETE FROM main.{tabla};"), [])?;
+            }
+            for tabla in &tablas {
+                transaccion.execute(
+                    &format!("INSERT INTO main.{tabla} SELECT * FROM respaldo.{tabla};"),
+                    [],
+                )?;
+            }
+            // Al vaciar las tablas se registran como eliminadas filas que el respaldo recupera
+            transaccion.execute(
+                "DELETE FROM main.eliminados
+                WHERE uid IN (SELECT uid FROM main.listas UNION ALL SELECT uid FROM main.items);",
+                [],
+            )?;
+            transaccion.execute(
+                "INSERT OR REPLACE INTO main.configuracion (clave, valor) VALUES ('id_dispositivo', ?1);",
+                [dispositivo],
+            )?;
+            match codigo {
+                Some(codigo) => transaccion.execute(
+                    "INSERT OR REPLACE INTO main.configuracion (clave, valor) VALUES (?1, ?2);",
+                    params![CLAVE_CODIGO_SYNC, codigo],
+                )?,
+                None => transaccion.execute(
+                    "DELETE FROM main.configuracion WHERE clave = ?1;",
+                    [CLAVE_CODIGO_SYNC],
+                )?,
+            };
+            // Lo restaurado se vuelve a enviar a todos los pares
+            transaccion.execute(
+                "UPDATE main.sync_reloj SET hora = max(hora, ?1), secuencia = max(secuencia, ?2) + 1;",
+                params![hora, secuencia],
+            )?;
+            transaccion.execute("DELETE FROM main.sync_pares;", [])?;
+            transaccion.commit()?;
+            Ok(())
+        })();
+        self.connection.execute("DETACH DATABASE respaldo;", [])?;
+        result
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directorio
+    }
+
+    pub fn current_member(&self) -> Option<usize> {
+        self.get_setting(CLAVE_MIEMBRO_ACTUAL)
+            .ok()
+            .flatten()
+            .and_then(|valor| valor.parse().ok())
+    }
+
+    fn list_assignees(&self, id_lista: usize) -> Result<Vec<usize>, anyhow::Error> {
+        Ok(self
+            .connection
+            .prepare_cached(
+                "SELECT id_miembro FROM listas_asignados WHERE id_lista = ?1 ORDER BY id_miembro;",
+            )?
+            .query_map([id_lista], |row| row.get(0))?
+            .collect::<Result<Vec<usize>, rusqlite::Error>>()?)
+    }
+
+    pub fn rotate_backups(&self) -> Result<(), anyhow::Error> {
+        self.create_backup()?;
+        let directorio = directorio_respaldos(&self.directorio);
+        for respaldo in listar_respaldos(&directorio)?
+            .into_iter()
+            .skip(MAXIMO_RESPALDOS)
+        {
+            std::fs::remove_file(directorio.join(respaldo.nombre))?;
+        }
+        Ok(())
     }
 
     #[allow(unused)]
@@ -100,14 +825,15 @@ impl SQLiteConnector {
 }
 impl DBConnector for SQLiteConnector {
     // Operaciones con listas
-    fn create_new_list(&self, nombre: String) -> Result<(), anyhow::Error> {
-        if !nombre.trim().is_empty() {
-            self.connection.execute(
-                "INSERT INTO listas (nombre, modo_simple) VALUES (?1, 0)",
-                [nombre],
-            )?;
+    fn create_new_list(&self, nombre: String) -> Result<usize, anyhow::Error> {
+        if nombre.trim().is_empty() {
+            return Ok(0);
         }
-        Ok(())
+        self.connection.execute(
+            "INSERT INTO listas (nombre, modo_simple, id_propietario) VALUES (?1, 0, ?2)",
+            params![nombre, self.current_member()],
+        )?;
+        Ok(self.connection.last_insert_rowid() as usize)
     }
 
     fn update_list(
@@ -115,7 +841,7 @@ impl DBConnector for SQLiteConnector {
         id: usize,
         nombre: String,
         modo_simple: usize,
-    ) -> Result<(), rusqlite::Error> {
+    ) -> Result<(), anyhow::Error> {
         if !nombre.trim().is_empty() {
             self.connection.execute(
                 "UPDATE listas SET nombre=?1, modo_simple=?2 WHERE id = ?3;",
@@ -126,13 +852,78 @@ impl DBConnector for SQLiteConnector {
         Ok(())
     }
 
+    fn update_list_tax_rate(&self, id: usize, tasa_impuesto: f32) -> Result<(), anyhow::Error> {
+        self.connection.execute(
+            "UPDATE listas SET tasa_impuesto=?1 WHERE id = ?2;",
+            params![validar_tasa(tasa_impuesto)?, id],
+        )?;
+        Ok(())
+    }
+
+    fn set_category_tax_rate(
+        &self,
+        id_lista: usize,
+        categoria: String,
+        tasa_impuesto: Option<f32>,
+    ) -> Result<(), anyhow::Error> {
+        let categoria = categoria.trim();
+        if categoria.is_empty() {
+            return Ok(());
+        }
+        let tasa_impuesto = tasa_impuesto.map(validar_tasa).transpose()?;
+        let transaccion = self.connection.unchecked_transaction()?;
+        match tasa_impuesto {
+            Some(tasa_impuesto) => self.connection.execute(
+                "INSERT OR REPLACE INTO tasas_categoria (id_lista, categoria, tasa_impuesto) VALUES (?1, ?2, ?3);",
+                params![id_lista, categoria, tasa_impuesto],
+            )?,
+            None => self.connection.execute(
+                "DELETE FROM tasas_categoria WHERE id_lista = ?1 AND categoria = ?2;",
+                params![id_lista, categoria],
+            )?,
+        };
+        // Sin triggers, porque al aplicar los cambios de otro dispositivo también se escribe la
+        // tabla y eso no es una modificación hecha aquí
+        self.connection.execute_batch(avanzar_reloj!())?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO crdt_registros (uid, tabla, campo, valor, modificado, dispositivo, cambio_local)
+            SELECT uid, 'listas', ?2, ?3, (SELECT hora FROM sync_reloj), (SELECT valor FROM configuracion WHERE clave = 'id_dispositivo'), (SELECT secuencia FROM sync_reloj)
+            FROM listas WHERE id = ?1;",
+            params![
+                id_lista,
+                format!("{CAMPO_TASA_CATEGORIA}{categoria}"),
+                serde_json::to_string(&tasa_impuesto)?
+            ],
+        )?;
+        transaccion.commit()?;
+        Ok(())
+    }
+
     fn get_list_of_lists(&self) -> Result<Vec<Lista>, anyhow::Error> {
+        // Los asignados de todas las listas en una sola consulta
+        let mut asignados: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for fila in self
+            .connection
+            .prepare_cached(
+                "SELECT id_lista, id_miembro FROM listas_asignados ORDER BY id_lista, id_miembro;",
+            )?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        {
+            let (id_lista, id_miembro) = fila?;
+            asignados.entry(id_lista).or_default().push(id_miembro);
+        }
         let result = self
             .connection
-            .prepare("SELECT id, nombre, modo_simple FROM listas ORDER BY nombre;")
+            .prepare("SELECT id, nombre, modo_simple, tasa_impuesto, id_propietario FROM listas ORDER BY nombre;")
             .unwrap()
             .query_map([], |row| {
-                Ok((row.get(0)?, row.get(1)?, row.get::<usize, usize>(2)?))
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get::<usize, usize>(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
             })
             .unwrap()
             .map(|r| {
@@ -141,8 +932,14 @@ impl DBConnector for SQLiteConnector {
                     id: row.0,
                     nombre: row.1,
                     items: None,
+                    subtotal: 0.0,
+                    impuesto: 0.0,
                     total: 0.0,
                     modo_simple: row.2 == 1,
+                    tasa_impuesto: row.3,
+                    propietario: row.4,
+                    asignados: asignados.remove(&row.0).unwrap_or_default(),
+                    tasas_categoria: BTreeMap::new(),
                 }
             })
             .collect();
@@ -152,93 +949,1470 @@ impl DBConnector for SQLiteConnector {
     fn get_list(&self, id_lista: usize) -> Result<Lista, anyhow::Error> {
         let result = self
             .connection
-            .prepare("SELECT id, nombre, modo_simple FROM listas WHERE id = (?1);")
+            .prepare("SELECT id, nombre, modo_simple, tasa_impuesto, id_propietario FROM listas WHERE id = (?1);")
             .unwrap()
             .query_row([id_lista], |row| {
-                Ok((row.get(0)?, row.get(1)?, row.get::<usize, usize>(2)?))
-            })
-            .unwrap();
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get::<usize, usize>(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })?;
         let mut final_list = Lista {
             id: result.0,
             nombre: result.1,
             items: None,
+            subtotal: 0.0,
+            impuesto: 0.0,
             total: 0.0,
             modo_simple: result.2 == 1,
+            tasa_impuesto: result.3,
+            propietario: result.4,
+            asignados: self.list_assignees(id_lista)?,
+            tasas_categoria: self
+                .connection
+                .prepare(
+                    "SELECT categoria, tasa_impuesto FROM tasas_categoria WHERE id_lista = ?1;",
+                )?
+                .query_map([id_lista], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<BTreeMap<String, f32>, rusqlite::Error>>()?,
         };
-        let mut result: Vec<Item> = self.connection.prepare("SELECT id, nombre, unidad, cantidad_requerida, cantidad_comprada, precio FROM items WHERE id_lista = (?1);")
+        let mut result: Vec<Item> = self
+            .connection
+            .prepare(&format!(
+                "SELECT {COLUMNAS_ITEM} FROM items WHERE id_lista = (?1);"
+            ))
             .unwrap()
-            .query_map([id_lista], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)))
+            .query_map([id_lista], leer_item)
             .unwrap()
-            .map(|r| {
-                let row = r.unwrap();
-                Item {
-                    id: row.0,
-                    id_lista,
-                    nombre: row.1,
-                    unidad: row.2,
-                    cantidad_requerida: row.3,
-                    cantidad_comprada: row.4,
-                    precio: row.5,
-                }
-            })
-            .collect()
-            ;
+            .map(|r| r.unwrap())
+            .collect();
         result.sort_by(|a, b| {
-            if a.cantidad_comprada < 0.001 && b.cantidad_comprada < 0.001
-                || a.cantidad_comprada >= 0.001 && b.cantidad_comprada >= 0.001
-            {
-                return a.nombre.cmp(&b.nombre);
-            }
-            if a.cantidad_comprada < 0.001 && b.cantidad_comprada >= 0.001 {
-                std::cmp::Ordering::Less
-            } else {
-                std::cmp::Ordering::Equal
-            }
+            a.comprado()
+                .cmp(&b.comprado())
+                .then(a.prioridad.cmp(&b.prioridad))
+                .then(a.nombre.cmp(&b.nombre))
         });
         final_list.items = Some(result);
-        final_list.total = final_list
-            .clone()
-            .items
-            .unwrap()
-            .iter()
-            .fold(0.0, |acc, item| acc + item.cantidad_comprada * item.precio);
+        final_list.calcular_totales();
         Ok(final_list)
     }
 
     fn delete_list(&self, id_lista: usize) -> Result<(), anyhow::Error> {
+        self.connection.execute(
+            "DELETE FROM imagenes_items WHERE id_item IN (SELECT id FROM items WHERE id_lista = ?1);",
+            [id_lista],
+        )?;
         self.connection
             .execute("DELETE FROM items WHERE id_lista = ?1;", [id_lista])?;
+        self.connection.execute(
+            "DELETE FROM listas_asignados WHERE id_lista = ?1;",
+            [id_lista],
+        )?;
+        self.connection.execute(
+            "DELETE FROM tasas_categoria WHERE id_lista = ?1;",
+            [id_lista],
+        )?;
         self.connection
             .execute("DELETE FROM listas WHERE id = ?1;", [id_lista])?;
         Ok(())
     }
 
     // Operaciones con items
-    fn create_new_list_item(&self, id_lista: usize, item: Item) -> Result<(), anyhow::Error> {
-        if !item.nombre.trim().is_empty() {
-            self.connection.execute("INSERT INTO items (id_lista, nombre, unidad, cantidad_requerida, cantidad_comprada, precio) VALUES (?1, ?2, ?3, ?4, ?5, ?6);", params![id_lista, item.nombre, item.unidad, item.cantidad_requerida, item.cantidad_comprada, item.precio])?;
+    fn create_new_list_item(&self, id_lista: usize, item: Item) -> Result<usize, anyhow::Error> {
+        if item.nombre.trim().is_empty() {
+            return Ok(0);
         }
-        Ok(())
+        let agregado_por = item.agregado_por.or(self.current_member());
+        let comprado_por = if item.comprado() {
+            item.comprado_por.or(agregado_por)
+        } else {
+            None
+        };
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute("INSERT INTO items (id_lista, nombre, unidad, cantidad_requerida, cantidad_comprada, precio, precio_incluye_impuesto, notas, prioridad, agregado_por, comprado_por, categoria, asignado_a, codigo_barras) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14);", params![id_lista, item.nombre, item.unidad, item.cantidad_requerida, item.cantidad_comprada, item.precio, item.precio_incluye_impuesto, item.notas, item.prioridad as usize, agregado_por, comprado_por, item.categoria.trim(), item.asignado_a, item.codigo_barras])?;
+        let id = self.connection.last_insert_rowid() as usize;
+        self.add_to_pantry(&item.nombre, &item.unidad, item.cantidad_comprada)?;
+        self.record_purchase(id_lista, &item.nombre, &item.unidad, item.cantidad_comprada)?;
+        transaccion.commit()?;
+        Ok(id)
     }
 
     fn update_list_item(&self, item: Item) -> Result<(), anyhow::Error> {
         if !item.nombre.trim().is_empty() {
-            self.connection.execute("UPDATE items SET nombre=?1, unidad=?2, cantidad_requerida=?3, cantidad_comprada=?4, precio=?5 WHERE id = ?6;", params![item.nombre, item.unidad, item.cantidad_requerida, item.cantidad_comprada, item.precio, item.id])?;
+            // Inmediata: si empezara leyendo no esperaría a que otra conexión suelte la base
+            let transaccion =
+                Transaction::new_unchecked(&self.connection, TransactionBehavior::Immediate)?;
+            // Si el item ya no existe, QueryReturnedNoRows hace que el servidor responda 404
+            let (comprado_antes, id_lista): (f32, usize) = self.connection.query_row(
+                "SELECT cantidad_comprada, id_lista FROM items WHERE id = ?1;",
+                [item.id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            // Quien compró el item se conserva hasta que se desmarque
+            self.connection.execute("UPDATE items SET nombre=?1, unidad=?2, cantidad_requerida=?3, cantidad_comprada=?4, precio=?5, precio_incluye_impuesto=?6, notas=?7, prioridad=?8, categoria=?12, asignado_a=?13, codigo_barras=?14,
+                comprado_por = CASE WHEN ?4 >= 0.001 THEN coalesce(comprado_por, ?10, ?11) END
+                WHERE id = ?9;", params![item.nombre, item.unidad, item.cantidad_requerida, item.cantidad_comprada, item.precio, item.precio_incluye_impuesto, item.notas, item.prioridad as usize, item.id, item.comprado_por, self.current_member(), item.categoria.trim(), item.asignado_a, item.codigo_barras])?;
+            self.add_to_pantry(
+                &item.nombre,
+                &item.unidad,
+                item.cantidad_comprada - comprado_antes,
+            )?;
+            self.record_purchase(
+                id_lista,
+                &item.nombre,
+                &item.unidad,
+                item.cantidad_comprada - comprado_antes,
+            )?;
+            transaccion.commit()?;
         }
         Ok(())
     }
 
     fn delete_item(&self, id: usize) -> Result<(), anyhow::Error> {
-        self.connection
-            .execute("DELETE FROM items WHERE id = ?1;", [id])?;
+        self.delete_item_image(id)?;
+        if self
+            .connection
+            .execute("DELETE FROM items WHERE id = ?1;", [id])?
+            == 0
+        {
+            return Err(rusqlite::Error::QueryReturnedNoRows.into());
+        }
         Ok(())
     }
 
     fn clear_list_items(&self, id_lista: usize) -> Result<(), anyhow::Error> {
         self.connection.execute(
-            "UPDATE items SET cantidad_comprada=?1 WHERE id_lista = ?2",
+            "UPDATE items SET cantidad_comprada=?1, comprado_por=NULL WHERE id_lista = ?2",
             params![0.0, id_lista],
         )?;
         Ok(())
     }
+
+    // Operaciones con imágenes de items
+    fn set_item_image(&self, id_item: usize, imagen: Imagen) -> Result<(), anyhow::Error> {
+        let miniatura = imagen.miniatura()?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO imagenes_items (id_item, tipo, imagen, tipo_miniatura, miniatura) VALUES (?1, ?2, ?3, ?4, ?5);",
+            params![id_item, imagen.tipo, imagen.datos, miniatura.tipo, miniatura.datos],
+        )?;
+        Ok(())
+    }
+
+    fn get_item_thumbnail(&self, id_item: usize) -> Result<Option<Imagen>, anyhow::Error> {
+        let result = self
+            .connection
+            .query_row(
+                "SELECT tipo_miniatura, miniatura FROM imagenes_items WHERE id_item = ?1;",
+                [id_item],
+                |row| {
+                    Ok(Imagen {
+                        tipo: row.get(0)?,
+                        datos: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(result)
+    }
+
+    fn get_item_image(&self, id_item: usize) -> Result<Option<Imagen>, anyhow::Error> {
+        let result = self
+            .connection
+            .query_row(
+                "SELECT tipo, imagen FROM imagenes_items WHERE id_item = ?1;",
+                [id_item],
+                |row| {
+                    Ok(Imagen {
+                        tipo: row.get(0)?,
+                        datos: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(result)
+    }
+
+    fn delete_item_image(&self, id_item: usize) -> Result<(), anyhow::Error> {
+        self.connection
+            .execute("DELETE FROM imagenes_items WHERE id_item = ?1;", [id_item])?;
+        Ok(())
+    }
+
+    // Búsqueda de items en todas las listas
+    fn search_items(&self, texto: String) -> Result<Vec<ResultadoBusqueda>, anyhow::Error> {
+        // Cada palabra se busca como prefijo, y todas deben aparecer en el nombre. Si lo buscado
+        // es un producto con sinónimos, se buscan también los otros nombres.
+        let consulta = Sinonimos::desde(&self.get_synonyms()?)
+            .nombres(&texto)
+            .iter()
+            .map(|nombre| {
+                nombre
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|palabra| !palabra.is_empty())
+                    .map(|palabra| format!("\"{palabra}\"*"))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .filter(|consulta| !consulta.is_empty())
+            .map(|consulta| format!("({consulta})"))
+            .collect::<Vec<String>>()
+            .join(" OR ");
+        if consulta.is_empty() {
+            return Ok(Vec::new());
+        }
+        let result = self
+            .connection
+            .prepare(&format!(
+                "SELECT {COLUMNAS_ITEM}, listas.nombre FROM items_fts
+                JOIN items ON items.id = items_fts.rowid
+                JOIN listas ON listas.id = items.id_lista
+                WHERE items_fts MATCH ?1
+                ORDER BY items_fts.rank, listas.nombre;"
+            ))?
+            .query_map([consulta], |row| {
+                Ok(ResultadoBusqueda {
+                    item: leer_item(row)?,
+                    nombre_lista: row.get(TOTAL_COLUMNAS_ITEM)?,
+                })
+            })?
+            .collect::<Result<Vec<ResultadoBusqueda>, rusqlite::Error>>()?;
+        Ok(result)
+    }
+
+    fn find_item_by_barcode(&self, codigo: &str) -> Result<Option<Item>, anyhow::Error> {
+        Ok(self
+            .connection
+            .query_row(
+                &format!(
+                    "SELECT {COLUMNAS_ITEM} FROM items WHERE codigo_barras = ?1 ORDER BY id DESC LIMIT 1;"
+                ),
+                [normalizar_codigo(codigo)],
+                leer_item,
+            )
+            .optional()?)
+    }
+
+    // Respaldos de la base de datos
+    fn create_backup(&self) -> Result<Respaldo, anyhow::Error> {
+        let directorio = directorio_respaldos(&self.directorio);
+        // Si ya hay un respaldo de ese segundo se usa el siguiente, para no sobrescribirlo
+        let mut fecha = ahora();
+        while directorio.join(nombre_respaldo(fecha)).exists() {
+            fecha += 1;
+        }
+        let nombre = nombre_respaldo(fecha);
+        let path = directorio.join(&nombre);
+        self.backup_to(&path)?;
+        Ok(Respaldo {
+            nombre,
+            fecha,
+            tamano: std::fs::metadata(&path)?.len(),
+        })
+    }
+
+    fn list_backups(&self) -> Result<Vec<Respaldo>, anyhow::Error> {
+        listar_respaldos(&directorio_respaldos(&self.directorio))
+    }
+
+    fn restore_backup(&self, nombre: String) -> Result<(), anyhow::Error> {
+        // Sólo los respaldos de este directorio, aunque el nombre venga de afuera
+        if !self
+            .list_backups()?
+            .iter()
+            .any(|respaldo| respaldo.nombre == nombre)
+        {
+            return Err(
+                std::io::Error::new(std::io::ErrorKind::NotFound, "Backup not found").into(),
+            );
+        }
+        let path = directorio_respaldos(&self.directorio).join(nombre);
+        let datos = std::fs::read(path)?;
+        self.restore_backup_data(datos)
+    }
+
+    fn restore_backup_data(&self, datos: Vec<u8>) -> Result<(), anyhow::Error> {
+        // Respaldo del estado actual, por si hay que deshacer la restauración
+        self.create_backup()?;
+        self.restore_from_bytes(&datos)
+    }
+
+    // Configuración
+    fn get_setting(&self, clave: &str) -> Result<Option<String>, anyhow::Error> {
+        Ok(self
+            .connection
+            .query_row(
+                "SELECT valor FROM configuracion WHERE clave = ?1;",
+                [clave],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn set_setting(&self, clave: &str, valor: &str) -> Result<(), anyhow::Error> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO configuracion (clave, valor) VALUES (?1, ?2);",
+            [clave, valor],
+        )?;
+        Ok(())
+    }
+
+    // Miembros del hogar
+    fn get_members(&self) -> Result<Vec<Miembro>, anyhow::Error> {
+        Ok(self
+            .connection
+            .prepare("SELECT id, nombre FROM miembros ORDER BY nombre;")?
+            .query_map([], |row| {
+                Ok(Miembro {
+                    id: row.get(0)?,
+                    nombre: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<Miembro>, rusqlite::Error>>()?)
+    }
+
+    fn create_member(&self, nombre: String) -> Result<usize, anyhow::Error> {
+        if nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        self.connection.execute(
+            "INSERT INTO miembros (nombre) VALUES (?1);",
+            [nombre.trim()],
+        )?;
+        Ok(self.connection.last_insert_rowid() as usize)
+    }
+
+    fn rename_member(&self, id: usize, nombre: String) -> Result<(), anyhow::Error> {
+        if !nombre.trim().is_empty() {
+            self.connection.execute(
+                "UPDATE miembros SET nombre = ?1 WHERE id = ?2;",
+                params![nombre.trim(), id],
+            )?;
+        }
+        Ok(())
+    }
+
+    // Las listas e items del miembro quedan sin atribuir. Sus gastos se conservan para no alterar
+    // los saldos de los demás
+    fn delete_member(&self, id: usize) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute(
+            "UPDATE listas SET id_propietario = NULL WHERE id_propietario = ?1;",
+            [id],
+        )?;
+        self.connection.execute(
+            "UPDATE items SET agregado_por = NULL WHERE agregado_por = ?1;",
+            [id],
+        )?;
+        self.connection.execute(
+            "UPDATE items SET comprado_por = NULL WHERE comprado_por = ?1;",
+            [id],
+        )?;
+        self.connection.execute(
+            "UPDATE items SET asignado_a = NULL WHERE asignado_a = ?1;",
+            [id],
+        )?;
+        self.connection
+            .execute("DELETE FROM listas_asignados WHERE id_miembro = ?1;", [id])?;
+        self.connection
+            .execute("DELETE FROM miembros WHERE id = ?1;", [id])?;
+        if self.current_member() == Some(id) {
+            self.connection.execute(
+                "DELETE FROM configuracion WHERE clave = ?1;",
+                [CLAVE_MIEMBRO_ACTUAL],
+            )?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    fn set_list_owner(
+        &self,
+        id_lista: usize,
+        propietario: Option<usize>,
+    ) -> Result<(), anyhow::Error> {
+        self.connection.execute(
+            "UPDATE listas SET id_propietario = ?1 WHERE id = ?2;",
+            params![propietario, id_lista],
+        )?;
+        Ok(())
+    }
+
+    fn set_list_assignees(
+        &self,
+        id_lista: usize,
+        asignados: Vec<usize>,
+    ) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute(
+            "DELETE FROM listas_asignados WHERE id_lista = ?1;",
+            [id_lista],
+        )?;
+        for id_miembro in asignados {
+            self.connection.execute(
+                "INSERT OR IGNORE INTO listas_asignados (id_lista, id_miembro) VALUES (?1, ?2);",
+                [id_lista, id_miembro],
+            )?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    // Gastos compartidos, del más reciente al más antiguo
+    fn get_expenses(&self) -> Result<Vec<Gasto>, anyhow::Error> {
+        let mut gastos = self
+            .connection
+            .prepare(
+                "SELECT id, id_lista, descripcion, fecha, pagado_por, liquidacion FROM gastos
+                ORDER BY fecha DESC, id DESC;",
+            )?
+            .query_map([], |row| {
+                Ok(Gasto {
+                    id: row.get(0)?,
+                    id_lista: row.get(1)?,
+                    descripcion: row.get(2)?,
+                    fecha: row.get(3)?,
+                    pagado_por: row.get(4)?,
+                    cuotas: Vec::new(),
+                    liquidacion: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<Gasto>, rusqlite::Error>>()?;
+        let mut consulta = self.connection.prepare(
+            "SELECT id_miembro, monto FROM gastos_cuotas WHERE id_gasto = ?1 ORDER BY id_miembro;",
+        )?;
+        for gasto in gastos.iter_mut() {
+            gasto.cuotas = consulta
+                .query_map([gasto.id], |row| {
+                    Ok(Cuota {
+                        miembro: row.get(0)?,
+                        monto: row.get(1)?,
+                    })
+                })?
+                .collect::<Result<Vec<Cuota>, rusqlite::Error>>()?;
+        }
+        Ok(gastos)
+    }
+
+    fn record_expense(&self, gasto: Gasto) -> Result<usize, anyhow::Error> {
+        if gasto.pagado_por == 0 || gasto.cuotas.is_empty() {
+            anyhow::bail!("An expense needs a payer and at least one share");
+        }
+        let transaccion = self.connection.unchecked_transaction()?;
+        let id = self.store_expense(&Gasto { id: 0, ..gasto })?;
+        transaccion.commit()?;
+        Ok(id)
+    }
+
+    fn delete_expense(&self, id: usize) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection
+            .execute("DELETE FROM gastos_cuotas WHERE id_gasto = ?1;", [id])?;
+        self.connection
+            .execute("DELETE FROM gastos WHERE id = ?1;", [id])?;
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    // Despensa
+    fn get_pantry(&self) -> Result<Vec<Producto>, anyhow::Error> {
+        Ok(self
+            .connection
+            .prepare("SELECT id, nombre, unidad, cantidad, minimo FROM despensa ORDER BY nombre;")?
+            .query_map([], |row| {
+                Ok(Producto {
+                    id: row.get(0)?,
+                    nombre: row.get(1)?,
+                    unidad: row.get(2)?,
+                    cantidad: row.get(3)?,
+                    minimo: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<Producto>, rusqlite::Error>>()?)
+    }
+
+    fn save_pantry_item(&self, producto: Producto) -> Result<usize, anyhow::Error> {
+        if producto.nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        Ok(self.connection.query_row(
+            "INSERT INTO despensa (id, nombre, unidad, cantidad, minimo)
+            VALUES (nullif(?1, 0), ?2, ?3, max(?4, 0), max(?5, 0))
+            ON CONFLICT (id) DO UPDATE SET nombre=excluded.nombre, unidad=excluded.unidad,
+            cantidad=excluded.cantidad, minimo=excluded.minimo
+            RETURNING id;",
+            params![
+                producto.id,
+                producto.nombre.trim(),
+                producto.unidad,
+                producto.cantidad,
+                producto.minimo
+            ],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn delete_pantry_item(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.connection
+            .execute("DELETE FROM despensa WHERE id = ?1;", [id])?;
+        Ok(())
+    }
+
+    // Vencimientos, del más próximo al más lejano
+    fn get_expirations(&self) -> Result<Vec<Vencimiento>, anyhow::Error> {
+        Ok(self
+            .connection
+            .prepare(
+                "SELECT id, nombre, unidad, cantidad, vence, id_lista, reponer FROM vencimientos
+                ORDER BY vence, nombre;",
+            )?
+            .query_map([], |row| {
+                Ok(Vencimiento {
+                    id: row.get(0)?,
+                    nombre: row.get(1)?,
+                    unidad: row.get(2)?,
+                    cantidad: row.get(3)?,
+                    vence: row.get(4)?,
+                    id_lista: row.get(5)?,
+                    reponer: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<Vencimiento>, rusqlite::Error>>()?)
+    }
+
+    fn add_expiration(&self, vencimiento: Vencimiento) -> Result<usize, anyhow::Error> {
+        if vencimiento.nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        self.store_expiration(&Vencimiento {
+            id: 0,
+            ..vencimiento
+        })
+    }
+
+    fn delete_expiration(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.connection
+            .execute("DELETE FROM vencimientos WHERE id = ?1;", [id])?;
+        Ok(())
+    }
+
+    // Recetas
+    fn get_recipes(&self) -> Result<Vec<Receta>, anyhow::Error> {
+        let mut recetas = self
+            .connection
+            .prepare("SELECT id, nombre, porciones FROM recetas ORDER BY nombre;")?
+            .query_map([], |row| {
+                Ok(Receta {
+                    id: row.get(0)?,
+                    nombre: row.get(1)?,
+                    porciones: row.get(2)?,
+                    ingredientes: Vec::new(),
+                })
+            })?
+            .collect::<Result<Vec<Receta>, rusqlite::Error>>()?;
+        let mut consulta = self.connection.prepare(
+            "SELECT nombre, unidad, cantidad FROM recetas_ingredientes WHERE id_receta = ?1
+            ORDER BY posicion;",
+        )?;
+        for receta in recetas.iter_mut() {
+            receta.ingredientes = consulta
+                .query_map([receta.id], |row| {
+                    Ok(Ingrediente {
+                        nombre: row.get(0)?,
+                        unidad: row.get(1)?,
+                        cantidad: row.get(2)?,
+                    })
+                })?
+                .collect::<Result<Vec<Ingrediente>, rusqlite::Error>>()?;
+        }
+        Ok(recetas)
+    }
+
+    fn save_recipe(&self, receta: Receta) -> Result<usize, anyhow::Error> {
+        if receta.nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        let transaccion = self.connection.unchecked_transaction()?;
+        let id = self.store_recipe(&receta)?;
+        transaccion.commit()?;
+        Ok(id)
+    }
+
+    fn delete_recipe(&self, id: usize) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute(
+            "DELETE FROM recetas_ingredientes WHERE id_receta = ?1;",
+            [id],
+        )?;
+        self.connection
+            .execute("DELETE FROM menu WHERE id_receta = ?1;", [id])?;
+        self.connection
+            .execute("DELETE FROM recetas WHERE id = ?1;", [id])?;
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    // Menú
+    fn get_meals(&self) -> Result<Vec<Comida>, anyhow::Error> {
+        Ok(self
+            .connection
+            .prepare(
+                "SELECT id, dia, momento, id_receta, porciones FROM menu ORDER BY dia, momento;",
+            )?
+            .query_map([], |row| {
+                Ok(Comida {
+                    id: row.get(0)?,
+                    dia: row.get(1)?,
+                    momento: Momento::from(row.get::<usize, usize>(2)?),
+                    id_receta: row.get(3)?,
+                    porciones: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<Comida>, rusqlite::Error>>()?)
+    }
+
+    fn save_meal(&self, comida: Comida) -> Result<usize, anyhow::Error> {
+        self.store_meal(&comida)
+    }
+
+    fn delete_meal(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.connection
+            .execute("DELETE FROM menu WHERE id = ?1;", [id])?;
+        Ok(())
+    }
+
+    // Sinónimos
+    fn get_synonyms(&self) -> Result<Vec<Sinonimo>, anyhow::Error> {
+        Ok(self
+            .connection
+            .prepare("SELECT id, alias, canonico FROM sinonimos ORDER BY canonico, alias;")?
+            .query_map([], |row| {
+                Ok(Sinonimo {
+                    id: row.get(0)?,
+                    alias: row.get(1)?,
+                    canonico: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<Sinonimo>, rusqlite::Error>>()?)
+    }
+
+    fn save_synonym(&self, sinonimo: Sinonimo) -> Result<usize, anyhow::Error> {
+        if sinonimo.alias.trim().is_empty() || sinonimo.canonico.trim().is_empty() {
+            return Ok(0);
+        }
+        self.store_synonym(&sinonimo)
+    }
+
+    fn delete_synonym(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.connection
+            .execute("DELETE FROM sinonimos WHERE id = ?1;", [id])?;
+        Ok(())
+    }
+
+    fn get_product_names(&self) -> Result<Vec<String>, anyhow::Error> {
+        Ok(self
+            .connection
+            .prepare(
+                "SELECT nombre FROM items UNION SELECT nombre FROM despensa
+                UNION SELECT nombre FROM compras UNION SELECT alias FROM sinonimos
+                UNION SELECT canonico FROM sinonimos;",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, rusqlite::Error>>()?)
+    }
+
+    // Historial de compras
+    fn get_purchases(&self) -> Result<Vec<Compra>, anyhow::Error> {
+        Ok(self
+            .connection
+            .prepare(
+                "SELECT nombre, unidad, cantidad, dia, id_lista FROM compras ORDER BY dia, id;",
+            )?
+            .query_map([], |row| {
+                Ok(Compra {
+                    nombre: row.get(0)?,
+                    unidad: row.get(1)?,
+                    cantidad: row.get(2)?,
+                    dia: row.get(3)?,
+                    id_lista: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<Compra>, rusqlite::Error>>()?)
+    }
+
+    fn local_sync(&self) -> Option<&dyn LocalSync> {
+        Some(self)
+    }
+
+    fn server_connection(&self) -> Option<&dyn ServerConnection> {
+        None
+    }
+}
+
+impl LocalSync for SQLiteConnector {
+    fn device_id(&self) -> Result<String, anyhow::Error> {
+        Ok(self.connection.query_row(
+            "SELECT valor FROM configuracion WHERE clave = 'id_dispositivo';",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn start_sync_server(&self) -> Result<(), anyhow::Error> {
+        iniciar_servidor(self.archivo.clone())
+    }
+
+    fn stop_sync_server(&self) {
+        detener_servidor();
+    }
+
+    fn discover_peers(&self) -> Result<Vec<Par>, anyhow::Error> {
+        buscar_en_red(&self.device_id()?, &codigo_sync(self)?)
+    }
+
+    fn sync_with(&self, direccion: SocketAddr) -> Result<ResumenSync, anyhow::Error> {
+        sincronizar_con(self, direccion)
+    }
+}
+
+// Seguimiento de cambios para la sincronización
+impl SQLiteConnector {
+    pub fn peer_watermark(&self, dispositivo: &str) -> Result<i64, anyhow::Error> {
+        Ok(self
+            .connection
+            .query_row(
+                "SELECT enviado_hasta FROM sync_pares WHERE dispositivo = ?1;",
+                [dispositivo],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or_default())
+    }
+
+    pub fn set_peer_watermark(&self, dispositivo: &str, hasta: i64) -> Result<(), anyhow::Error> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO sync_pares (dispositivo, enviado_hasta) VALUES (?1, ?2);",
+            params![dispositivo, hasta],
+        )?;
+        Ok(())
+    }
+
+    // Registros y eliminaciones escritos en este dispositivo (o recibidos de otro) después de
+    // la secuencia `desde`, sin los que escribió el mismo par al que se envían. También devuelve
+    // la secuencia hasta la que se leyó, que es la nueva marca del par si la recibe
+    pub fn changes_since(
+        &self,
+        desde: i64,
+        par: &str,
+    ) -> Result<(CambiosSync, i64), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        let hasta: i64 =
+            self.connection
+                .query_row("SELECT secuencia FROM sync_reloj;", [], |row| row.get(0))?;
+        let registros = self
+            .connection
+            .prepare(
+                "SELECT uid, tabla, campo, valor, modificado, dispositivo FROM crdt_registros
+                WHERE cambio_local > ?1 AND cambio_local <= ?2 AND dispositivo != ?3;",
+            )?
+            .query_map(params![desde, hasta, par], leer_registro)?
+            .collect::<Result<Vec<Registro>, rusqlite::Error>>()?;
+        let eliminados = self
+            .connection
+            .prepare(
+                "SELECT uid, tabla, modificado, dispositivo FROM eliminados
+                WHERE cambio_local > ?1 AND cambio_local <= ?2 AND dispositivo != ?3;",
+            )?
+            .query_map(params![desde, hasta, par], leer_eliminado)?
+            .collect::<Result<Vec<Eliminado>, rusqlite::Error>>()?;
+        transaccion.commit()?;
+        Ok((
+            CambiosSync {
+                registros,
+                eliminados,
+            },
+            hasta,
+        ))
+    }
+
+    // Estado guardado de las listas e items indicados
+    fn replica(&self, uids: &BTreeSet<String>) -> Result<Replica, anyhow::Error> {
+        let mut replica = Replica::default();
+        for uid in uids {
+            let cambios = CambiosSync {
+                registros: self
+                    .connection
+                    .prepare_cached(
+                        "SELECT uid, tabla, campo, valor, modificado, dispositivo
+                        FROM crdt_registros WHERE uid = ?1;",
+                    )?
+                    .query_map([uid], leer_registro)?
+                    .collect::<Result<Vec<Registro>, rusqlite::Error>>()?,
+                eliminados: self
+                    .connection
+                    .prepare_cached(
+                        "SELECT uid, tabla, modificado, dispositivo FROM eliminados WHERE uid = ?1;",
+                    )?
+                    .query_map([uid], leer_eliminado)?
+                    .collect::<Result<Vec<Eliminado>, rusqlite::Error>>()?,
+            };
+            replica.fusionar(&cambios);
+        }
+        Ok(replica)
+    }
+
+    // Fusiona los cambios de otro dispositivo con el estado local, y actualiza las listas e
+    // items afectados con el resultado
+    pub fn apply_changes(&self, cambios: &CambiosSync) -> Result<(), anyhow::Error> {
+        // Inmediata, porque puede haber otra sincronización escribiendo al mismo tiempo
+        let transaccion =
+            Transaction::new_unchecked(&self.connection, TransactionBehavior::Immediate)?;
+        // El reloj no vuelve a quedar detrás de lo recibido. Se marcan con un número de secuencia
+        // nuevo para reenviarlos a otros pares
+        let recibida = cambios
+            .registros
+            .iter()
+            .map(|registro| registro.modificado)
+            .chain(
+                cambios
+                    .eliminados
+                    .iter()
+                    .map(|eliminado| eliminado.modificado),
+            )
+            .max()
+            .unwrap_or_default();
+        let cambio_local: i64 = self.connection.query_row(
+            "UPDATE sync_reloj SET hora = max(hora, ?1), secuencia = secuencia + 1 RETURNING secuencia;",
+            [recibida],
+            |row| row.get(0),
+        )?;
+        let local = self.replica(&cambios.uids())?;
+        let mut fusionada = local.clone();
+        fusionada.fusionar(cambios);
+
+        for (uid, eliminado) in &fusionada.eliminados {
+            if local.eliminados.get(uid) == Some(eliminado) {
+                continue;
+            }
+            if !local.eliminado(uid) {
+                self.delete_by_uid(&eliminado.tabla, uid)?;
+            }
+            self.connection.execute(
+                "INSERT OR REPLACE INTO eliminados (uid, tabla, modificado, dispositivo, cambio_local) VALUES (?1, ?2, ?3, ?4, ?5);",
+                params![uid, eliminado.tabla, eliminado.modificado, eliminado.dispositivo, cambio_local],
+            )?;
+        }
+
+        let mut listas = BTreeSet::new();
+        let mut items = BTreeSet::new();
+        for (clave, registro) in &fusionada.registros {
+            if fusionada.eliminado(&registro.uid) || local.registros.get(clave) == Some(registro) {
+                continue;
+            }
+            self.connection.execute(
+                "INSERT OR REPLACE INTO crdt_registros (uid, tabla, campo, valor, modificado, dispositivo, cambio_local) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7);",
+                params![registro.uid, registro.tabla, registro.campo, registro.valor, registro.modificado, registro.dispositivo, cambio_local],
+            )?;
+            if registro.tabla == "listas" {
+                listas.insert(registro.uid.clone());
+            } else {
+                items.insert(registro.uid.clone());
+            }
+        }
+
+        for uid in &listas {
+            self.materialize_list(&fusionada, uid)?;
+        }
+        // Items que llegaron antes que su lista, o cuya lista acaba de llegar
+        items.extend(
+            self.connection
+                .prepare(
+                    "SELECT uid FROM crdt_registros
+                    WHERE tabla = 'items' AND campo = 'lista'
+                    AND uid NOT IN (SELECT uid FROM items)
+                    AND json_extract(valor, '$') IN (SELECT uid FROM listas);",
+                )?
+                .query_map([], |row| row.get::<usize, String>(0))?
+                .collect::<Result<Vec<String>, rusqlite::Error>>()?,
+        );
+        let replica_items = self.replica(&items)?;
+        for uid in &items {
+            self.materialize_item(&replica_items, uid)?;
+        }
+
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    fn delete_by_uid(&self, tabla: &str, uid: &str) -> Result<(), anyhow::Error> {
+        if tabla == "listas" {
+            self.connection.execute(
+                "DELETE FROM imagenes_items WHERE id_item IN (SELECT items.id FROM items JOIN listas ON listas.id = items.id_lista WHERE listas.uid = ?1);",
+                [uid],
+            )?;
+            self.connection.execute(
+                "DELETE FROM items WHERE id_lista IN (SELECT id FROM listas WHERE uid = ?1);",
+                [uid],
+            )?;
+            self.connection.execute(
+                "DELETE FROM tasas_categoria WHERE id_lista IN (SELECT id FROM listas WHERE uid = ?1);",
+                [uid],
+            )?;
+            self.connection
+                .execute("DELETE FROM listas WHERE uid = ?1;", [uid])?;
+        } else {
+            self.connection.execute(
+                "DELETE FROM imagenes_items WHERE id_item IN (SELECT id FROM items WHERE uid = ?1);",
+                [uid],
+            )?;
+            self.connection
+                .execute("DELETE FROM items WHERE uid = ?1;", [uid])?;
+        }
+        // Si la fila no existía aquí, el trigger no limpió su estado
+        self.connection
+            .execute("DELETE FROM crdt_registros WHERE uid = ?1;", [uid])?;
+        Ok(())
+    }
+
+    // Escribe en `listas` (y en `tasas_categoria`) el valor fusionado de cada campo. Al cambiar
+    // cambio_local, los triggers no lo registran como una modificación hecha en este dispositivo
+    fn materialize_list(&self, replica: &Replica, uid: &str) -> Result<(), anyhow::Error> {
+        let nombre = replica.valor::<String>(uid, "nombre").unwrap_or_default();
+        let modo_simple = replica.valor(uid, "modo_simple").unwrap_or(0_usize);
+        let tasa_impuesto = replica.valor(uid, "tasa_impuesto").unwrap_or(0.0_f32);
+        let actualizadas = self.connection.execute(
+            "UPDATE listas SET nombre=?1, modo_simple=?2, tasa_impuesto=?3, cambio_local=cambio_local + 1 WHERE uid = ?4;",
+            params![nombre, modo_simple, tasa_impuesto, uid],
+        )?;
+        if actualizadas == 0 {
+            self.connection.execute(
+                "INSERT INTO listas (nombre, modo_simple, tasa_impuesto, uid) VALUES (?1, ?2, ?3, ?4);",
+                params![nombre, modo_simple, tasa_impuesto, uid],
+            )?;
+        }
+        let id_lista: usize =
+            self.connection
+                .query_row("SELECT id FROM listas WHERE uid = ?1;", [uid], |row| {
+                    row.get(0)
+                })?;
+        for registro in replica
+            .registros
+            .values()
+            .filter(|registro| registro.uid == uid)
+        {
+            let Some(categoria) = registro.campo.strip_prefix(CAMPO_TASA_CATEGORIA) else {
+                continue;
+            };
+            match serde_json::from_str::<Option<f32>>(&registro.valor).ok().flatten() {
+                Some(tasa_impuesto) => self.connection.execute(
+                    "INSERT OR REPLACE INTO tasas_categoria (id_lista, categoria, tasa_impuesto) VALUES (?1, ?2, ?3);",
+                    params![id_lista, categoria, tasa_impuesto],
+                )?,
+                None => self.connection.execute(
+                    "DELETE FROM tasas_categoria WHERE id_lista = ?1 AND categoria = ?2;",
+                    params![id_lista, categoria],
+                )?,
+            };
+        }
+        Ok(())
+    }
+
+    fn materialize_item(&self, replica: &Replica, uid: &str) -> Result<(), anyhow::Error> {
+        // Si la lista todavía no llegó, el item espera
+        let Some(uid_lista) = replica.valor::<String>(uid, "lista") else {
+            return Ok(());
+        };
+        let Some(id_lista) = self
+            .connection
+            .query_row(
+                "SELECT id FROM listas WHERE uid = ?1;",
+                [uid_lista],
+                |row| row.get::<usize, usize>(0),
+            )
+            .optional()?
+        else {
+            return Ok(());
+        };
+        let item = Item::default();
+        let nombre = replica.valor(uid, "nombre").unwrap_or(item.nombre);
+        let unidad = replica.valor(uid, "unidad").unwrap_or(item.unidad);
+        let cantidad_requerida = replica
+            .valor(uid, "cantidad_requerida")
+            .unwrap_or(item.cantidad_requerida);
+        let cantidad_comprada = replica
+            .valor(uid, "cantidad_comprada")
+            .unwrap_or(item.cantidad_comprada);
+        let precio = replica.valor(uid, "precio").unwrap_or(item.precio);
+        let precio_incluye_impuesto = replica
+            .valor(uid, "precio_incluye_impuesto")
+            .unwrap_or(item.precio_incluye_impuesto as usize);
+        let notas = replica.valor(uid, "notas").unwrap_or(item.notas);
+        let prioridad = replica
+            .valor(uid, "prioridad")
+            .unwrap_or(item.prioridad as usize);
+        let categoria = replica.valor(uid, "categoria").unwrap_or(item.categoria);
+        let codigo_barras = replica
+            .valor(uid, "codigo_barras")
+            .unwrap_or(item.codigo_barras);
+        let actualizados = self.connection.execute(
+            "UPDATE items SET id_lista=?1, nombre=?2, unidad=?3, cantidad_requerida=?4, cantidad_comprada=?5, precio=?6, precio_incluye_impuesto=?7, notas=?8, prioridad=?9, categoria=?11, codigo_barras=?12, cambio_local=cambio_local + 1 WHERE uid = ?10;",
+            params![id_lista, nombre, unidad, cantidad_requerida, cantidad_comprada, precio, precio_incluye_impuesto, notas, prioridad, uid, categoria, codigo_barras],
+        )?;
+        if actualizados == 0 {
+            self.connection.execute(
+                "INSERT INTO items (id_lista, nombre, unidad, cantidad_requerida, cantidad_comprada, precio, precio_incluye_impuesto, notas, prioridad, uid, categoria, codigo_barras) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12);",
+                params![id_lista, nombre, unidad, cantidad_requerida, cantidad_comprada, precio, precio_incluye_impuesto, notas, prioridad, uid, categoria, codigo_barras],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Copia local de los datos de un servidor, para usarla sin conexión
+impl SQLiteConnector {
+    // Deja sólo las listas indicadas, con los mismos id que en el servidor
+    pub fn replace_lists(&self, listas: &[Lista]) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        let ids = listas
+            .iter()
+            .map(|lista| lista.id.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        self.connection.execute(
+            &format!(
+                "DELETE FROM imagenes_items WHERE id_item IN (SELECT id FROM items WHERE id_lista NOT IN ({ids}));"
+            ),
+            [],
+        )?;
+        self.connection.execute(
+            &format!("DELETE FROM items WHERE id_lista NOT IN ({ids});"),
+            [],
+        )?;
+        self.connection.execute(
+            &format!("DELETE FROM listas_asignados WHERE id_lista NOT IN ({ids});"),
+            [],
+        )?;
+        self.connection.execute(
+            &format!("DELETE FROM tasas_categoria WHERE id_lista NOT IN ({ids});"),
+            [],
+        )?;
+        self.connection
+            .execute(&format!("DELETE FROM listas WHERE id NOT IN ({ids});"), [])?;
+        for lista in listas {
+            self.store_list_row(lista)?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    // Guarda una lista y sus items con los mismos id que en el servidor
+    pub fn store_list(&self, lista: &Lista) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.store_list_row(lista)?;
+        let items = lista.items.clone().unwrap_or_default();
+        let ids = items
+            .iter()
+            .map(|item| item.id.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        self.connection.execute(
+            &format!("DELETE FROM items WHERE id_lista = ?1 AND id NOT IN ({ids});"),
+            [lista.id],
+        )?;
+        for item in items {
+            self.store_item(lista.id, &item)?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    // Guarda un item con el mismo id que en el servidor (o un id local, si se creó sin conexión)
+    pub fn store_item(&self, id_lista: usize, item: &Item) -> Result<(), anyhow::Error> {
+        // Sin ON CONFLICT: su política reemplazaría la de los triggers de sincronización
+        let actualizados = self.connection.execute(
+            "UPDATE items SET id_lista=?2, nombre=?3, unidad=?4, cantidad_requerida=?5, cantidad_comprada=?6, precio=?7, precio_incluye_impuesto=?8, notas=?9, prioridad=?10, agregado_por=?11, comprado_por=?12, categoria=?13, asignado_a=?14, codigo_barras=?15 WHERE id = ?1;",
+            params![item.id, id_lista, item.nombre, item.unidad, item.cantidad_requerida, item.cantidad_comprada, item.precio, item.precio_incluye_impuesto, item.notas, item.prioridad as usize, item.agregado_por, item.comprado_por, item.categoria, item.asignado_a, item.codigo_barras],
+        )?;
+        if actualizados == 0 {
+            self.connection.execute(
+                "INSERT INTO items (id, id_lista, nombre, unidad, cantidad_requerida, cantidad_comprada, precio, precio_incluye_impuesto, notas, prioridad, agregado_por, comprado_por, categoria, asignado_a, codigo_barras) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15);",
+                params![item.id, id_lista, item.nombre, item.unidad, item.cantidad_requerida, item.cantidad_comprada, item.precio, item.precio_incluye_impuesto, item.notas, item.prioridad as usize, item.agregado_por, item.comprado_por, item.categoria, item.asignado_a, item.codigo_barras],
+            )?;
+        }
+        Ok(())
+    }
+
+    // Próximo id para una lista o item creado sin conexión. Empiezan en PRIMER_ID_LOCAL para
+    // que no choquen con los que el servidor ya dio a otros dispositivos
+    pub fn next_local_id(&self, tabla: &str) -> Result<usize, anyhow::Error> {
+        Ok(self.connection.query_row(
+            &format!("SELECT max(coalesce(max(id) + 1, 0), ?1) FROM {tabla};"),
+            [PRIMER_ID_LOCAL],
+            |row| row.get(0),
+        )?)
+    }
+
+    fn store_list_row(&self, lista: &Lista) -> Result<(), anyhow::Error> {
+        let actualizadas = self.connection.execute(
+            "UPDATE listas SET nombre=?2, modo_simple=?3, tasa_impuesto=?4, id_propietario=?5 WHERE id = ?1;",
+            params![lista.id, lista.nombre, lista.modo_simple as usize, lista.tasa_impuesto, lista.propietario],
+        )?;
+        if actualizadas == 0 {
+            self.connection.execute(
+                "INSERT INTO listas (id, nombre, modo_simple, tasa_impuesto, id_propietario) VALUES (?1, ?2, ?3, ?4, ?5);",
+                params![lista.id, lista.nombre, lista.modo_simple as usize, lista.tasa_impuesto, lista.propietario],
+            )?;
+        }
+        self.connection.execute(
+            "DELETE FROM listas_asignados WHERE id_lista = ?1;",
+            [lista.id],
+        )?;
+        for id_miembro in &lista.asignados {
+            self.connection.execute(
+                "INSERT OR IGNORE INTO listas_asignados (id_lista, id_miembro) VALUES (?1, ?2);",
+                [lista.id, *id_miembro],
+            )?;
+        }
+        // La lista de listas no trae las tasas por categoría
+        if lista.items.is_some() {
+            self.connection.execute(
+                "DELETE FROM tasas_categoria WHERE id_lista = ?1;",
+                [lista.id],
+            )?;
+            for (categoria, tasa_impuesto) in &lista.tasas_categoria {
+                self.connection.execute(
+                    "INSERT INTO tasas_categoria (id_lista, categoria, tasa_impuesto) VALUES (?1, ?2, ?3);",
+                    params![lista.id, categoria, tasa_impuesto],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn replace_members(&self, miembros: &[Miembro]) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute("DELETE FROM miembros;", [])?;
+        for miembro in miembros {
+            self.connection.execute(
+                "INSERT INTO miembros (id, nombre) VALUES (?1, ?2);",
+                params![miembro.id, miembro.nombre],
+            )?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    // Lo que se compra (o se desmarca) en una lista se suma (o resta) a la despensa, si el
+    // producto está en ella
+    fn add_to_pantry(
+        &self,
+        nombre: &str,
+        unidad: &str,
+        cantidad: f32,
+    ) -> Result<(), anyhow::Error> {
+        if cantidad.abs() < 0.001 {
+            return Ok(());
+        }
+        if let Some(producto) = self
+            .get_pantry()?
+            .into_iter()
+            .find(|producto| producto.corresponde_a(nombre, unidad))
+        {
+            self.connection.execute(
+                "UPDATE despensa SET cantidad = max(cantidad + ?1, 0) WHERE id = ?2;",
+                params![cantidad, producto.id],
+            )?;
+        }
+        Ok(())
+    }
+
+    // También se anota cuándo se compró, y si se desmarca se anota en negativo
+    fn record_purchase(
+        &self,
+        id_lista: usize,
+        nombre: &str,
+        unidad: &str,
+        cantidad: f32,
+    ) -> Result<(), anyhow::Error> {
+        if cantidad.abs() < 0.001 {
+            return Ok(());
+        }
+        self.store_purchase(&Compra {
+            nombre: nombre.trim().to_string(),
+            unidad: unidad.to_string(),
+            cantidad,
+            dia: hoy(),
+            id_lista,
+        })
+    }
+
+    fn store_purchase(&self, compra: &Compra) -> Result<(), anyhow::Error> {
+        self.connection.execute(
+            "INSERT INTO compras (nombre, unidad, cantidad, dia, id_lista) VALUES (?1, ?2, ?3, ?4, ?5);",
+            params![
+                compra.nombre,
+                compra.unidad,
+                compra.cantidad,
+                compra.dia,
+                compra.id_lista
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn replace_synonyms(&self, sinonimos: &[Sinonimo]) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute("DELETE FROM sinonimos;", [])?;
+        for sinonimo in sinonimos {
+            self.store_synonym(sinonimo)?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    fn store_synonym(&self, sinonimo: &Sinonimo) -> Result<usize, anyhow::Error> {
+        Ok(self.connection.query_row(
+            "INSERT INTO sinonimos (id, alias, canonico) VALUES (nullif(?1, 0), ?2, ?3)
+            ON CONFLICT (id) DO UPDATE SET alias=excluded.alias, canonico=excluded.canonico
+            RETURNING id;",
+            params![sinonimo.id, sinonimo.alias.trim(), sinonimo.canonico.trim()],
+            |row| row.get(0),
+        )?)
+    }
+
+    pub fn replace_purchases(&self, compras: &[Compra]) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute("DELETE FROM compras;", [])?;
+        for compra in compras {
+            self.store_purchase(compra)?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    pub fn replace_recipes(&self, recetas: &[Receta]) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection
+            .execute("DELETE FROM recetas_ingredientes;", [])?;
+        self.connection.execute("DELETE FROM recetas;", [])?;
+        for receta in recetas {
+            self.store_recipe(receta)?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    // Con id 0 se le asigna uno nuevo. Los ingredientes se reemplazan
+    fn store_recipe(&self, receta: &Receta) -> Result<usize, anyhow::Error> {
+        let id: usize = self.connection.query_row(
+            "INSERT INTO recetas (id, nombre, porciones) VALUES (nullif(?1, 0), ?2, max(?3, 1))
+            ON CONFLICT (id) DO UPDATE SET nombre=excluded.nombre, porciones=excluded.porciones
+            RETURNING id;",
+            params![receta.id, receta.nombre.trim(), receta.porciones],
+            |row| row.get(0),
+        )?;
+        self.connection.execute(
+            "DELETE FROM recetas_ingredientes WHERE id_receta = ?1;",
+            [id],
+        )?;
+        for (posicion, ingrediente) in receta
+            .ingredientes
+            .iter()
+            .filter(|ingrediente| !ingrediente.nombre.trim().is_empty())
+            .enumerate()
+        {
+            self.connection.execute(
+                "INSERT INTO recetas_ingredientes (id_receta, posicion, nombre, unidad, cantidad)
+                VALUES (?1, ?2, ?3, ?4, ?5);",
+                params![
+                    id,
+                    posicion,
+                    ingrediente.nombre.trim(),
+                    ingrediente.unidad,
+                    ingrediente.cantidad
+                ],
+            )?;
+        }
+        Ok(id)
+    }
+
+    pub fn replace_meals(&self, comidas: &[Comida]) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute("DELETE FROM menu;", [])?;
+        for comida in comidas {
+            self.store_meal(comida)?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    fn store_meal(&self, comida: &Comida) -> Result<usize, anyhow::Error> {
+        Ok(self.connection.query_row(
+            "INSERT INTO menu (id, dia, momento, id_receta, porciones)
+            VALUES (nullif(?1, 0), ?2, ?3, ?4, max(?5, 1))
+            ON CONFLICT (id) DO UPDATE SET dia=excluded.dia, momento=excluded.momento, id_receta=excluded.id_receta, porciones=excluded.porciones
+            RETURNING id;",
+            params![
+                comida.id,
+                comida.dia,
+                comida.momento as usize,
+                comida.id_receta,
+                comida.porciones
+            ],
+            |row| row.get(0),
+        )?)
+    }
+
+    pub fn replace_expirations(&self, vencimientos: &[Vencimiento]) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute("DELETE FROM vencimientos;", [])?;
+        for vencimiento in vencimientos {
+            self.store_expiration(vencimiento)?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    // Con id 0 se le asigna uno nuevo
+    fn store_expiration(&self, vencimiento: &Vencimiento) -> Result<usize, anyhow::Error> {
+        Ok(self.connection.query_row(
+            "INSERT INTO vencimientos (id, nombre, unidad, cantidad, vence, id_lista, reponer)
+            VALUES (nullif(?1, 0), ?2, ?3, ?4, ?5, ?6, ?7) RETURNING id;",
+            params![
+                vencimiento.id,
+                vencimiento.nombre.trim(),
+                vencimiento.unidad,
+                vencimiento.cantidad,
+                vencimiento.vence,
+                vencimiento.id_lista,
+                vencimiento.reponer
+            ],
+            |row| row.get(0),
+        )?)
+    }
+
+    pub fn replace_pantry(&self, productos: &[Producto]) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute("DELETE FROM despensa;", [])?;
+        for producto in productos {
+            self.save_pantry_item(producto.clone())?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    pub fn replace_expenses(&self, gastos: &[Gasto]) -> Result<(), anyhow::Error> {
+        let transaccion = self.connection.unchecked_transaction()?;
+        self.connection.execute("DELETE FROM gastos_cuotas;", [])?;
+        self.connection.execute("DELETE FROM gastos;", [])?;
+        for gasto in gastos {
+            self.store_expense(gasto)?;
+        }
+        transaccion.commit()?;
+        Ok(())
+    }
+
+    // Con id 0 se le asigna uno nuevo
+    fn store_expense(&self, gasto: &Gasto) -> Result<usize, anyhow::Error> {
+        let id: usize = self.connection.query_row(
+            "INSERT INTO gastos (id, id_lista, descripcion, fecha, pagado_por, liquidacion)
+            VALUES (nullif(?1, 0), ?2, ?3, ?4, ?5, ?6) RETURNING id;",
+            params![
+                gasto.id,
+                gasto.id_lista,
+                gasto.descripcion.trim(),
+                gasto.fecha,
+                gasto.pagado_por,
+                gasto.liquidacion
+            ],
+            |row| row.get(0),
+        )?;
+        for cuota in &gasto.cuotas {
+            self.connection.execute(
+                "INSERT INTO gastos_cuotas (id_gasto, id_miembro, monto) VALUES (?1, ?2, ?3)
+                ON CONFLICT (id_gasto, id_miembro) DO UPDATE SET monto = monto + excluded.monto;",
+                params![id, cuota.miembro, cuota.monto],
+            )?;
+        }
+        Ok(id)
+    }
+
+    pub fn pending_operations(&self) -> Result<Vec<(usize, String)>, anyhow::Error> {
+        Ok(self
+            .connection
+            .prepare("SELECT id, operacion FROM cola_servidor ORDER BY id;")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(usize, String)>, rusqlite::Error>>()?)
+    }
+
+    pub fn push_operation(&self, operacion: &str) -> Result<(), anyhow::Error> {
+        self.connection.execute(
+            "INSERT INTO cola_servidor (operacion) VALUES (?1);",
+            [operacion],
+        )?;
+        Ok(())
+    }
+
+    pub fn replace_operation(&self, id: usize, operacion: &str) -> Result<(), anyhow::Error> {
+        self.connection.execute(
+            "UPDATE cola_servidor SET operacion = ?1 WHERE id = ?2;",
+            params![operacion, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_operation(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.connection
+            .execute("DELETE FROM cola_servidor WHERE id = ?1;", [id])?;
+        Ok(())
+    }
+}
+
+// Directorio temporal propio de una prueba, que se borra al terminar
+#[cfg(test)]
+pub struct DirectorioPrueba(PathBuf);
+
+#[cfg(test)]
+impl DirectorioPrueba {
+    pub fn new() -> Self {
+        static SIGUIENTE: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let numero = SIGUIENTE.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let directorio = std::env::temp_dir().join(format!(
+            "shopping_list_prueba_{}_{numero}",
+            std::process::id()
+        ));
+        _ = std::fs::remove_dir_all(&directorio);
+        std::fs::create_dir_all(&directorio).unwrap();
+        DirectorioPrueba(directorio)
+    }
+
+    // Base de datos nueva dentro del directorio
+    pub fn conector(&self, archivo: &str) -> SQLiteConnector {
+        SQLiteConnector::with_path(&self.0.join(archivo))
+    }
+}
+
+#[cfg(test)]
+impl Default for DirectorioPrueba {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for DirectorioPrueba {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for DirectorioPrueba {
+    fn drop(&mut self) {
+        _ = std::fs::remove_dir_all(&self.0);
+    }
 }
@@ -0,0 +1,160 @@
+use crate::model::texto::normalizar;
+use crate::model::{DBConnector, Item, Lista};
+use serde::{Deserialize, Serialize};
+
+// Un producto que se tiene en casa, con la cantidad por debajo de la cual hay que reponerlo
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Producto {
+    pub id: usize,
+    pub nombre: String,
+    pub unidad: String,
+    pub cantidad: f32,
+    pub minimo: f32,
+}
+
+impl Producto {
+    // Lo que hay que comprar para llegar al mínimo
+    pub fn faltante(&self) -> f32 {
+        (self.minimo - self.cantidad).max(0.0)
+    }
+
+    // Los items de las listas se cuentan en la despensa si tienen el mismo nombre y unidad
+    pub fn corresponde_a(&self, nombre: &str, unidad: &str) -> bool {
+        self.unidad == unidad && normalizar(&self.nombre) == normalizar(nombre)
+    }
+}
+
+impl Default for Producto {
+    fn default() -> Producto {
+        Producto {
+            id: 0,
+            nombre: String::from(""),
+            unidad: String::from("unidad"),
+            cantidad: 0.0,
+            minimo: 0.0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ProductoForm {
+    pub id: String,
+    pub nombre: String,
+    pub unidad: String,
+    pub cantidad: String,
+    pub minimo: String,
+}
+
+impl ProductoForm {
+    pub fn into_producto(self) -> Producto {
+        Producto {
+            id: self.id.parse().unwrap_or_default(),
+            nombre: self.nombre,
+            unidad: self.unidad,
+            cantidad: self.cantidad.parse().unwrap_or_default(),
+            minimo: self.minimo.parse().unwrap_or_default(),
+        }
+    }
+}
+
+// Items para reponer lo que está por debajo del mínimo. Si la lista ya tiene el producto
+// pendiente se le sube la cantidad requerida en vez de repetirlo.
+pub fn faltantes(productos: &[Producto], lista: &Lista) -> Vec<Item> {
+    let items = lista.items.clone().unwrap_or_default();
+    productos
+        .iter()
+        .filter(|producto| producto.faltante() >= 0.001)
+        .filter_map(|producto| {
+            match items
+                .iter()
+                .find(|item| !item.comprado() && producto.corresponde_a(&item.nombre, &item.unidad))
+            {
+                Some(item) if item.cantidad_requerida >= producto.faltante() => None,
+                Some(item) => Some(Item {
+                    cantidad_requerida: producto.faltante(),
+                    ..item.clone()
+                }),
+                None => Some(Item {
+                    id_lista: lista.id,
+                    nombre: producto.nombre.clone(),
+                    unidad: producto.unidad.clone(),
+                    cantidad_requerida: producto.faltante(),
+                    ..Default::default()
+                }),
+            }
+        })
+        .collect()
+}
+
+// Agrega a la lista lo que falta en la despensa. Devuelve cuántos items se agregaron o cambiaron.
+pub fn generar_lista(conector: &dyn DBConnector, id_lista: usize) -> Result<usize, anyhow::Error> {
+    let faltantes = faltantes(&conector.get_pantry()?, &conector.get_list(id_lista)?);
+    for item in faltantes.iter() {
+        if item.id == 0 {
+            conector.create_new_list_item(id_lista, item.clone())?;
+        } else {
+            conector.update_list_item(item.clone())?;
+        }
+    }
+    Ok(faltantes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::database::DirectorioPrueba;
+
+    fn cantidad_en_despensa(conector: &dyn DBConnector) -> f32 {
+        conector.get_pantry().unwrap()[0].cantidad
+    }
+
+    #[test]
+    fn marcar_comprado_repone_la_despensa_una_sola_vez() {
+        let directorio = DirectorioPrueba::new();
+        let conector = directorio.conector("shopping_list.db");
+        conector
+            .save_pantry_item(Producto {
+                nombre: "Leche".to_string(),
+                unidad: "litro".to_string(),
+                cantidad: 1.0,
+                ..Producto::default()
+            })
+            .unwrap();
+        let id_lista = conector.create_new_list("Feria".to_string()).unwrap();
+        let item = Item {
+            id_lista,
+            nombre: "leche ".to_string(),
+            unidad: "litro".to_string(),
+            cantidad_requerida: 2.0,
+            ..Item::default()
+        };
+        let id = conector
+            .create_new_list_item(id_lista, item.clone())
+            .unwrap();
+        let comprado = Item {
+            id,
+            cantidad_comprada: 2.0,
+            ..item
+        };
+        conector.update_list_item(comprado.clone()).unwrap();
+        assert_eq!(cantidad_en_despensa(&conector), 3.0);
+
+        // Guardar otra vez el item sin cambiar lo comprado no vuelve a sumar
+        conector
+            .update_list_item(Item {
+                notas: "Entera".to_string(),
+                ..comprado.clone()
+            })
+            .unwrap();
+        assert_eq!(cantidad_en_despensa(&conector), 3.0);
+
+        // Desmarcarlo lo descuenta
+        conector
+            .update_list_item(Item {
+                cantidad_comprada: 0.0,
+                ..comprado
+            })
+            .unwrap();
+        assert_eq!(cantidad_en_despensa(&conector), 1.0);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::model::recetas::conversion;
+use crate::model::sinonimos::Sinonimos;
+use crate::model::{DBConnector, Item};
+
+// Dos items pendientes son duplicados si son el mismo producto en unidades compatibles
+fn duplicados(uno: &Item, otro: &Item, sinonimos: &Sinonimos) -> bool {
+    !uno.comprado()
+        && !otro.comprado()
+        && sinonimos.mismo_producto(&uno.nombre, &otro.nombre)
+        && conversion(&otro.unidad, &uno.unidad).is_some()
+}
+
+// El item anterior de la lista del que este es un duplicado. Los items sin guardar son los más
+// nuevos.
+pub fn duplicado_de(item: &Item, items: &[Item], sinonimos: &Sinonimos) -> Option<Item> {
+    items
+        .iter()
+        .filter(|otro| otro.id != 0 && otro.id != item.id && (item.id == 0 || otro.id < item.id))
+        .find(|otro| duplicados(otro, item, sinonimos))
+        .cloned()
+}
+
+// El item que queda al juntar el duplicado con el existente
+pub fn fusion(existente: &Item, duplicado: &Item) -> Item {
+    let factor = conversion(&duplicado.unidad, &existente.unidad).unwrap_or(1.0);
+    Item {
+        cantidad_requerida: existente.cantidad_requerida + duplicado.cantidad_requerida * factor,
+        notas: if existente.notas.trim().is_empty() {
+            duplicado.notas.clone()
+        } else {
+            existente.notas.clone()
+        },
+        ..existente.clone()
+    }
+}
+
+// Pares (existente, duplicado) de una lista, de modo que cada duplicado se junta con el item más
+// antiguo del mismo producto
+pub fn buscar_duplicados(items: &[Item], sinonimos: &Sinonimos) -> Vec<(Item, Item)> {
+    let mut ordenados: Vec<Item> = items.iter().filter(|item| item.id != 0).cloned().collect();
+    ordenados.sort_by_key(|item| item.id);
+    let mut pares: Vec<(Item, Item)> = Vec::new();
+    for item in ordenados.iter() {
+        if pares.iter().any(|(_, duplicado)| duplicado.id == item.id) {
+            continue;
+        }
+        if let Some(existente) = duplicado_de(item, &ordenados, sinonimos).filter(|existente| {
+            !pares
+                .iter()
+                .any(|(_, duplicado)| duplicado.id == existente.id)
+        }) {
+            pares.push((existente, item.clone()));
+        }
+    }
+    pares
+}
+
+// Suma la cantidad del duplicado al item existente y borra el duplicado
+pub fn fusionar(
+    conector: &dyn DBConnector,
+    existente: &Item,
+    duplicado: &Item,
+) -> Result<(), anyhow::Error> {
+    conector.update_list_item(fusion(existente, duplicado))?;
+    conector.delete_item(duplicado.id)
+}
+
+// Junta todos los duplicados de la lista. Devuelve cuántos items se quitaron.
+pub fn limpiar_duplicados(
+    conector: &dyn DBConnector,
+    id_lista: usize,
+    sinonimos: &Sinonimos,
+) -> Result<usize, anyhow::Error> {
+    let items = conector.get_list(id_lista)?.items.unwrap_or_default();
+    let pares = buscar_duplicados(&items, sinonimos);
+    // Un item puede recibir varios duplicados, así que se suman sobre el valor ya actualizado
+    let mut fusionados: Vec<Item> = Vec::new();
+    for (existente, duplicado) in pares.iter() {
+        let existente = fusionados
+            .iter()
+            .find(|fusionado| fusionado.id == existente.id)
+            .cloned()
+            .unwrap_or_else(|| existente.clone());
+        let fusionado = fusion(&existente, duplicado);
+        conector.update_list_item(fusionado.clone())?;
+        conector.delete_item(duplicado.id)?;
+        fusionados.retain(|otro| otro.id != fusionado.id);
+        fusionados.push(fusionado);
+    }
+    Ok(pares.len())
+}
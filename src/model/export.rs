@@ -0,0 +1,152 @@
+use crate::model::{Item, Lista};
+use serde::Serialize;
+
+pub const VERSION_EXPORTACION: u32 = 1;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum FormatoExportacion {
+    Json,
+    Csv,
+    Markdown,
+    Texto,
+}
+
+impl FormatoExportacion {
+    pub const TODOS: [FormatoExportacion; 4] = [
+        FormatoExportacion::Json,
+        FormatoExportacion::Csv,
+        FormatoExportacion::Markdown,
+        FormatoExportacion::Texto,
+    ];
+
+    // Clave del mensaje traducido para mostrar el formato
+    pub fn clave(&self) -> &'static str {
+        match self {
+            FormatoExportacion::Json => "format_json",
+            FormatoExportacion::Csv => "format_csv",
+            FormatoExportacion::Markdown => "format_markdown",
+            FormatoExportacion::Texto => "format_text",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Exportacion<'a> {
+    version: u32,
+    listas: &'a [Lista],
+}
+
+pub fn exportar(listas: &[Lista], formato: FormatoExportacion) -> Result<String, anyhow::Error> {
+    Ok(match formato {
+        FormatoExportacion::Json => to_json(listas)?,
+        FormatoExportacion::Csv => to_csv(listas),
+        FormatoExportacion::Markdown => to_markdown(listas),
+        FormatoExportacion::Texto => to_text(listas),
+    })
+}
+
+pub fn to_json(listas: &[Lista]) -> Result<String, anyhow::Error> {
+    let exportacion = Exportacion {
+        version: VERSION_EXPORTACION,
+        listas,
+    };
+    Ok(serde_json::to_string_pretty(&exportacion)?)
+}
+
+pub const COLUMNAS_CSV: [&str; 9] = [
+    "lista",
+    "nombre",
+    "unidad",
+    "cantidad_requerida",
+    "cantidad_comprada",
+    "precio",
+    "precio_incluye_impuesto",
+    "prioridad",
+    "notas",
+];
+
+pub fn to_csv(listas: &[Lista]) -> String {
+    let mut csv = COLUMNAS_CSV.join(",");
+    csv.push('\n');
+    for lista in listas {
+        for item in items(lista) {
+            let campos = [
+                campo_csv(&lista.nombre),
+                campo_csv(&item.nombre),
+                campo_csv(&item.unidad),
+                item.cantidad_requerida.to_string(),
+                item.cantidad_comprada.to_string(),
+                item.precio.to_string(),
+                (item.precio_incluye_impuesto as usize).to_string(),
+                (item.prioridad as usize).to_string(),
+                campo_csv(&item.notas),
+            ];
+            csv.push_str(&campos.join(","));
+            csv.push('\n');
+        }
+    }
+    csv
+}
+
+pub fn to_markdown(listas: &[Lista]) -> String {
+    listas
+        .iter()
+        .map(|lista| {
+            let mut markdown = format!("## {}\n\n", lista.nombre);
+            for item in items(lista) {
+                let marca = if item.comprado() { "x" } else { " " };
+                markdown.push_str(&format!("- [{marca}] {}\n", describir(item)));
+            }
+            markdown
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Formato pensado para pegar en un mensaje de chat: sólo lo que falta comprar
+pub fn to_text(listas: &[Lista]) -> String {
+    listas
+        .iter()
+        .map(|lista| {
+            let mut texto = format!("{}\n", lista.nombre);
+            for item in items(lista).iter().filter(|item| !item.comprado()) {
+                texto.push_str(&format!("- {}\n", describir(item)));
+            }
+            texto
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn formatear_cantidad(cantidad: f32) -> String {
+    let texto = format!("{cantidad:.3}");
+    texto
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn items(lista: &Lista) -> &[Item] {
+    lista.items.as_deref().unwrap_or_default()
+}
+
+fn describir(item: &Item) -> String {
+    let mut descripcion = format!(
+        "{} {} {}",
+        formatear_cantidad(item.cantidad_requerida),
+        item.unidad,
+        item.nombre
+    );
+    if !item.notas.is_empty() {
+        descripcion.push_str(&format!(" ({})", item.notas));
+    }
+    descripcion
+}
+
+fn campo_csv(valor: &str) -> String {
+    if valor.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", valor.replace('"', "\"\""))
+    } else {
+        valor.to_string()
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SEGUNDOS_POR_DIA: u64 = 86_400;
+
+// Segundos desde 1970-01-01 UTC
+pub fn ahora() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duracion| duracion.as_secs())
+        .unwrap_or_default()
+}
+
+// Convierte días desde 1970-01-01 en (año, mes, día), según el algoritmo de Howard Hinnant
+pub fn civil_desde_dias(dias: i64) -> (i64, u32, u32) {
+    let z = dias + 719_468;
+    let era = z.div_euclid(146_097);
+    let dia_de_era = z.rem_euclid(146_097);
+    let anio_de_era =
+        (dia_de_era - dia_de_era / 1460 + dia_de_era / 36_524 - dia_de_era / 146_096) / 365;
+    let dia_del_anio = dia_de_era - (365 * anio_de_era + anio_de_era / 4 - anio_de_era / 100);
+    let mes_desde_marzo = (5 * dia_del_anio + 2) / 153;
+    let dia = (dia_del_anio - (153 * mes_desde_marzo + 2) / 5 + 1) as u32;
+    let mes = if mes_desde_marzo < 10 {
+        mes_desde_marzo + 3
+    } else {
+        mes_desde_marzo - 9
+    } as u32;
+    let anio = anio_de_era + era * 400 + if mes <= 2 { 1 } else { 0 };
+    (anio, mes, dia)
+}
+
+// Inverso de civil_desde_dias
+pub fn dias_desde_civil(anio: i64, mes: u32, dia: u32) -> i64 {
+    let anio = if mes <= 2 { anio - 1 } else { anio };
+    let era = anio.div_euclid(400);
+    let anio_de_era = anio.rem_euclid(400);
+    let mes = mes as i64;
+    let dia_del_anio = (153 * (if mes > 2 { mes - 3 } else { mes + 9 }) + 2) / 5 + dia as i64 - 1;
+    let dia_de_era = anio_de_era * 365 + anio_de_era / 4 - anio_de_era / 100 + dia_del_anio;
+    era * 146_097 + dia_de_era - 719_468
+}
+
+// "AAAA-MM-DD HH:MM" en UTC
+pub fn formatear_fecha_hora(segundos: u64) -> String {
+    let (anio, mes, dia) = civil_desde_dias((segundos / SEGUNDOS_POR_DIA) as i64);
+    let segundos_del_dia = segundos % SEGUNDOS_POR_DIA;
+    format!(
+        "{anio:04}-{mes:02}-{dia:02} {:02}:{:02}",
+        segundos_del_dia / 3600,
+        segundos_del_dia % 3600 / 60
+    )
+}
+
+// Días desde 1970-01-01 UTC
+pub fn hoy() -> i64 {
+    (ahora() / SEGUNDOS_POR_DIA) as i64
+}
+
+// "AAAA-MM-DD", el formato de los inputs de tipo date
+pub fn formatear_fecha(dias: i64) -> String {
+    let (anio, mes, dia) = civil_desde_dias(dias);
+    format!("{anio:04}-{mes:02}-{dia:02}")
+}
+
+// Inverso de formatear_fecha. Las fechas que no existen, como el 31 de febrero, no se aceptan
+pub fn leer_fecha(texto: &str) -> Option<i64> {
+    let mut partes = texto.trim().splitn(3, '-');
+    let anio = partes.next()?.parse().ok()?;
+    let mes = partes.next()?.parse().ok()?;
+    let dia = partes.next()?.parse().ok()?;
+    let dias = dias_desde_civil(anio, mes, dia);
+    (civil_desde_dias(dias) == (anio, mes, dia)).then_some(dias)
+}
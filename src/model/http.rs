@@ -0,0 +1,1232 @@
+use crate::model::api::{
+    Busqueda, CambioAsignados, CambioImpuesto, CambioImpuestoCategoria, CambioLista,
+    CambioPropietario, Creado, ErrorApi, ImagenApi, NombreMiembro, NuevaLista, PREFIJO_API,
+};
+use crate::model::backup::Respaldo;
+use crate::model::codigo_barras::normalizar_codigo;
+use crate::model::cuentas::Gasto;
+use crate::model::database::{CLAVE_MIEMBRO_ACTUAL, PRIMER_ID_LOCAL};
+use crate::model::despensa::Producto;
+use crate::model::fecha::ahora;
+use crate::model::menu::Comida;
+use crate::model::recetas::Receta;
+use crate::model::sinonimos::Sinonimo;
+use crate::model::sugerencias::Compra;
+use crate::model::vencimientos::Vencimiento;
+use crate::model::{
+    DBConnector, Imagen, Item, Lista, LocalSync, Miembro, ResultadoBusqueda, SQLiteConnector,
+    ServerConnection, validar_tasa,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub const CLAVE_URL_SERVIDOR: &str = "server_url";
+pub const CLAVE_TOKEN_SERVIDOR: &str = "server_token";
+// Cambios hechos sin conexión que el servidor rechazó y que el usuario todavía no vio
+const CLAVE_RECHAZADOS: &str = "rejected_changes";
+// Después de fallar una conexión, se trabaja sin conexión durante este tiempo antes de reintentar
+const ESPERA_RECONEXION: u64 = 30;
+const TIEMPO_ESPERA: Duration = Duration::from_secs(5);
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum EstadoConexion {
+    EnLinea,
+    SinConexion { pendientes: usize },
+    Rechazados { rechazados: usize },
+}
+
+// Escritura hecha sin conexión, que se repite en el servidor al reconectar
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+enum Operacion {
+    CrearLista {
+        id_local: usize,
+        nombre: String,
+    },
+    CambiarLista {
+        id: usize,
+        nombre: String,
+        modo_simple: usize,
+    },
+    CambiarImpuesto {
+        id: usize,
+        tasa_impuesto: f32,
+    },
+    CambiarImpuestoCategoria {
+        id: usize,
+        categoria: String,
+        tasa_impuesto: Option<f32>,
+    },
+    CambiarPropietario {
+        id: usize,
+        propietario: Option<usize>,
+    },
+    CambiarAsignados {
+        id: usize,
+        asignados: Vec<usize>,
+    },
+    BorrarLista {
+        id: usize,
+    },
+    LimpiarLista {
+        id: usize,
+    },
+    CrearItem {
+        id_lista: usize,
+        id_local: usize,
+        item: Item,
+    },
+    CambiarItem {
+        item: Item,
+    },
+    BorrarItem {
+        id: usize,
+    },
+}
+
+impl Operacion {
+    // Cuando el servidor crea una lista hecha sin conexión, las operaciones siguientes usan su id
+    fn reasignar_lista(&mut self, id_local: usize, id: usize) {
+        let id_lista = match self {
+            Operacion::CambiarLista { id, .. }
+            | Operacion::CambiarImpuesto { id, .. }
+            | Operacion::CambiarImpuestoCategoria { id, .. }
+            | Operacion::CambiarPropietario { id, .. }
+            | Operacion::CambiarAsignados { id, .. }
+            | Operacion::BorrarLista { id }
+            | Operacion::LimpiarLista { id } => id,
+            Operacion::CrearItem { id_lista, .. } => id_lista,
+            Operacion::CambiarItem { item } => &mut item.id_lista,
+            Operacion::CrearLista { .. } | Operacion::BorrarItem { .. } => return,
+        };
+        if *id_lista == id_local {
+            *id_lista = id;
+        }
+    }
+
+    // Lo mismo con los items creados sin conexión
+    fn reasignar_item(&mut self, id_local: usize, id: usize) {
+        let id_item = match self {
+            Operacion::CambiarItem { item } => &mut item.id,
+            Operacion::BorrarItem { id } => id,
+            _ => return,
+        };
+        if *id_item == id_local {
+            *id_item = id;
+        }
+    }
+}
+
+enum ErrorHttp {
+    SinConexion(anyhow::Error),
+    Rechazada(anyhow::Error),
+}
+
+impl From<serde_json::Error> for ErrorHttp {
+    fn from(error: serde_json::Error) -> Self {
+        ErrorHttp::Rechazada(error.into())
+    }
+}
+
+impl From<anyhow::Error> for ErrorHttp {
+    fn from(error: anyhow::Error) -> Self {
+        ErrorHttp::Rechazada(error)
+    }
+}
+
+impl From<ErrorHttp> for anyhow::Error {
+    fn from(error: ErrorHttp) -> Self {
+        match error {
+            ErrorHttp::SinConexion(error) | ErrorHttp::Rechazada(error) => error,
+        }
+    }
+}
+
+fn cuerpo<T: Serialize>(valor: &T) -> Result<Vec<u8>, ErrorHttp> {
+    Ok(serde_json::to_vec(valor)?)
+}
+
+// Conector que trabaja contra el servidor propio. Guarda una copia de lo que lee para usarla
+// sin conexión, y las escrituras hechas sin conexión se encolan y se repiten al reconectar.
+pub struct HTTPConnector {
+    url: String,
+    token: String,
+    agente: ureq::Agent,
+    // Base de datos local, para la configuración del dispositivo
+    local: SQLiteConnector,
+    espejo: SQLiteConnector,
+    reintentar_desde: Cell<u64>,
+    // Id en el servidor de las listas e items creados sin conexión, para las vistas que
+    // todavía usan el id local
+    ids_servidor: RefCell<HashMap<usize, usize>>,
+    ids_items: RefCell<HashMap<usize, usize>>,
+}
+
+impl HTTPConnector {
+    pub fn new(
+        url: String,
+        token: String,
+        local: SQLiteConnector,
+        espejo: SQLiteConnector,
+    ) -> Self {
+        HTTPConnector {
+            url: url.trim().trim_end_matches('/').to_string(),
+            token: token.trim().to_string(),
+            agente: ureq::Agent::config_builder()
+                .http_status_as_error(false)
+                .timeout_global(Some(TIEMPO_ESPERA))
+                .build()
+                .into(),
+            local,
+            espejo,
+            reintentar_desde: Cell::new(0),
+            ids_servidor: RefCell::new(HashMap::new()),
+            ids_items: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn pedir(&self, metodo: &str, ruta: &str, datos: Vec<u8>) -> Result<Vec<u8>, ErrorHttp> {
+        if ahora() < self.reintentar_desde.get() {
+            return Err(ErrorHttp::SinConexion(anyhow::anyhow!(
+                "The server is unreachable"
+            )));
+        }
+        let peticion = ureq::http::Request::builder()
+            .method(metodo)
+            .uri(format!("{}{PREFIJO_API}{ruta}", self.url))
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Content-Type", "application/json")
+            .body(datos)
+            .map_err(anyhow::Error::from)?;
+        let mut respuesta = match self.agente.run(peticion) {
+            Ok(respuesta) => respuesta,
+            Err(error) => {
+                self.reintentar_desde.set(ahora() + ESPERA_RECONEXION);
+                return Err(ErrorHttp::SinConexion(error.into()));
+            }
+        };
+        self.reintentar_desde.set(0);
+        let estado = respuesta.status();
+        let datos = respuesta
+            .body_mut()
+            .read_to_vec()
+            .map_err(|error| ErrorHttp::SinConexion(error.into()))?;
+        if estado.is_success() {
+            return Ok(datos);
+        }
+        let mensaje = serde_json::from_slice::<ErrorApi>(&datos)
+            .map(|error| error.error)
+            .unwrap_or_else(|_| estado.to_string());
+        // Un error del servidor puede ser pasajero, así que se trata como falta de conexión
+        if estado.is_server_error() {
+            self.reintentar_desde.set(ahora() + ESPERA_RECONEXION);
+            Err(ErrorHttp::SinConexion(anyhow::anyhow!(mensaje)))
+        } else {
+            Err(ErrorHttp::Rechazada(anyhow::anyhow!(mensaje)))
+        }
+    }
+
+    // Lee del servidor, después de enviarle lo que esté pendiente
+    fn leer<T: DeserializeOwned>(&self, ruta: &str) -> Result<T, ErrorHttp> {
+        self.sincronizar_pendientes()?;
+        Ok(serde_json::from_slice(&self.pedir(
+            "GET",
+            ruta,
+            Vec::new(),
+        )?)?)
+    }
+
+    fn ejecutar(&self, operacion: &Operacion) -> Result<Option<usize>, ErrorHttp> {
+        let respuesta = match operacion {
+            Operacion::CrearLista { nombre, .. } => {
+                let datos = self.pedir(
+                    "POST",
+                    "/listas",
+                    cuerpo(&NuevaLista {
+                        nombre: nombre.clone(),
+                    })?,
+                )?;
+                return Ok(Some(serde_json::from_slice::<Creado>(&datos)?.id));
+            }
+            Operacion::CambiarLista {
+                id,
+                nombre,
+                modo_simple,
+            } => self.pedir(
+                "PUT",
+                &format!("/listas/{id}"),
+                cuerpo(&CambioLista {
+                    nombre: nombre.clone(),
+                    modo_simple: *modo_simple,
+                })?,
+            ),
+            Operacion::CambiarImpuesto { id, tasa_impuesto } => self.pedir(
+                "PUT",
+                &format!("/listas/{id}/impuesto"),
+                cuerpo(&CambioImpuesto {
+                    tasa_impuesto: *tasa_impuesto,
+                })?,
+            ),
+            Operacion::CambiarImpuestoCategoria {
+                id,
+                categoria,
+                tasa_impuesto,
+            } => self.pedir(
+                "PUT",
+                &format!("/listas/{id}/impuestos"),
+                cuerpo(&CambioImpuestoCategoria {
+                    categoria: categoria.clone(),
+                    tasa_impuesto: *tasa_impuesto,
+                })?,
+            ),
+            Operacion::CambiarPropietario { id, propietario } => self.pedir(
+                "PUT",
+                &format!("/listas/{id}/propietario"),
+                cuerpo(&CambioPropietario {
+                    propietario: *propietario,
+                })?,
+            ),
+            Operacion::CambiarAsignados { id, asignados } => self.pedir(
+                "PUT",
+                &format!("/listas/{id}/asignados"),
+                cuerpo(&CambioAsignados {
+                    asignados: asignados.clone(),
+                })?,
+            ),
+            Operacion::BorrarLista { id } => {
+                self.pedir("DELETE", &format!("/listas/{id}"), Vec::new())
+            }
+            Operacion::LimpiarLista { id } => {
+                self.pedir("POST", &format!("/listas/{id}/limpiar"), Vec::new())
+            }
+            Operacion::CrearItem { id_lista, item, .. } => {
+                let datos = self.pedir(
+                    "POST",
+                    &format!("/listas/{id_lista}/items"),
+                    cuerpo(&Item {
+                        id: 0,
+                        ..item.clone()
+                    })?,
+                )?;
+                return Ok(Some(serde_json::from_slice::<Creado>(&datos)?.id));
+            }
+            Operacion::CambiarItem { item } => {
+                self.pedir("PUT", &format!("/items/{}", item.id), cuerpo(item)?)
+            }
+            Operacion::BorrarItem { id } => {
+                self.pedir("DELETE", &format!("/items/{id}"), Vec::new())
+            }
+        };
+        respuesta.map(|_| None)
+    }
+
+    // Repite en el servidor las operaciones encoladas. Si no hay conexión o el servidor falla
+    // se conservan para el próximo intento. Las que el servidor rechaza (por ejemplo, porque
+    // alguien más borró la lista) se descartan y se cuentan para avisarle al usuario.
+    fn sincronizar_pendientes(&self) -> Result<(), ErrorHttp> {
+        let mut pendientes = self.espejo.pending_operations()?;
+        while !pendientes.is_empty() {
+            let (id_cola, json) = pendientes.remove(0);
+            let Ok(operacion) = serde_json::from_str::<Operacion>(&json) else {
+                self.espejo.remove_operation(id_cola)?;
+                self.contar_rechazado()?;
+                continue;
+            };
+            match self.ejecutar(&operacion) {
+                Err(ErrorHttp::SinConexion(error)) => return Err(ErrorHttp::SinConexion(error)),
+                Err(ErrorHttp::Rechazada(_)) => self.contar_rechazado()?,
+                // Sólo las creaciones devuelven el id que les dio el servidor
+                Ok(Some(id)) => {
+                    match operacion {
+                        Operacion::CrearLista { id_local, .. } => {
+                            self.ids_servidor.borrow_mut().insert(id_local, id);
+                        }
+                        Operacion::CrearItem { id_local, .. } => {
+                            self.ids_items.borrow_mut().insert(id_local, id);
+                        }
+                        _ => {}
+                    }
+                    for (id_siguiente, json) in pendientes.iter_mut() {
+                        let mut siguiente = serde_json::from_str::<Operacion>(json)?;
+                        match operacion {
+                            Operacion::CrearLista { id_local, .. } => {
+                                siguiente.reasignar_lista(id_local, id)
+                            }
+                            Operacion::CrearItem { id_local, .. } => {
+                                siguiente.reasignar_item(id_local, id)
+                            }
+                            _ => {}
+                        }
+                        *json = serde_json::to_string(&siguiente)?;
+                        self.espejo.replace_operation(*id_siguiente, json)?;
+                    }
+                }
+                Ok(None) => {}
+            }
+            self.espejo.remove_operation(id_cola)?;
+        }
+        Ok(())
+    }
+
+    fn rechazados(&self) -> usize {
+        self.espejo
+            .get_setting(CLAVE_RECHAZADOS)
+            .ok()
+            .flatten()
+            .and_then(|valor| valor.parse().ok())
+            .unwrap_or_default()
+    }
+
+    fn contar_rechazado(&self) -> Result<(), anyhow::Error> {
+        self.espejo
+            .set_setting(CLAVE_RECHAZADOS, &(self.rechazados() + 1).to_string())
+    }
+
+    // Id en el servidor de una lista creada sin conexión que ya se envió
+    fn id_lista(&self, id: usize) -> usize {
+        self.ids_servidor.borrow().get(&id).copied().unwrap_or(id)
+    }
+
+    fn id_item(&self, id: usize) -> usize {
+        self.ids_items.borrow().get(&id).copied().unwrap_or(id)
+    }
+
+    fn escribir(&self, mut operacion: Operacion) -> Result<Option<usize>, anyhow::Error> {
+        for (id_local, id) in self.ids_servidor.borrow().iter() {
+            operacion.reasignar_lista(*id_local, *id);
+        }
+        for (id_local, id) in self.ids_items.borrow().iter() {
+            operacion.reasignar_item(*id_local, *id);
+        }
+        match self
+            .sincronizar_pendientes()
+            .and_then(|()| self.ejecutar(&operacion))
+        {
+            Err(ErrorHttp::SinConexion(_)) => {
+                let id_local = self.aplicar_en_espejo(&mut operacion)?;
+                self.encolar(operacion)?;
+                Ok(id_local)
+            }
+            resultado => Ok(resultado?),
+        }
+    }
+
+    fn aplicar_en_espejo(&self, operacion: &mut Operacion) -> Result<Option<usize>, anyhow::Error> {
+        match operacion {
+            Operacion::CrearLista { id_local, nombre } => {
+                *id_local = self.espejo.next_local_id("listas")?;
+                self.espejo.store_list(&Lista {
+                    id: *id_local,
+                    nombre: nombre.clone(),
+                    ..Lista::default()
+                })?;
+                return Ok(Some(*id_local));
+            }
+            Operacion::CambiarLista {
+                id,
+                nombre,
+                modo_simple,
+            } => self.espejo.update_list(*id, nombre.clone(), *modo_simple)?,
+            Operacion::CambiarImpuesto { id, tasa_impuesto } => {
+                self.espejo.update_list_tax_rate(*id, *tasa_impuesto)?
+            }
+            Operacion::CambiarImpuestoCategoria {
+                id,
+                categoria,
+                tasa_impuesto,
+            } => self
+                .espejo
+                .set_category_tax_rate(*id, categoria.clone(), *tasa_impuesto)?,
+            Operacion::CambiarPropietario { id, propietario } => {
+                self.espejo.set_list_owner(*id, *propietario)?
+            }
+            Operacion::CambiarAsignados { id, asignados } => {
+                self.espejo.set_list_assignees(*id, asignados.clone())?
+            }
+            Operacion::BorrarLista { id } => self.espejo.delete_list(*id)?,
+            Operacion::LimpiarLista { id } => self.espejo.clear_list_items(*id)?,
+            Operacion::CrearItem {
+                id_lista,
+                id_local,
+                item,
+            } => {
+                *id_local = self.espejo.next_local_id("items")?;
+                self.espejo.store_item(
+                    *id_lista,
+                    &Item {
+                        id: *id_local,
+                        ..item.clone()
+                    },
+                )?;
+                return Ok(Some(*id_local));
+            }
+            Operacion::CambiarItem { item } => self.espejo.update_list_item(item.clone())?,
+            Operacion::BorrarItem { id } => self.espejo.delete_item(*id)?,
+        }
+        Ok(None)
+    }
+
+    fn encolar(&self, operacion: Operacion) -> Result<(), anyhow::Error> {
+        // Un item creado sin conexión todavía no tiene id en el servidor, así que sus cambios se
+        // juntan con su creación
+        let id_item = match &operacion {
+            Operacion::CambiarItem { item } => Some(item.id),
+            Operacion::BorrarItem { id } => Some(*id),
+            _ => None,
+        };
+        if let Some(id_item) = id_item {
+            for (id_cola, json) in self.espejo.pending_operations()? {
+                if let Ok(Operacion::CrearItem {
+                    id_lista, id_local, ..
+                }) = serde_json::from_str(&json)
+                    && id_local == id_item
+                {
+                    return match operacion {
+                        Operacion::CambiarItem { item } => self.espejo.replace_operation(
+                            id_cola,
+                            &serde_json::to_string(&Operacion::CrearItem {
+                                id_lista,
+                                id_local,
+                                item,
+                            })?,
+                        ),
+                        _ => self.espejo.remove_operation(id_cola),
+                    };
+                }
+            }
+        }
+        self.espejo
+            .push_operation(&serde_json::to_string(&operacion)?)
+    }
+
+    fn imagen(&self, ruta: &str) -> Result<Option<Imagen>, anyhow::Error> {
+        match self.leer::<Option<ImagenApi>>(ruta) {
+            Ok(imagen) => imagen.map(Imagen::try_from).transpose(),
+            // Las fotos no se guardan en la copia local
+            Err(ErrorHttp::SinConexion(_)) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+// Usa el servidor si el usuario configuró uno; si no, la base de datos local
+pub fn conector_configurado() -> Box<dyn DBConnector> {
+    let local = SQLiteConnector::new();
+    let url = local
+        .get_setting(CLAVE_URL_SERVIDOR)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    if url.trim().is_empty() {
+        return Box::new(local);
+    }
+    let token = local
+        .get_setting(CLAVE_TOKEN_SERVIDOR)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let espejo = SQLiteConnector::with_path(&local.directory().join("server_cache.db"));
+    Box::new(HTTPConnector::new(url, token, local, espejo))
+}
+
+impl DBConnector for HTTPConnector {
+    // Operaciones con listas
+    fn create_new_list(&self, nombre: String) -> Result<usize, anyhow::Error> {
+        if nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        let id = self
+            .escribir(Operacion::CrearLista {
+                id_local: 0,
+                nombre,
+            })?
+            .unwrap_or_default();
+        // El servidor no sabe quién usa este dispositivo
+        if let Some(miembro) = self.local.current_member() {
+            self.set_list_owner(id, Some(miembro))?;
+        }
+        Ok(id)
+    }
+
+    fn update_list(
+        &self,
+        id: usize,
+        nombre: String,
+        modo_simple: usize,
+    ) -> Result<(), anyhow::Error> {
+        if nombre.trim().is_empty() {
+            return Ok(());
+        }
+        self.escribir(Operacion::CambiarLista {
+            id,
+            nombre,
+            modo_simple,
+        })?;
+        Ok(())
+    }
+
+    fn update_list_tax_rate(&self, id: usize, tasa_impuesto: f32) -> Result<(), anyhow::Error> {
+        let tasa_impuesto = validar_tasa(tasa_impuesto)?;
+        self.escribir(Operacion::CambiarImpuesto { id, tasa_impuesto })?;
+        Ok(())
+    }
+
+    fn set_category_tax_rate(
+        &self,
+        id_lista: usize,
+        categoria: String,
+        tasa_impuesto: Option<f32>,
+    ) -> Result<(), anyhow::Error> {
+        let tasa_impuesto = tasa_impuesto.map(validar_tasa).transpose()?;
+        self.escribir(Operacion::CambiarImpuestoCategoria {
+            id: id_lista,
+            categoria,
+            tasa_impuesto,
+        })?;
+        Ok(())
+    }
+
+    fn get_list_of_lists(&self) -> Result<Vec<Lista>, anyhow::Error> {
+        match self.leer::<Vec<Lista>>("/listas") {
+            Ok(listas) => {
+                self.espejo.replace_lists(&listas)?;
+                Ok(listas)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_list_of_lists(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn get_list(&self, id_lista: usize) -> Result<Lista, anyhow::Error> {
+        // Una lista creada sin conexión sólo existe en la copia local hasta que se envía
+        if id_lista >= PRIMER_ID_LOCAL && self.id_lista(id_lista) == id_lista {
+            _ = self.sincronizar_pendientes();
+            if self.id_lista(id_lista) == id_lista {
+                return self.espejo.get_list(id_lista);
+            }
+        }
+        let id_lista = self.id_lista(id_lista);
+        match self.leer::<Lista>(&format!("/listas/{id_lista}")) {
+            Ok(lista) => {
+                self.espejo.store_list(&lista)?;
+                Ok(lista)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_list(id_lista),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn delete_list(&self, id_lista: usize) -> Result<(), anyhow::Error> {
+        self.escribir(Operacion::BorrarLista { id: id_lista })?;
+        Ok(())
+    }
+
+    // Operaciones con items
+    fn create_new_list_item(&self, id_lista: usize, item: Item) -> Result<usize, anyhow::Error> {
+        if item.nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        let agregado_por = item.agregado_por.or(self.local.current_member());
+        let id = self.escribir(Operacion::CrearItem {
+            id_lista,
+            id_local: 0,
+            item: Item {
+                agregado_por,
+                ..item
+            },
+        })?;
+        Ok(id.unwrap_or_default())
+    }
+
+    fn update_list_item(&self, item: Item) -> Result<(), anyhow::Error> {
+        if !item.nombre.trim().is_empty() {
+            // Sólo se usa si el item no estaba comprado
+            let comprado_por = item.comprado_por.or(self.local.current_member());
+            self.escribir(Operacion::CambiarItem {
+                item: Item {
+                    comprado_por,
+                    ..item
+                },
+            })?;
+        }
+        Ok(())
+    }
+
+    fn delete_item(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.escribir(Operacion::BorrarItem { id })?;
+        Ok(())
+    }
+
+    fn clear_list_items(&self, id_lista: usize) -> Result<(), anyhow::Error> {
+        self.escribir(Operacion::LimpiarLista { id: id_lista })?;
+        Ok(())
+    }
+
+    // Operaciones con imágenes de items
+    fn set_item_image(&self, id_item: usize, imagen: Imagen) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        let id_item = self.id_item(id_item);
+        self.pedir(
+            "PUT",
+            &format!("/items/{id_item}/imagen"),
+            cuerpo(&ImagenApi::from(imagen))?,
+        )?;
+        Ok(())
+    }
+
+    fn get_item_thumbnail(&self, id_item: usize) -> Result<Option<Imagen>, anyhow::Error> {
+        self.imagen(&format!("/items/{}/miniatura", self.id_item(id_item)))
+    }
+
+    fn get_item_image(&self, id_item: usize) -> Result<Option<Imagen>, anyhow::Error> {
+        self.imagen(&format!("/items/{}/imagen", self.id_item(id_item)))
+    }
+
+    fn delete_item_image(&self, id_item: usize) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        let id_item = self.id_item(id_item);
+        self.pedir("DELETE", &format!("/items/{id_item}/imagen"), Vec::new())?;
+        Ok(())
+    }
+
+    // Búsqueda de items en todas las listas
+    fn search_items(&self, texto: String) -> Result<Vec<ResultadoBusqueda>, anyhow::Error> {
+        let resultado = self.sincronizar_pendientes().and_then(|()| {
+            let datos = self.pedir(
+                "POST",
+                "/buscar",
+                cuerpo(&Busqueda {
+                    texto: texto.clone(),
+                })?,
+            )?;
+            Ok(serde_json::from_slice(&datos)?)
+        });
+        match resultado {
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.search_items(texto),
+            resultado => Ok(resultado?),
+        }
+    }
+
+    fn find_item_by_barcode(&self, codigo: &str) -> Result<Option<Item>, anyhow::Error> {
+        match self.leer::<Option<Item>>(&format!("/codigos/{}", normalizar_codigo(codigo))) {
+            Ok(item) => Ok(item),
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.find_item_by_barcode(codigo),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    // Respaldos de la base de datos, que se guardan en el servidor
+    fn create_backup(&self) -> Result<Respaldo, anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        Ok(serde_json::from_slice(&self.pedir(
+            "POST",
+            "/respaldos",
+            Vec::new(),
+        )?)?)
+    }
+
+    fn list_backups(&self) -> Result<Vec<Respaldo>, anyhow::Error> {
+        Ok(self.leer("/respaldos")?)
+    }
+
+    fn restore_backup(&self, nombre: String) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir(
+            "POST",
+            &format!("/respaldos/{nombre}/restaurar"),
+            Vec::new(),
+        )?;
+        Ok(())
+    }
+
+    fn restore_backup_data(&self, datos: Vec<u8>) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir("POST", "/respaldos/restaurar", datos)?;
+        Ok(())
+    }
+
+    // Configuración de este dispositivo
+    fn get_setting(&self, clave: &str) -> Result<Option<String>, anyhow::Error> {
+        self.local.get_setting(clave)
+    }
+
+    fn set_setting(&self, clave: &str, valor: &str) -> Result<(), anyhow::Error> {
+        self.local.set_setting(clave, valor)
+    }
+
+    // Miembros del hogar, que se guardan en el servidor
+    fn get_members(&self) -> Result<Vec<Miembro>, anyhow::Error> {
+        match self.leer::<Vec<Miembro>>("/miembros") {
+            Ok(miembros) => {
+                self.espejo.replace_members(&miembros)?;
+                Ok(miembros)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_members(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn create_member(&self, nombre: String) -> Result<usize, anyhow::Error> {
+        if nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        self.sincronizar_pendientes()?;
+        let datos = self.pedir("POST", "/miembros", cuerpo(&NombreMiembro { nombre })?)?;
+        Ok(serde_json::from_slice::<Creado>(&datos)?.id)
+    }
+
+    fn rename_member(&self, id: usize, nombre: String) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir(
+            "PUT",
+            &format!("/miembros/{id}"),
+            cuerpo(&NombreMiembro { nombre })?,
+        )?;
+        Ok(())
+    }
+
+    fn delete_member(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir("DELETE", &format!("/miembros/{id}"), Vec::new())?;
+        if self.local.current_member() == Some(id) {
+            self.local.set_setting(CLAVE_MIEMBRO_ACTUAL, "")?;
+        }
+        Ok(())
+    }
+
+    fn set_list_owner(
+        &self,
+        id_lista: usize,
+        propietario: Option<usize>,
+    ) -> Result<(), anyhow::Error> {
+        self.escribir(Operacion::CambiarPropietario {
+            id: id_lista,
+            propietario,
+        })?;
+        Ok(())
+    }
+
+    fn set_list_assignees(
+        &self,
+        id_lista: usize,
+        asignados: Vec<usize>,
+    ) -> Result<(), anyhow::Error> {
+        self.escribir(Operacion::CambiarAsignados {
+            id: id_lista,
+            asignados,
+        })?;
+        Ok(())
+    }
+
+    // Gastos compartidos, que se guardan en el servidor
+    fn get_expenses(&self) -> Result<Vec<Gasto>, anyhow::Error> {
+        match self.leer::<Vec<Gasto>>("/gastos") {
+            Ok(gastos) => {
+                self.espejo.replace_expenses(&gastos)?;
+                Ok(gastos)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_expenses(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn record_expense(&self, gasto: Gasto) -> Result<usize, anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        let datos = self.pedir("POST", "/gastos", cuerpo(&gasto)?)?;
+        Ok(serde_json::from_slice::<Creado>(&datos)?.id)
+    }
+
+    fn delete_expense(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir("DELETE", &format!("/gastos/{id}"), Vec::new())?;
+        Ok(())
+    }
+
+    // Despensa, que se guarda en el servidor. Lo comprado se suma al aplicar los cambios de los
+    // items, tanto en el servidor como en la copia local
+    fn get_pantry(&self) -> Result<Vec<Producto>, anyhow::Error> {
+        match self.leer::<Vec<Producto>>("/despensa") {
+            Ok(productos) => {
+                self.espejo.replace_pantry(&productos)?;
+                Ok(productos)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_pantry(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn save_pantry_item(&self, producto: Producto) -> Result<usize, anyhow::Error> {
+        if producto.nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        self.sincronizar_pendientes()?;
+        if producto.id == 0 {
+            let datos = self.pedir("POST", "/despensa", cuerpo(&producto)?)?;
+            Ok(serde_json::from_slice::<Creado>(&datos)?.id)
+        } else {
+            self.pedir(
+                "PUT",
+                &format!("/despensa/{}", producto.id),
+                cuerpo(&producto)?,
+            )?;
+            Ok(producto.id)
+        }
+    }
+
+    fn delete_pantry_item(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir("DELETE", &format!("/despensa/{id}"), Vec::new())?;
+        Ok(())
+    }
+
+    // Vencimientos, que se guardan en el servidor
+    fn get_expirations(&self) -> Result<Vec<Vencimiento>, anyhow::Error> {
+        match self.leer::<Vec<Vencimiento>>("/vencimientos") {
+            Ok(vencimientos) => {
+                self.espejo.replace_expirations(&vencimientos)?;
+                Ok(vencimientos)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_expirations(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn add_expiration(&self, vencimiento: Vencimiento) -> Result<usize, anyhow::Error> {
+        if vencimiento.nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        self.sincronizar_pendientes()?;
+        let datos = self.pedir("POST", "/vencimientos", cuerpo(&vencimiento)?)?;
+        Ok(serde_json::from_slice::<Creado>(&datos)?.id)
+    }
+
+    fn delete_expiration(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir("DELETE", &format!("/vencimientos/{id}"), Vec::new())?;
+        Ok(())
+    }
+
+    // Recetas, que se guardan en el servidor
+    fn get_recipes(&self) -> Result<Vec<Receta>, anyhow::Error> {
+        match self.leer::<Vec<Receta>>("/recetas") {
+            Ok(recetas) => {
+                self.espejo.replace_recipes(&recetas)?;
+                Ok(recetas)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_recipes(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn save_recipe(&self, receta: Receta) -> Result<usize, anyhow::Error> {
+        if receta.nombre.trim().is_empty() {
+            return Ok(0);
+        }
+        self.sincronizar_pendientes()?;
+        if receta.id == 0 {
+            let datos = self.pedir("POST", "/recetas", cuerpo(&receta)?)?;
+            Ok(serde_json::from_slice::<Creado>(&datos)?.id)
+        } else {
+            self.pedir("PUT", &format!("/recetas/{}", receta.id), cuerpo(&receta)?)?;
+            Ok(receta.id)
+        }
+    }
+
+    fn delete_recipe(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir("DELETE", &format!("/recetas/{id}"), Vec::new())?;
+        Ok(())
+    }
+
+    // Menú, que se guarda en el servidor
+    fn get_meals(&self) -> Result<Vec<Comida>, anyhow::Error> {
+        match self.leer::<Vec<Comida>>("/menu") {
+            Ok(comidas) => {
+                self.espejo.replace_meals(&comidas)?;
+                Ok(comidas)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_meals(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn save_meal(&self, comida: Comida) -> Result<usize, anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        if comida.id == 0 {
+            let datos = self.pedir("POST", "/menu", cuerpo(&comida)?)?;
+            Ok(serde_json::from_slice::<Creado>(&datos)?.id)
+        } else {
+            self.pedir("PUT", &format!("/menu/{}", comida.id), cuerpo(&comida)?)?;
+            Ok(comida.id)
+        }
+    }
+
+    fn delete_meal(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir("DELETE", &format!("/menu/{id}"), Vec::new())?;
+        Ok(())
+    }
+
+    // Sinónimos, que se guardan en el servidor
+    fn get_synonyms(&self) -> Result<Vec<Sinonimo>, anyhow::Error> {
+        match self.leer::<Vec<Sinonimo>>("/sinonimos") {
+            Ok(sinonimos) => {
+                self.espejo.replace_synonyms(&sinonimos)?;
+                Ok(sinonimos)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_synonyms(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn save_synonym(&self, sinonimo: Sinonimo) -> Result<usize, anyhow::Error> {
+        if sinonimo.alias.trim().is_empty() || sinonimo.canonico.trim().is_empty() {
+            return Ok(0);
+        }
+        self.sincronizar_pendientes()?;
+        if sinonimo.id == 0 {
+            let datos = self.pedir("POST", "/sinonimos", cuerpo(&sinonimo)?)?;
+            Ok(serde_json::from_slice::<Creado>(&datos)?.id)
+        } else {
+            self.pedir(
+                "PUT",
+                &format!("/sinonimos/{}", sinonimo.id),
+                cuerpo(&sinonimo)?,
+            )?;
+            Ok(sinonimo.id)
+        }
+    }
+
+    fn delete_synonym(&self, id: usize) -> Result<(), anyhow::Error> {
+        self.sincronizar_pendientes()?;
+        self.pedir("DELETE", &format!("/sinonimos/{id}"), Vec::new())?;
+        Ok(())
+    }
+
+    // Sin conexión se usan los nombres de la copia local
+    fn get_product_names(&self) -> Result<Vec<String>, anyhow::Error> {
+        match self.leer::<Vec<String>>("/nombres") {
+            Ok(nombres) => Ok(nombres),
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_product_names(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    // El historial de compras lo anota el servidor al cambiar los items
+    fn get_purchases(&self) -> Result<Vec<Compra>, anyhow::Error> {
+        match self.leer::<Vec<Compra>>("/compras") {
+            Ok(compras) => {
+                self.espejo.replace_purchases(&compras)?;
+                Ok(compras)
+            }
+            Err(ErrorHttp::SinConexion(_)) => self.espejo.get_purchases(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    // Con un servidor configurado no se sincroniza directamente con otros dispositivos
+    fn local_sync(&self) -> Option<&dyn LocalSync> {
+        None
+    }
+
+    fn server_connection(&self) -> Option<&dyn ServerConnection> {
+        Some(self)
+    }
+}
+
+impl ServerConnection for HTTPConnector {
+    fn connection_status(&self) -> EstadoConexion {
+        let pendientes = self
+            .espejo
+            .pending_operations()
+            .map(|pendientes| pendientes.len())
+            .unwrap_or_default();
+        let rechazados = self.rechazados();
+        if pendientes > 0 || ahora() < self.reintentar_desde.get() {
+            EstadoConexion::SinConexion { pendientes }
+        } else if rechazados > 0 {
+            EstadoConexion::Rechazados { rechazados }
+        } else {
+            EstadoConexion::EnLinea
+        }
+    }
+
+    fn retry_pending(&self) -> Result<(), anyhow::Error> {
+        self.reintentar_desde.set(0);
+        Ok(self.sincronizar_pendientes()?)
+    }
+
+    fn dismiss_rejected(&self) {
+        _ = self.espejo.set_setting(CLAVE_RECHAZADOS, "0");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::database::DirectorioPrueba;
+    use std::sync::{Arc, Mutex};
+
+    type Respuesta = fn(&str, &str) -> (u16, String);
+
+    // Servidor de prueba que responde según el método y la ruta, y anota lo que recibe
+    fn servidor(responder: Respuesta) -> (String, Arc<Mutex<Vec<String>>>) {
+        let servidor = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", servidor.server_addr());
+        let recibidas = Arc::new(Mutex::new(Vec::new()));
+        let anotadas = recibidas.clone();
+        std::thread::spawn(move || {
+            for peticion in servidor.incoming_requests() {
+                let metodo = peticion.method().to_string();
+                let ruta = peticion.url().trim_start_matches(PREFIJO_API).to_string();
+                anotadas.lock().unwrap().push(format!("{metodo} {ruta}"));
+                let (estado, cuerpo) = responder(&metodo, &ruta);
+                _ = peticion
+                    .respond(tiny_http::Response::from_string(cuerpo).with_status_code(estado));
+            }
+        });
+        (url, recibidas)
+    }
+
+    fn conector(url: &str) -> (HTTPConnector, DirectorioPrueba) {
+        let directorio = DirectorioPrueba::new();
+        let conector = HTTPConnector::new(
+            url.to_string(),
+            "token".to_string(),
+            directorio.conector("local.db"),
+            directorio.conector("server_cache.db"),
+        );
+        (conector, directorio)
+    }
+
+    fn pendientes(conector: &HTTPConnector) -> usize {
+        conector.espejo.pending_operations().unwrap().len()
+    }
+
+    #[test]
+    fn conserva_los_cambios_si_el_servidor_falla() {
+        let (url, recibidas) = servidor(|_, _| (503, r#"{"error":"Try again later"}"#.to_string()));
+        let (conector, _directorio) = conector(&url);
+
+        let id = conector.create_new_list("Feria".to_string()).unwrap();
+        assert!(id >= PRIMER_ID_LOCAL);
+        assert_eq!(conector.get_list(id).unwrap().nombre, "Feria");
+        assert_eq!(pendientes(&conector), 1);
+        conector.retry_pending().unwrap_err();
+        assert_eq!(pendientes(&conector), 1);
+        assert_eq!(
+            conector.connection_status(),
+            EstadoConexion::SinConexion { pendientes: 1 }
+        );
+        assert_eq!(recibidas.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn descarta_y_avisa_los_cambios_rechazados() {
+        let (url, _) = servidor(|metodo, _| match metodo {
+            "PUT" => (404, r#"{"error":"Not found"}"#.to_string()),
+            _ => (200, "[]".to_string()),
+        });
+        let (conector, _directorio) = conector(&url);
+        conector.reintentar_desde.set(ahora() + ESPERA_RECONEXION);
+        conector.update_list(7, "Feria".to_string(), 0).unwrap();
+        assert_eq!(pendientes(&conector), 1);
+
+        conector.retry_pending().unwrap();
+        assert_eq!(pendientes(&conector), 0);
+        assert_eq!(
+            conector.connection_status(),
+            EstadoConexion::Rechazados { rechazados: 1 }
+        );
+        conector.dismiss_rejected();
+        assert_eq!(conector.connection_status(), EstadoConexion::EnLinea);
+    }
+
+    #[test]
+    fn usa_ids_locales_que_no_chocan_con_los_del_servidor() {
+        let (url, recibidas) = servidor(|metodo, ruta| match (metodo, ruta) {
+            ("POST", "/listas") => (200, r#"{"id":3}"#.to_string()),
+            ("POST", "/listas/3/items") => (200, r#"{"id":9}"#.to_string()),
+            ("GET", "/listas/3") => (
+                200,
+                serde_json::to_string(&Lista {
+                    id: 3,
+                    nombre: "Feria".to_string(),
+                    items: Some(Vec::new()),
+                    ..Lista::default()
+                })
+                .unwrap(),
+            ),
+            _ => (204, String::new()),
+        });
+        let (conector, _directorio) = conector(&url);
+        conector.reintentar_desde.set(ahora() + ESPERA_RECONEXION);
+
+        let id = conector.create_new_list("Feria".to_string()).unwrap();
+        conector
+            .create_new_list_item(
+                id,
+                Item {
+                    nombre: "Pan".to_string(),
+                    ..Item::default()
+                },
+            )
+            .unwrap();
+        let item = conector.get_list(id).unwrap().items.unwrap().remove(0);
+        assert!(id >= PRIMER_ID_LOCAL && item.id >= PRIMER_ID_LOCAL);
+
+        // Al reconectar, la vista que todavía tiene el id local lee la lista del servidor
+        conector.retry_pending().unwrap();
+        assert_eq!(conector.get_list(id).unwrap().id, 3);
+        assert_eq!(
+            *recibidas.lock().unwrap(),
+            ["POST /listas", "POST /listas/3/items", "GET /listas/3"]
+        );
+    }
+
+    #[test]
+    fn cambia_en_el_servidor_un_item_creado_sin_conexion() {
+        let (url, recibidas) = servidor(|metodo, ruta| match (metodo, ruta) {
+            ("POST", "/listas/3/items") => (200, r#"{"id":12}"#.to_string()),
+            ("PUT", "/items/12") => (204, String::new()),
+            _ => (404, r#"{"error":"Not found"}"#.to_string()),
+        });
+        let (conector, _directorio) = conector(&url);
+        conector
+            .espejo
+            .store_list(&Lista {
+                id: 3,
+                nombre: "Feria".to_string(),
+                items: Some(Vec::new()),
+                ..Lista::default()
+            })
+            .unwrap();
+        conector.reintentar_desde.set(ahora() + ESPERA_RECONEXION);
+
+        let item = Item {
+            nombre: "Pan".to_string(),
+            ..Item::default()
+        };
+        let id = conector.create_new_list_item(3, item.clone()).unwrap();
+        assert!(id >= PRIMER_ID_LOCAL);
+
+        // La vista sigue usando el id local después de reconectar
+        conector.retry_pending().unwrap();
+        conector
+            .update_list_item(Item {
+                id,
+                id_lista: 3,
+                cantidad_comprada: 1.0,
+                ..item
+            })
+            .unwrap();
+        assert_eq!(
+            *recibidas.lock().unwrap(),
+            ["POST /listas/3/items", "PUT /items/12"]
+        );
+        assert_eq!(pendientes(&conector), 0);
+        assert_eq!(conector.connection_status(), EstadoConexion::EnLinea);
+    }
+}
@@ -0,0 +1,40 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::ImageFormat;
+use std::io::Cursor;
+
+const LADO_MINIATURA: u32 = 160;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Imagen {
+    pub tipo: String,
+    pub datos: Vec<u8>,
+}
+
+impl Imagen {
+    // Valida que los datos sean una imagen y detecta su tipo MIME
+    pub fn nueva(datos: Vec<u8>) -> Result<Imagen, anyhow::Error> {
+        let formato = image::guess_format(&datos)?;
+        image::load_from_memory_with_format(&datos, formato)?;
+        Ok(Imagen {
+            tipo: formato.to_mime_type().to_string(),
+            datos,
+        })
+    }
+
+    // Genera una versión reducida en JPEG para mostrar en las tarjetas
+    pub fn miniatura(&self) -> Result<Imagen, anyhow::Error> {
+        let original = image::load_from_memory(&self.datos)?;
+        let reducida = original.thumbnail(LADO_MINIATURA, LADO_MINIATURA).to_rgb8();
+        let mut datos = Vec::new();
+        reducida.write_to(&mut Cursor::new(&mut datos), ImageFormat::Jpeg)?;
+        Ok(Imagen {
+            tipo: ImageFormat::Jpeg.to_mime_type().to_string(),
+            datos,
+        })
+    }
+
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.tipo, STANDARD.encode(&self.datos))
+    }
+}
@@ -0,0 +1,562 @@
+use crate::model::compartir::decodificar;
+use crate::model::export::VERSION_EXPORTACION;
+use crate::model::{Item, Lista, Prioridad};
+use serde::Deserialize;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum FormatoImportacion {
+    Json,
+    Csv,
+    Texto,
+    Compartida,
+}
+
+impl FormatoImportacion {
+    pub const TODOS: [FormatoImportacion; 4] = [
+        FormatoImportacion::Texto,
+        FormatoImportacion::Csv,
+        FormatoImportacion::Json,
+        FormatoImportacion::Compartida,
+    ];
+
+    // Clave del mensaje traducido para mostrar el formato
+    pub fn clave(&self) -> &'static str {
+        match self {
+            FormatoImportacion::Json => "format_json",
+            FormatoImportacion::Csv => "format_csv",
+            FormatoImportacion::Texto => "format_text",
+            FormatoImportacion::Compartida => "format_shared_code",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct LineaNoReconocida {
+    pub numero: usize,
+    pub texto: String,
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Importacion {
+    pub nombre_lista: Option<String>,
+    pub items: Vec<Item>,
+    pub errores: Vec<LineaNoReconocida>,
+}
+
+#[derive(Deserialize)]
+struct Exportacion {
+    version: u32,
+    listas: Vec<Lista>,
+}
+
+// Devuelve una importación por cada lista del texto. Los formatos de una sola lista devuelven
+// siempre una.
+pub fn importar(
+    texto: &str,
+    formato: FormatoImportacion,
+) -> Result<Vec<Importacion>, anyhow::Error> {
+    match formato {
+        FormatoImportacion::Json => from_json(texto),
+        FormatoImportacion::Csv => Ok(from_csv(texto)),
+        FormatoImportacion::Texto => Ok(vec![from_text(texto)]),
+        FormatoImportacion::Compartida => Ok(vec![decodificar(texto)?]),
+    }
+}
+
+// Un item importado es un item nuevo y pendiente: no se trae lo comprado ni los miembros, que
+// son de otro hogar
+fn item_importado(item: Item) -> Item {
+    Item {
+        id: 0,
+        id_lista: 0,
+        cantidad_comprada: 0.0,
+        agregado_por: None,
+        comprado_por: None,
+        asignado_a: None,
+        ..item
+    }
+}
+
+pub fn from_json(texto: &str) -> Result<Vec<Importacion>, anyhow::Error> {
+    let exportacion: Exportacion = serde_json::from_str(texto)?;
+    if exportacion.version > VERSION_EXPORTACION {
+        anyhow::bail!(
+            "Unsupported export version {} (latest known is {VERSION_EXPORTACION})",
+            exportacion.version
+        );
+    }
+    Ok(exportacion
+        .listas
+        .into_iter()
+        .map(|lista| Importacion {
+            nombre_lista: Some(lista.nombre),
+            items: lista
+                .items
+                .unwrap_or_default()
+                .into_iter()
+                .map(item_importado)
+                .collect(),
+            errores: Vec::new(),
+        })
+        .collect())
+}
+
+// La importación de la lista con ese nombre, que se agrega si todavía no está
+fn importacion_de<'a>(
+    importaciones: &'a mut Vec<Importacion>,
+    nombre_lista: Option<&str>,
+) -> &'a mut Importacion {
+    let posicion = match importaciones
+        .iter()
+        .position(|importacion| importacion.nombre_lista.as_deref() == nombre_lista)
+    {
+        Some(posicion) => posicion,
+        None => {
+            importaciones.push(Importacion {
+                nombre_lista: nombre_lista.map(String::from),
+                ..Importacion::default()
+            });
+            importaciones.len() - 1
+        }
+    };
+    &mut importaciones[posicion]
+}
+
+// Las filas se agrupan según la columna "lista", así una exportación de varias listas vuelve a
+// dar varias listas
+pub fn from_csv(texto: &str) -> Vec<Importacion> {
+    let mut importaciones = Vec::new();
+    let mut registros = leer_registros_csv(texto).into_iter();
+    let Some((_, encabezado)) = registros.next() else {
+        return vec![Importacion::default()];
+    };
+    let columna = |nombre: &str| encabezado.iter().position(|campo| campo.trim() == nombre);
+    let Some(columna_nombre) = columna("nombre") else {
+        return vec![Importacion {
+            errores: vec![LineaNoReconocida {
+                numero: 1,
+                texto: encabezado.join(","),
+            }],
+            ..Importacion::default()
+        }];
+    };
+    let columna_lista = columna("lista");
+    let columna_unidad = columna("unidad");
+    let columna_requerida = columna("cantidad_requerida");
+    let columna_precio = columna("precio");
+    let columna_incluye_impuesto = columna("precio_incluye_impuesto");
+    let columna_prioridad = columna("prioridad");
+    let columna_notas = columna("notas");
+
+    for (numero, campos) in registros {
+        if campos.iter().all(|campo| campo.trim().is_empty()) {
+            continue;
+        }
+        let campo = |columna: Option<usize>| {
+            columna
+                .and_then(|columna| campos.get(columna))
+                .map(|campo| campo.trim())
+                .filter(|campo| !campo.is_empty())
+        };
+        let numero_en = |columna: Option<usize>, omision: f32| match campo(columna) {
+            Some(valor) => leer_numero(valor),
+            None => Some(omision),
+        };
+        let importacion = importacion_de(&mut importaciones, campo(columna_lista));
+        let nombre = campo(Some(columna_nombre));
+        let unidad = match campo(columna_unidad) {
+            Some(unidad) => leer_unidad(unidad),
+            None => Some(("unidad", 1.0)),
+        };
+        let requerida = numero_en(columna_requerida, 1.0);
+        let precio = numero_en(columna_precio, 0.0);
+        let (Some(nombre), Some((unidad, factor)), Some(requerida), Some(precio)) =
+            (nombre, unidad, requerida, precio)
+        else {
+            importacion.errores.push(LineaNoReconocida {
+                numero,
+                texto: campos.join(","),
+            });
+            continue;
+        };
+        // El precio es por unidad, así que se divide por el mismo factor
+        importacion.items.push(Item {
+            nombre: nombre.to_string(),
+            unidad: unidad.to_string(),
+            cantidad_requerida: requerida * factor,
+            precio: precio / factor,
+            precio_incluye_impuesto: campo(columna_incluye_impuesto) != Some("0"),
+            notas: campo(columna_notas).unwrap_or_default().to_string(),
+            prioridad: campo(columna_prioridad)
+                .and_then(|prioridad| prioridad.parse::<usize>().ok())
+                .map(Prioridad::from)
+                .unwrap_or_default(),
+            ..Item::default()
+        });
+    }
+    if importaciones.is_empty() {
+        importaciones.push(Importacion::default());
+    }
+    importaciones
+}
+
+// Interpreta texto libre como "2 kg tomates", "1 dozen eggs" o "leche", una línea por item.
+// También acepta las viñetas y checklists que genera la exportación.
+pub fn from_text(texto: &str) -> Importacion {
+    let mut importacion = Importacion::default();
+    let hay_vinetas = texto
+        .lines()
+        .any(|linea| quitar_vineta(linea.trim()).is_some());
+
+    for (indice, linea) in texto.lines().enumerate() {
+        let linea = linea.trim();
+        if linea.is_empty() {
+            continue;
+        }
+        let contenido = match quitar_vineta(linea) {
+            Some(contenido) => contenido,
+            None if linea.starts_with('#') || linea.ends_with(':') || hay_vinetas => {
+                let titulo = linea.trim_start_matches('#').trim_end_matches(':').trim();
+                if importacion.nombre_lista.is_none() && !titulo.is_empty() {
+                    importacion.nombre_lista = Some(titulo.to_string());
+                }
+                continue;
+            }
+            None => linea,
+        };
+        match parse_linea(contenido) {
+            Some(item) => importacion.items.push(item),
+            None => importacion.errores.push(LineaNoReconocida {
+                numero: indice + 1,
+                texto: linea.to_string(),
+            }),
+        }
+    }
+    importacion
+}
+
+// Convierte una línea sin viñeta en un item, o None si no se entiende
+pub fn parse_linea(linea: &str) -> Option<Item> {
+    let (linea, notas) = match linea
+        .strip_suffix(')')
+        .and_then(|resto| resto.rsplit_once('('))
+    {
+        Some((resto, notas)) => (resto.trim(), notas.trim()),
+        None => (linea, ""),
+    };
+    let mut palabras: Vec<&str> = linea.split_whitespace().collect();
+    let mut cantidad = 1.0;
+    let mut unidad = "unidad";
+
+    if let Some(primera) = palabras.first().copied() {
+        // "2", "2x", "1,5kg"; una palabra como "7up" se deja como parte del nombre
+        let fin_numero = primera
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ',' || c == '/'))
+            .unwrap_or(primera.len());
+        let pegado = &primera[fin_numero..];
+        let unidad_pegada = leer_unidad(pegado);
+        if fin_numero > 0
+            && (pegado.is_empty() || pegado.eq_ignore_ascii_case("x") || unidad_pegada.is_some())
+        {
+            cantidad = leer_numero(&primera[..fin_numero])?;
+            palabras.remove(0);
+            if let Some((unidad_leida, factor)) = unidad_pegada {
+                unidad = unidad_leida;
+                cantidad *= factor;
+            } else if let Some((unidad_leida, factor)) =
+                palabras.first().and_then(|p| leer_unidad(p))
+            {
+                unidad = unidad_leida;
+                cantidad *= factor;
+                palabras.remove(0);
+            } else if palabras
+                .first()
+                .is_some_and(|palabra| palabra.eq_ignore_ascii_case("x"))
+            {
+                palabras.remove(0);
+            }
+            if palabras.len() > 1 && ["de", "of"].contains(&palabras[0].to_lowercase().as_str()) {
+                palabras.remove(0);
+            }
+        }
+    }
+
+    let nombre = palabras.join(" ");
+    if nombre.is_empty() || cantidad <= 0.0 {
+        return None;
+    }
+    Some(Item {
+        nombre,
+        unidad: unidad.to_string(),
+        cantidad_requerida: cantidad,
+        notas: notas.to_string(),
+        ..Item::default()
+    })
+}
+
+const KG_POR_GRAMO: f32 = 0.001;
+const KG_POR_LIBRA: f32 = 0.453_592_37;
+const KG_POR_ONZA: f32 = 0.028_349_524;
+
+// Devuelve la clave de una unidad conocida a partir de sus abreviaturas en inglés o español, y el
+// factor para pasar la cantidad a esa unidad: los gramos, libras y onzas se pasan a kg
+pub fn leer_unidad(texto: &str) -> Option<(&'static str, f32)> {
+    match texto.to_lowercase().trim_end_matches('.') {
+        "u" | "un" | "und" | "unidad" | "unidades" | "unit" | "units" | "pc" | "pcs" | "pza"
+        | "pieza" | "piezas" => Some(("unidad", 1.0)),
+        "kg" | "kgs" | "kilo" | "kilos" | "kilogramo" | "kilogramos" | "kilogram" | "kilograms" => {
+            Some(("kg", 1.0))
+        }
+        "g" | "gr" | "grs" | "gramo" | "gramos" | "gram" | "grams" | "gramme" | "grammes" => {
+            Some(("kg", KG_POR_GRAMO))
+        }
+        "lb" | "lbs" | "libra" | "libras" | "pound" | "pounds" => Some(("kg", KG_POR_LIBRA)),
+        "oz" | "onza" | "onzas" | "ounce" | "ounces" => Some(("kg", KG_POR_ONZA)),
+        "docena" | "docenas" | "dozen" | "dozens" | "doz" | "dz" => Some(("docena", 1.0)),
+        _ => None,
+    }
+}
+
+pub fn leer_numero(texto: &str) -> Option<f32> {
+    if let Some((numerador, denominador)) = texto.split_once('/') {
+        let numerador: f32 = numerador.parse().ok()?;
+        let denominador: f32 = denominador.parse().ok()?;
+        return (denominador != 0.0).then(|| numerador / denominador);
+    }
+    texto.replace(',', ".").parse().ok()
+}
+
+fn quitar_vineta(linea: &str) -> Option<&str> {
+    let resto = linea.strip_prefix(['-', '*', '•', '+'])?.trim_start();
+    let resto = ["[ ]", "[x]", "[X]"]
+        .iter()
+        .find_map(|marca| resto.strip_prefix(marca))
+        .unwrap_or(resto);
+    Some(resto.trim())
+}
+
+// Separa el CSV en registros, respetando comillas y saltos de línea dentro de los campos.
+// Cada registro lleva el número de línea donde empieza.
+fn leer_registros_csv(texto: &str) -> Vec<(usize, Vec<String>)> {
+    let mut registros = Vec::new();
+    let mut campos = Vec::new();
+    let mut campo = String::new();
+    let mut entre_comillas = false;
+    let mut linea = 1;
+    let mut linea_registro = 1;
+    let mut caracteres = texto.chars().peekable();
+
+    while let Some(caracter) = caracteres.next() {
+        match caracter {
+            '"' if entre_comillas && caracteres.peek() == Some(&'"') => {
+                campo.push('"');
+                caracteres.next();
+            }
+            '"' => entre_comillas = !entre_comillas,
+            ',' if !entre_comillas => campos.push(std::mem::take(&mut campo)),
+            '\r' if !entre_comillas => {}
+            '\n' if !entre_comillas => {
+                campos.push(std::mem::take(&mut campo));
+                registros.push((linea_registro, std::mem::take(&mut campos)));
+                linea += 1;
+                linea_registro = linea;
+            }
+            _ => {
+                if caracter == '\n' {
+                    linea += 1;
+                }
+                campo.push(caracter);
+            }
+        }
+    }
+    if !campo.is_empty() || !campos.is_empty() {
+        campos.push(campo);
+        registros.push((linea_registro, campos));
+    }
+    registros
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::database::{DBConnector, DirectorioPrueba};
+    use crate::model::export::{to_csv, to_json};
+
+    fn cerca(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    fn linea(texto: &str) -> (f32, String, String, String) {
+        let item = parse_linea(texto).unwrap();
+        (
+            item.cantidad_requerida,
+            item.unidad,
+            item.nombre,
+            item.notas,
+        )
+    }
+
+    #[test]
+    fn parse_linea_lee_cantidad_unidad_y_notas() {
+        assert_eq!(
+            linea("2 kg tomates"),
+            (2.0, "kg".into(), "tomates".into(), "".into())
+        );
+        assert_eq!(
+            linea("1 dozen eggs"),
+            (1.0, "docena".into(), "eggs".into(), "".into())
+        );
+        assert_eq!(
+            linea("1,5kg de queso (fresco)"),
+            (1.5, "kg".into(), "queso".into(), "fresco".into())
+        );
+        assert_eq!(
+            linea("2 x pan"),
+            (2.0, "unidad".into(), "pan".into(), "".into())
+        );
+        assert_eq!(
+            linea("1/2 kilo jamón"),
+            (0.5, "kg".into(), "jamón".into(), "".into())
+        );
+        assert_eq!(
+            linea("leche"),
+            (1.0, "unidad".into(), "leche".into(), "".into())
+        );
+        assert_eq!(
+            linea("7up"),
+            (1.0, "unidad".into(), "7up".into(), "".into())
+        );
+        assert!(parse_linea("0 x nada").is_none());
+        assert!(parse_linea("3 kg").is_none());
+    }
+
+    #[test]
+    fn parse_linea_pasa_gramos_libras_y_onzas_a_kg() {
+        let (cantidad, unidad, nombre, _) = linea("500g harina");
+        assert!(cerca(cantidad, 0.5));
+        assert_eq!((unidad.as_str(), nombre.as_str()), ("kg", "harina"));
+        let (cantidad, unidad, _, _) = linea("2 lb ground beef");
+        assert!(cerca(cantidad, 0.907_184_7));
+        assert_eq!(unidad, "kg");
+        let (cantidad, _, _, _) = linea("8 oz. cheddar");
+        assert!(cerca(cantidad, 0.226_796_2));
+        assert_eq!(leer_unidad("Gramos"), Some(("kg", KG_POR_GRAMO)));
+        assert_eq!(leer_unidad("pcs"), Some(("unidad", 1.0)));
+        assert_eq!(leer_unidad("litro"), None);
+    }
+
+    #[test]
+    fn from_text_lee_titulo_vinetas_y_errores() {
+        let importacion = from_text("# Súper\n- [ ] 2 kg papas\n- [x] leche\n- 3 kg\n");
+        assert_eq!(importacion.nombre_lista.as_deref(), Some("Súper"));
+        assert_eq!(importacion.items.len(), 2);
+        assert_eq!(importacion.errores.len(), 1);
+        assert_eq!(importacion.errores[0].numero, 4);
+    }
+
+    fn listas_exportadas() -> Vec<Lista> {
+        let item = |nombre: &str| Item {
+            nombre: nombre.to_string(),
+            cantidad_comprada: 1.0,
+            agregado_por: Some(1),
+            comprado_por: Some(2),
+            asignado_a: Some(3),
+            ..Item::default()
+        };
+        vec![
+            Lista {
+                id: 1,
+                nombre: "Súper".into(),
+                items: Some(vec![item("Leche, entera"), item("Pan")]),
+                ..Lista::default()
+            },
+            Lista {
+                id: 2,
+                nombre: "Ferretería".into(),
+                items: Some(vec![item("Clavos")]),
+                ..Lista::default()
+            },
+        ]
+    }
+
+    fn nombres(importacion: &Importacion) -> Vec<&str> {
+        importacion
+            .items
+            .iter()
+            .map(|item| item.nombre.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn from_json_importa_cada_lista_aparte_y_sin_lo_comprado() {
+        let importaciones = from_json(&to_json(&listas_exportadas()).unwrap()).unwrap();
+        assert_eq!(importaciones.len(), 2);
+        assert_eq!(importaciones[0].nombre_lista.as_deref(), Some("Súper"));
+        assert_eq!(nombres(&importaciones[0]), ["Leche, entera", "Pan"]);
+        assert_eq!(importaciones[1].nombre_lista.as_deref(), Some("Ferretería"));
+        assert_eq!(nombres(&importaciones[1]), ["Clavos"]);
+        for item in importaciones
+            .iter()
+            .flat_map(|importacion| &importacion.items)
+        {
+            assert_eq!(item.cantidad_comprada, 0.0);
+            assert_eq!(
+                (item.agregado_por, item.comprado_por, item.asignado_a),
+                (None, None, None)
+            );
+        }
+        assert!(from_json("{\"version\":9,\"listas\":[]}").is_err());
+    }
+
+    #[test]
+    fn from_csv_agrupa_por_lista() {
+        let importaciones = from_csv(&to_csv(&listas_exportadas()));
+        assert_eq!(importaciones.len(), 2);
+        assert_eq!(nombres(&importaciones[0]), ["Leche, entera", "Pan"]);
+        assert_eq!(nombres(&importaciones[1]), ["Clavos"]);
+        assert!(
+            importaciones[0]
+                .items
+                .iter()
+                .all(|item| item.cantidad_comprada == 0.0)
+        );
+    }
+
+    #[test]
+    fn from_csv_convierte_unidades_y_marca_errores() {
+        let importaciones = from_csv(
+            "nombre,unidad,cantidad_requerida,precio\nharina,g,500,0.002\nleche,u,1,abc\n",
+        );
+        assert_eq!(importaciones.len(), 1);
+        let importacion = &importaciones[0];
+        assert_eq!(importacion.nombre_lista, None);
+        assert_eq!(importacion.items.len(), 1);
+        assert_eq!(importacion.items[0].unidad, "kg");
+        assert!(cerca(importacion.items[0].cantidad_requerida, 0.5));
+        assert!(cerca(importacion.items[0].precio, 2.0));
+        assert_eq!(importacion.errores.len(), 1);
+        assert_eq!(importacion.errores[0].numero, 3);
+
+        let sin_nombre = from_csv("producto\nleche\n");
+        assert_eq!(sin_nombre[0].errores[0].numero, 1);
+    }
+
+    #[test]
+    fn importar_no_cambia_la_despensa_ni_el_historial() {
+        let directorio = DirectorioPrueba::new();
+        let conector = directorio.conector("shopping_list.db");
+
+        let texto = to_json(&listas_exportadas()).unwrap();
+        for importacion in importar(&texto, FormatoImportacion::Json).unwrap() {
+            let id_lista = conector
+                .create_new_list(importacion.nombre_lista.unwrap())
+                .unwrap();
+            for item in importacion.items {
+                conector.create_new_list_item(id_lista, item).unwrap();
+            }
+        }
+        assert_eq!(conector.get_list_of_lists().unwrap().len(), 2);
+        assert!(conector.get_pantry().unwrap().is_empty());
+        assert!(conector.get_purchases().unwrap().is_empty());
+    }
+}
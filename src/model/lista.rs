@@ -1,15 +1,66 @@
-use serde::Deserialize;
+use crate::model::codigo_barras::normalizar_codigo;
+use crate::model::texto::normalizar;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Lista {
     pub id: usize,
     pub nombre: String,
     pub items: Option<Vec<Item>>,
+    pub subtotal: f32,
+    pub impuesto: f32,
     pub total: f32,
     pub modo_simple: bool,
+    pub tasa_impuesto: f32,
+    // Miembro del hogar dueño de la lista, y los que además pueden encargarse de ella
+    #[serde(default)]
+    pub propietario: Option<usize>,
+    #[serde(default)]
+    pub asignados: Vec<usize>,
+    // Tasas de impuesto de algunas categorías, que reemplazan la de la lista
+    #[serde(default)]
+    pub tasas_categoria: BTreeMap<String, f32>,
 }
 
-#[derive(PartialEq, Clone, Debug, Deserialize)]
+// Las tasas de impuesto son porcentajes. Con -100 el precio con impuesto incluido se dividiría
+// por cero
+pub fn validar_tasa(tasa_impuesto: f32) -> Result<f32, anyhow::Error> {
+    if !(0.0..=100.0).contains(&tasa_impuesto) {
+        anyhow::bail!("The tax rate must be between 0 and 100");
+    }
+    Ok(tasa_impuesto)
+}
+
+impl Lista {
+    // Tasa de impuesto de un item: la de su categoría si tiene una propia, o la de la lista
+    pub fn tasa_de(&self, item: &Item) -> f32 {
+        let categoria = normalizar(&item.categoria);
+        self.tasas_categoria
+            .iter()
+            .find(|(nombre, _)| !categoria.is_empty() && normalizar(nombre) == categoria)
+            .map(|(_, tasa)| *tasa)
+            .unwrap_or(self.tasa_impuesto)
+    }
+
+    // Recalcula subtotal, impuesto y total con los items comprados
+    pub fn calcular_totales(&mut self) {
+        let (subtotal, impuesto) = self
+            .items
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|item| item.subtotal_e_impuesto(self.tasa_de(item)))
+            .fold((0.0, 0.0), |acc, (subtotal, impuesto)| {
+                (acc.0 + subtotal, acc.1 + impuesto)
+            });
+        self.subtotal = subtotal;
+        self.impuesto = impuesto;
+        self.total = subtotal + impuesto;
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
 pub struct Item {
     pub id: usize,
     pub id_lista: usize,
@@ -18,6 +69,77 @@ pub struct Item {
     pub cantidad_requerida: f32,
     pub cantidad_comprada: f32,
     pub precio: f32,
+    pub precio_incluye_impuesto: bool,
+    #[serde(default)]
+    pub notas: String,
+    #[serde(default)]
+    pub prioridad: Prioridad,
+    // Miembros del hogar que agregaron y compraron el item
+    #[serde(default)]
+    pub agregado_por: Option<usize>,
+    #[serde(default)]
+    pub comprado_por: Option<usize>,
+    // Sección de la tienda, para repartir la compra
+    #[serde(default)]
+    pub categoria: String,
+    #[serde(default)]
+    pub asignado_a: Option<usize>,
+    // EAN-8 o EAN-13 (los UPC-A se guardan como EAN-13), o vacío
+    #[serde(default)]
+    pub codigo_barras: String,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub enum Prioridad {
+    Imprescindible = 0,
+    #[default]
+    Normal = 1,
+    Opcional = 2,
+}
+
+impl Prioridad {
+    pub const TODAS: [Prioridad; 3] = [
+        Prioridad::Imprescindible,
+        Prioridad::Normal,
+        Prioridad::Opcional,
+    ];
+
+    // Clave del mensaje traducido para mostrar la prioridad
+    pub fn clave(&self) -> &'static str {
+        match self {
+            Prioridad::Imprescindible => "must_have",
+            Prioridad::Normal => "normal",
+            Prioridad::Opcional => "nice_to_have",
+        }
+    }
+}
+
+impl From<usize> for Prioridad {
+    fn from(valor: usize) -> Prioridad {
+        match valor {
+            0 => Prioridad::Imprescindible,
+            2 => Prioridad::Opcional,
+            _ => Prioridad::Normal,
+        }
+    }
+}
+
+impl Item {
+    pub fn comprado(&self) -> bool {
+        self.cantidad_comprada >= 0.001
+    }
+
+    // Devuelve (subtotal, impuesto) de lo comprado, con la tasa en porcentaje
+    pub fn subtotal_e_impuesto(&self, tasa_impuesto: f32) -> (f32, f32) {
+        let bruto = self.cantidad_comprada * self.precio;
+        let tasa = tasa_impuesto / 100.0;
+        if self.precio_incluye_impuesto {
+            let subtotal = bruto / (1.0 + tasa);
+            (subtotal, bruto - subtotal)
+        } else {
+            (bruto, bruto * tasa)
+        }
+    }
 }
 
 impl Default for Item {
@@ -30,10 +152,98 @@ impl Default for Item {
             cantidad_requerida: 1.0,
             cantidad_comprada: 0.0,
             precio: 0.0,
+            precio_incluye_impuesto: true,
+            notas: String::from(""),
+            prioridad: Prioridad::Normal,
+            agregado_por: None,
+            comprado_por: None,
+            categoria: String::from(""),
+            asignado_a: None,
+            codigo_barras: String::from(""),
         }
     }
 }
 
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Miembro {
+    pub id: usize,
+    pub nombre: String,
+}
+
+// Nombre de un miembro para mostrar, si todavía existe
+pub fn nombre_miembro(miembros: &[Miembro], id: Option<usize>) -> Option<String> {
+    miembros
+        .iter()
+        .find(|miembro| Some(miembro.id) == id)
+        .map(|miembro| miembro.nombre.clone())
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct ResultadoBusqueda {
+    pub item: Item,
+    pub nombre_lista: String,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum EstadoItem {
+    #[default]
+    Todos,
+    Pendientes,
+    Comprados,
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct FiltroItems {
+    pub texto: String,
+    pub estado: EstadoItem,
+    pub unidad: Option<String>,
+    pub precio_minimo: Option<f32>,
+    pub precio_maximo: Option<f32>,
+    pub solo_imprescindibles: bool,
+    // Sólo los items asignados a este miembro
+    pub asignados_a: Option<usize>,
+}
+
+impl FiltroItems {
+    pub fn acepta(&self, item: &Item) -> bool {
+        let texto = normalizar(&self.texto);
+        if !texto.is_empty()
+            && !normalizar(&item.nombre).contains(&texto)
+            && !normalizar(&item.notas).contains(&texto)
+        {
+            return false;
+        }
+        match self.estado {
+            EstadoItem::Pendientes if item.comprado() => return false,
+            EstadoItem::Comprados if !item.comprado() => return false,
+            _ => {}
+        }
+        if self
+            .unidad
+            .as_ref()
+            .is_some_and(|unidad| *unidad != item.unidad)
+        {
+            return false;
+        }
+        if self
+            .precio_minimo
+            .is_some_and(|minimo| item.precio < minimo)
+            || self
+                .precio_maximo
+                .is_some_and(|maximo| item.precio > maximo)
+        {
+            return false;
+        }
+        if self
+            .asignados_a
+            .is_some_and(|miembro| item.asignado_a != Some(miembro))
+        {
+            return false;
+        }
+        !self.solo_imprescindibles || item.prioridad == Prioridad::Imprescindible
+    }
+}
+
 #[derive(Deserialize)]
 pub struct ItemForm {
     pub id: String,
@@ -43,6 +253,12 @@ pub struct ItemForm {
     pub cantidad_requerida: String,
     pub cantidad_comprada: Option<String>,
     pub precio: String,
+    pub precio_incluye_impuesto: Option<String>,
+    pub notas: Option<String>,
+    pub prioridad: Option<String>,
+    pub categoria: Option<String>,
+    pub asignado_a: Option<String>,
+    pub codigo_barras: Option<String>,
 }
 
 impl ItemForm {
@@ -59,6 +275,122 @@ impl ItemForm {
                 .parse()
                 .unwrap_or_default(),
             precio: self.precio.parse().unwrap_or_default(),
+            precio_incluye_impuesto: self.precio_incluye_impuesto.is_some(),
+            notas: self.notas.unwrap_or_default().trim().to_string(),
+            prioridad: self
+                .prioridad
+                .and_then(|prioridad| prioridad.parse::<usize>().ok())
+                .map(Prioridad::from)
+                .unwrap_or_default(),
+            agregado_por: None,
+            comprado_por: None,
+            categoria: self.categoria.unwrap_or_default().trim().to_string(),
+            asignado_a: self
+                .asignado_a
+                .and_then(|asignado_a| asignado_a.parse::<usize>().ok())
+                .filter(|id| *id != 0),
+            codigo_barras: normalizar_codigo(&self.codigo_barras.unwrap_or_default()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comprado(precio: f32, cantidad: f32, precio_incluye_impuesto: bool) -> Item {
+        Item {
+            precio,
+            cantidad_comprada: cantidad,
+            precio_incluye_impuesto,
+            ..Default::default()
+        }
+    }
+
+    fn cerca(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn subtotal_e_impuesto_con_impuesto_incluido() {
+        let (subtotal, impuesto) = comprado(11.0, 2.0, true).subtotal_e_impuesto(10.0);
+        assert!(cerca(subtotal, 20.0));
+        assert!(cerca(impuesto, 2.0));
+    }
+
+    #[test]
+    fn subtotal_e_impuesto_sin_impuesto_incluido() {
+        let (subtotal, impuesto) = comprado(10.0, 2.0, false).subtotal_e_impuesto(21.0);
+        assert!(cerca(subtotal, 20.0));
+        assert!(cerca(impuesto, 4.2));
+        assert_eq!(
+            comprado(10.0, 2.0, true).subtotal_e_impuesto(0.0),
+            (20.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn calcular_totales_solo_cuenta_lo_comprado() {
+        let mut lista = Lista {
+            tasa_impuesto: 10.0,
+            items: Some(vec![
+                comprado(5.0, 2.0, false),
+                comprado(11.0, 1.0, true),
+                comprado(100.0, 0.0, false),
+            ]),
+            ..Default::default()
+        };
+        lista.calcular_totales();
+        assert!(cerca(lista.subtotal, 20.0));
+        assert!(cerca(lista.impuesto, 2.0));
+        assert!(cerca(lista.total, 22.0));
+
+        lista.items = None;
+        lista.calcular_totales();
+        assert_eq!(
+            (lista.subtotal, lista.impuesto, lista.total),
+            (0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn calcular_totales_usa_la_tasa_de_la_categoria() {
+        let mut lista = Lista {
+            tasa_impuesto: 20.0,
+            tasas_categoria: BTreeMap::from([("Alimentos".to_string(), 5.0)]),
+            items: Some(vec![
+                Item {
+                    categoria: " alimentos".to_string(),
+                    ..comprado(10.0, 1.0, false)
+                },
+                comprado(10.0, 1.0, false),
+            ]),
+            ..Default::default()
+        };
+        lista.calcular_totales();
+        assert!(cerca(lista.subtotal, 20.0));
+        assert!(cerca(lista.impuesto, 2.5));
+        assert!(cerca(lista.total, 22.5));
+    }
+
+    // Como los guardaban las versiones anteriores, por ejemplo en un respaldo o una exportación
+    #[test]
+    fn lee_items_sin_los_campos_nuevos() {
+        let item: Item = serde_json::from_str(
+            r#"{"id":1,"id_lista":1,"nombre":"Leche","unidad":"l","cantidad_requerida":1.0,
+            "cantidad_comprada":0.0,"precio":1.5,"precio_incluye_impuesto":true}"#,
+        )
+        .unwrap();
+        assert_eq!(item.notas, "");
+        assert_eq!(item.prioridad, Prioridad::Normal);
+    }
+
+    #[test]
+    fn validar_tasa_rechaza_fuera_de_rango() {
+        assert_eq!(validar_tasa(0.0).unwrap(), 0.0);
+        assert_eq!(validar_tasa(100.0).unwrap(), 100.0);
+        assert!(validar_tasa(-100.0).is_err());
+        assert!(validar_tasa(101.0).is_err());
+        assert!(validar_tasa(f32::NAN).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::model::DBConnector;
+use crate::model::despensa::Producto;
+use crate::model::fecha::formatear_fecha;
+use crate::model::recetas::{Ingrediente, Receta, combinar, conversion};
+use crate::model::texto::normalizar;
+use serde::{Deserialize, Serialize};
+
+pub const DIAS_POR_SEMANA: i64 = 7;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub enum Momento {
+    Desayuno = 0,
+    #[default]
+    Almuerzo = 1,
+    Cena = 2,
+}
+
+impl Momento {
+    pub const TODOS: [Momento; 3] = [Momento::Desayuno, Momento::Almuerzo, Momento::Cena];
+
+    // Clave del mensaje traducido para mostrar el momento del día
+    pub fn clave(&self) -> &'static str {
+        match self {
+            Momento::Desayuno => "breakfast",
+            Momento::Almuerzo => "lunch",
+            Momento::Cena => "dinner",
+        }
+    }
+}
+
+impl From<usize> for Momento {
+    fn from(valor: usize) -> Momento {
+        match valor {
+            0 => Momento::Desayuno,
+            2 => Momento::Cena,
+            _ => Momento::Almuerzo,
+        }
+    }
+}
+
+// Una receta planificada para un día
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Comida {
+    pub id: usize,
+    // Días desde 1970-01-01
+    pub dia: i64,
+    pub momento: Momento,
+    pub id_receta: usize,
+    pub porciones: u32,
+}
+
+// El lunes de la semana del día. El 1970-01-01 fue jueves.
+pub fn inicio_de_semana(dia: i64) -> i64 {
+    dia - (dia + 3).rem_euclid(DIAS_POR_SEMANA)
+}
+
+// Clave del mensaje traducido con el nombre del día de la semana
+pub fn clave_dia_de_semana(dia: i64) -> &'static str {
+    [
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ][(dia + 3).rem_euclid(DIAS_POR_SEMANA) as usize]
+}
+
+// Nombre para la lista que se crea con las compras de la semana
+pub fn nombre_de_lista(inicio: i64) -> String {
+    format!(
+        "{} – {}",
+        formatear_fecha(inicio),
+        formatear_fecha(inicio + DIAS_POR_SEMANA - 1)
+    )
+}
+
+// Las comidas de la semana que empieza en ese día, ordenadas por día y momento
+pub fn de_la_semana(comidas: &[Comida], inicio: i64) -> Vec<Comida> {
+    let mut de_la_semana: Vec<Comida> = comidas
+        .iter()
+        .filter(|comida| comida.dia >= inicio && comida.dia < inicio + DIAS_POR_SEMANA)
+        .cloned()
+        .collect();
+    de_la_semana.sort_by(|a, b| a.dia.cmp(&b.dia).then(a.momento.cmp(&b.momento)));
+    de_la_semana
+}
+
+// Suma los ingredientes de todas las comidas. El mismo producto en unidades compatibles se
+// junta en la unidad en que apareció primero.
+pub fn ingredientes(comidas: &[Comida], recetas: &[Receta]) -> Vec<Ingrediente> {
+    let mut total: Vec<Ingrediente> = Vec::new();
+    for comida in comidas {
+        let Some(receta) = recetas.iter().find(|receta| receta.id == comida.id_receta) else {
+            continue;
+        };
+        for ingrediente in receta.escalar(comida.porciones) {
+            let nombre = normalizar(&ingrediente.nombre);
+            match total.iter_mut().find_map(|sumado| {
+                (normalizar(&sumado.nombre) == nombre)
+                    .then(|| conversion(&ingrediente.unidad, &sumado.unidad))
+                    .flatten()
+                    .map(|factor| (sumado, factor))
+            }) {
+                Some((sumado, factor)) => sumado.cantidad += ingrediente.cantidad * factor,
+                None => total.push(ingrediente),
+            }
+        }
+    }
+    total
+}
+
+// Lo que falta comprar después de usar lo que hay en la despensa
+pub fn descontar_despensa(
+    ingredientes: &[Ingrediente],
+    productos: &[Producto],
+) -> Vec<Ingrediente> {
+    ingredientes
+        .iter()
+        .map(|ingrediente| {
+            let en_casa: f32 = productos
+                .iter()
+                .filter(|producto| normalizar(&producto.nombre) == normalizar(&ingrediente.nombre))
+                .filter_map(|producto| {
+                    conversion(&producto.unidad, &ingrediente.unidad)
+                        .map(|factor| producto.cantidad.max(0.0) * factor)
+                })
+                .sum();
+            Ingrediente {
+                cantidad: ingrediente.cantidad - en_casa,
+                ..ingrediente.clone()
+            }
+        })
+        .filter(|ingrediente| ingrediente.cantidad >= 0.001)
+        .collect()
+}
+
+// Agrega a la lista lo que hace falta para las comidas de la semana. Con id_lista 0 se crea una
+// lista nueva. Devuelve el id de la lista y cuántos items se agregaron o cambiaron.
+pub fn generar_lista(
+    conector: &dyn DBConnector,
+    inicio: i64,
+    id_lista: usize,
+) -> Result<(usize, usize), anyhow::Error> {
+    let comidas = de_la_semana(&conector.get_meals()?, inicio);
+    let faltantes = descontar_despensa(
+        &ingredientes(&comidas, &conector.get_recipes()?),
+        &conector.get_pantry()?,
+    );
+    if faltantes.is_empty() {
+        return Ok((id_lista, 0));
+    }
+    let lista = if id_lista == 0 {
+        conector.get_list(conector.create_new_list(nombre_de_lista(inicio))?)?
+    } else {
+        conector.get_list(id_lista)?
+    };
+    let items = combinar(&faltantes, &lista);
+    for item in items.iter() {
+        if item.id == 0 {
+            conector.create_new_list_item(lista.id, item.clone())?;
+        } else {
+            conector.update_list_item(item.clone())?;
+        }
+    }
+    Ok((lista.id, items.len()))
+}
@@ -1,4 +1,30 @@
+pub mod api;
+pub mod backup;
+pub mod codigo_barras;
+pub mod compartir;
+pub mod crdt;
+pub mod cuentas;
 pub mod database;
-pub use database::{DBConnector, SQLiteConnector};
+pub mod despensa;
+pub mod duplicados;
+pub use database::{DBConnector, LocalSync, SQLiteConnector, ServerConnection};
+mod imagen;
+pub use imagen::Imagen;
 mod lista;
-pub use lista::{Item, ItemForm, Lista};
+pub use lista::{
+    EstadoItem, FiltroItems, Item, ItemForm, Lista, Miembro, Prioridad, ResultadoBusqueda,
+    nombre_miembro, validar_tasa,
+};
+pub mod export;
+pub mod fecha;
+pub mod http;
+pub mod import;
+pub mod menu;
+pub mod recetas;
+pub mod recibo;
+pub mod reparto;
+pub mod sinonimos;
+pub mod sugerencias;
+pub mod sync;
+pub mod texto;
+pub mod vencimientos;
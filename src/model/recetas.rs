@@ -0,0 +1,179 @@
+use crate::model::export::formatear_cantidad;
+use crate::model::import::{LineaNoReconocida, from_text};
+use crate::model::texto::normalizar;
+use crate::model::{DBConnector, Item, Lista};
+use serde::{Deserialize, Serialize};
+
+const UNIDADES_POR_DOCENA: f32 = 12.0;
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Ingrediente {
+    pub nombre: String,
+    pub unidad: String,
+    pub cantidad: f32,
+}
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Receta {
+    pub id: usize,
+    pub nombre: String,
+    pub porciones: u32,
+    pub ingredientes: Vec<Ingrediente>,
+}
+
+impl Receta {
+    // Los ingredientes para otra cantidad de porciones
+    pub fn escalar(&self, porciones: u32) -> Vec<Ingrediente> {
+        let factor = porciones as f32 / self.porciones.max(1) as f32;
+        self.ingredientes
+            .iter()
+            .map(|ingrediente| Ingrediente {
+                cantidad: ingrediente.cantidad * factor,
+                ..ingrediente.clone()
+            })
+            .collect()
+    }
+
+    // Una línea por ingrediente, en el formato que entiende la importación de texto
+    pub fn ingredientes_como_texto(&self) -> String {
+        self.ingredientes
+            .iter()
+            .map(|ingrediente| {
+                format!(
+                    "{} {} {}\n",
+                    formatear_cantidad(ingrediente.cantidad),
+                    ingrediente.unidad,
+                    ingrediente.nombre
+                )
+            })
+            .collect()
+    }
+}
+
+// Factor para pasar una cantidad de una unidad a otra, si son compatibles
+pub fn conversion(desde: &str, hacia: &str) -> Option<f32> {
+    match (desde, hacia) {
+        _ if desde == hacia => Some(1.0),
+        ("docena", "unidad") => Some(UNIDADES_POR_DOCENA),
+        ("unidad", "docena") => Some(1.0 / UNIDADES_POR_DOCENA),
+        _ => None,
+    }
+}
+
+// "Porciones: 4", "Serves 4", "Para 4 personas"
+fn leer_porciones(linea: &str) -> Option<u32> {
+    let linea = normalizar(linea);
+    let resto = [
+        "porciones",
+        "raciones",
+        "rinde",
+        "para",
+        "servings",
+        "serves",
+        "makes",
+    ]
+    .iter()
+    .find_map(|prefijo| linea.strip_prefix(prefijo))?;
+    resto
+        .trim_start_matches([':', ' '])
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+        .filter(|porciones| *porciones > 0)
+}
+
+// Interpreta una receta escrita como lista de ingredientes. El título, si lo tiene, es el
+// nombre de la receta, y una línea como "Porciones: 4" indica para cuántos es.
+pub fn desde_texto(texto: &str) -> (Receta, Vec<LineaNoReconocida>) {
+    let mut porciones = None;
+    // Las líneas de porciones se vacían en vez de quitarse para no cambiar la numeración
+    let sin_porciones: Vec<&str> = texto
+        .lines()
+        .map(
+            |linea| match leer_porciones(linea.trim_start_matches(['#', '-', '*'])) {
+                Some(leidas) if porciones.is_none() => {
+                    porciones = Some(leidas);
+                    ""
+                }
+                _ => linea,
+            },
+        )
+        .collect();
+    let importacion = from_text(&sin_porciones.join("\n"));
+    let receta = Receta {
+        id: 0,
+        nombre: importacion.nombre_lista.unwrap_or_default(),
+        porciones: porciones.unwrap_or(1),
+        ingredientes: importacion
+            .items
+            .into_iter()
+            .map(|item| Ingrediente {
+                nombre: item.nombre,
+                unidad: item.unidad,
+                cantidad: item.cantidad_requerida,
+            })
+            .collect(),
+    };
+    (receta, importacion.errores)
+}
+
+// Los items que hay que crear o cambiar para tener los ingredientes en la lista. Si la lista ya
+// tiene pendiente el mismo producto en una unidad compatible, se suma la cantidad.
+pub fn combinar(ingredientes: &[Ingrediente], lista: &Lista) -> Vec<Item> {
+    let mut items = lista.items.clone().unwrap_or_default();
+    let mut cambiados: Vec<usize> = Vec::new();
+    let mut nuevos: Vec<Item> = Vec::new();
+    for ingrediente in ingredientes
+        .iter()
+        .filter(|ingrediente| ingrediente.cantidad > 0.0)
+    {
+        let nombre = normalizar(&ingrediente.nombre);
+        let existente = items
+            .iter_mut()
+            .chain(nuevos.iter_mut())
+            .filter(|item| !item.comprado() && normalizar(&item.nombre) == nombre)
+            .find_map(|item| {
+                conversion(&ingrediente.unidad, &item.unidad).map(|factor| (item, factor))
+            });
+        match existente {
+            Some((item, factor)) => {
+                item.cantidad_requerida += ingrediente.cantidad * factor;
+                if item.id != 0 && !cambiados.contains(&item.id) {
+                    cambiados.push(item.id);
+                }
+            }
+            None => nuevos.push(Item {
+                id_lista: lista.id,
+                nombre: ingrediente.nombre.clone(),
+                unidad: ingrediente.unidad.clone(),
+                cantidad_requerida: ingrediente.cantidad,
+                ..Default::default()
+            }),
+        }
+    }
+    items
+        .into_iter()
+        .filter(|item| cambiados.contains(&item.id))
+        .chain(nuevos)
+        .collect()
+}
+
+// Agrega los ingredientes de la receta, para esa cantidad de porciones, a la lista. Devuelve
+// cuántos items se agregaron o cambiaron.
+pub fn agregar_a_lista(
+    conector: &dyn DBConnector,
+    receta: &Receta,
+    porciones: u32,
+    id_lista: usize,
+) -> Result<usize, anyhow::Error> {
+    let items = combinar(&receta.escalar(porciones), &conector.get_list(id_lista)?);
+    for item in items.iter() {
+        if item.id == 0 {
+            conector.create_new_list_item(id_lista, item.clone())?;
+        } else {
+            conector.update_list_item(item.clone())?;
+        }
+    }
+    Ok(items.len())
+}
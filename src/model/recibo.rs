@@ -0,0 +1,568 @@
+use crate::model::import::{LineaNoReconocida, leer_numero, leer_unidad};
+use crate::model::sinonimos::Sinonimos;
+use crate::model::texto::normalizar;
+use crate::model::{DBConnector, Item};
+
+// Por debajo de esta similitud un item no se propone para una línea
+pub const SIMILITUD_MINIMA: f32 = 0.4;
+// Desde esta similitud, y con esta ventaja sobre el siguiente candidato, la línea se empareja
+// sin preguntar
+pub const SIMILITUD_SEGURA: f32 = 0.75;
+pub const VENTAJA_MINIMA: f32 = 0.1;
+
+// Líneas del recibo que no son productos, según su primera palabra
+const PALABRAS_IGNORADAS: [&str; 28] = [
+    "total",
+    "subtotal",
+    "sub-total",
+    "iva",
+    "impuesto",
+    "impuestos",
+    "tax",
+    "taxes",
+    "cambio",
+    "change",
+    "vuelto",
+    "efectivo",
+    "cash",
+    "tarjeta",
+    "card",
+    "visa",
+    "mastercard",
+    "debito",
+    "debit",
+    "credito",
+    "credit",
+    "pago",
+    "payment",
+    "saldo",
+    "balance",
+    "redondeo",
+    "propina",
+    "tip",
+];
+
+// Un producto del recibo con lo que se pagó por él
+#[derive(PartialEq, Clone, Debug)]
+pub struct LineaRecibo {
+    pub numero: usize,
+    pub texto: String,
+    pub nombre: String,
+    pub cantidad: f32,
+    pub total: f32,
+}
+
+impl LineaRecibo {
+    pub fn precio(&self) -> f32 {
+        self.total / self.cantidad
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Recibo {
+    pub lineas: Vec<LineaRecibo>,
+    // Encabezados, totales, formas de pago y lo que no se entendió
+    pub ignoradas: Vec<LineaNoReconocida>,
+}
+
+// Un importe con dos decimales, como "1.50", "1,50", "$1.234,50", "2.50A" o "0.50-"
+fn leer_importe(palabra: &str) -> Option<f32> {
+    let palabra = palabra
+        .trim_start_matches(['$', '€', '£'])
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '*');
+    let (negativo, palabra) = match palabra
+        .strip_prefix('-')
+        .or_else(|| palabra.strip_suffix('-'))
+    {
+        Some(resto) => (true, resto.trim_start_matches(['$', '€', '£'])),
+        None => (false, palabra),
+    };
+    let (entero, decimales) = palabra.split_at(palabra.rfind(['.', ','])?);
+    let decimales = &decimales[1..];
+    // Los separadores de miles separan grupos de tres dígitos, así una fecha no es un importe
+    let mut grupos = entero.split(['.', ',']);
+    let primero = grupos.next().unwrap_or_default();
+    if decimales.len() != 2
+        || !decimales.chars().all(|c| c.is_ascii_digit())
+        || primero.is_empty()
+        || (entero.contains(['.', ',']) && primero.len() > 3)
+        || !grupos.all(|grupo| grupo.len() == 3)
+        || !entero
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == ',')
+    {
+        return None;
+    }
+    let entero: String = entero.chars().filter(|c| c.is_ascii_digit()).collect();
+    let valor: f32 = format!("{entero}.{decimales}").parse().ok()?;
+    Some(if negativo { -valor } else { valor })
+}
+
+// Una cantidad como "2", "1.234", "2x" o "x2"
+fn leer_cantidad(palabra: &str) -> Option<f32> {
+    let palabra = palabra
+        .strip_suffix(['x', 'X'])
+        .or_else(|| palabra.strip_prefix(['x', 'X']))
+        .unwrap_or(palabra);
+    leer_numero(palabra).filter(|cantidad| *cantidad > 0.0)
+}
+
+// Lee una línea como "LECHE ENTERA 1L 1.50", "2 x 1.25 PAN 2.50" o "TOMATE 1.234 kg x 2.50 /kg
+// 3.09". El último importe es el total de la línea. None si la línea no tiene importe o nombre.
+fn leer_linea(linea: &str) -> Option<(String, f32, f32)> {
+    let mut palabras: Vec<&str> = linea.split_whitespace().collect();
+    let posicion_total = palabras
+        .iter()
+        .rposition(|palabra| leer_importe(palabra).is_some())?;
+    let total = leer_importe(palabras[posicion_total])?;
+    palabras.truncate(posicion_total);
+
+    let mut cantidad = 1.0;
+    if let Some(signo) = palabras
+        .iter()
+        .position(|palabra| ["x", "X", "@", "*"].contains(palabra))
+        .filter(|signo| *signo > 0 && *signo + 1 < palabras.len())
+    {
+        // La cantidad puede llevar la unidad antes del signo: "1.234 kg x 2.50"
+        let mut inicio = signo - 1;
+        let mut factor = 1.0;
+        if let Some((_, factor_unidad)) = leer_unidad(palabras[inicio]).filter(|_| inicio > 0) {
+            inicio -= 1;
+            factor = factor_unidad;
+        }
+        if let Some(leida) = leer_cantidad(palabras[inicio]) {
+            cantidad = leida * factor;
+            let mut fin = signo + 2;
+            if palabras
+                .get(fin)
+                .is_some_and(|palabra| palabra.starts_with('/'))
+            {
+                fin += 1;
+            }
+            palabras.drain(inicio..fin);
+        }
+    } else if let Some(posicion) = palabras.iter().position(|palabra| {
+        let sin_x = palabra
+            .trim_start_matches(['x', 'X'])
+            .trim_end_matches(['x', 'X']);
+        sin_x.len() < palabra.len() && leer_cantidad(palabra).is_some()
+    }) {
+        cantidad = leer_cantidad(palabras[posicion]).unwrap_or(1.0);
+        palabras.remove(posicion);
+    } else if let Some(posicion) = palabras.windows(2).position(|par| {
+        // Solo los kg: "CAFE 250 G" es el tamaño del paquete, no lo que se pesó
+        leer_cantidad(par[0]).is_some() && leer_unidad(par[1]) == Some(("kg", 1.0))
+    }) {
+        cantidad = leer_cantidad(palabras[posicion]).unwrap_or(1.0);
+        palabras.drain(posicion..posicion + 2);
+    } else if palabras.len() > 1 {
+        // Un número entero al principio es la cantidad: "2 PAN 2.50"
+        if let Some(unidades) = palabras[0]
+            .parse::<u32>()
+            .ok()
+            .filter(|unidades| (1..100).contains(unidades))
+        {
+            cantidad = unidades as f32;
+            palabras.remove(0);
+        }
+    }
+
+    // Los códigos de producto y los símbolos de moneda sueltos no son parte del nombre
+    palabras.retain(|palabra| {
+        let codigo = palabra.len() >= 5 && palabra.chars().all(|c| c.is_ascii_digit());
+        !codigo && !["$", "€", "£"].contains(palabra)
+    });
+    let nombre = palabras.join(" ");
+    if nombre.is_empty() {
+        return None;
+    }
+    Some((nombre, cantidad, total))
+}
+
+// Separa el texto de un recibo en productos. Los descuentos (importes negativos) se restan del
+// producto anterior.
+pub fn leer_recibo(texto: &str) -> Recibo {
+    let mut recibo = Recibo::default();
+    for (indice, linea) in texto.lines().enumerate() {
+        let linea = linea.trim();
+        if linea.is_empty() {
+            continue;
+        }
+        let ignorada = LineaNoReconocida {
+            numero: indice + 1,
+            texto: linea.to_string(),
+        };
+        let Some((nombre, cantidad, total)) = leer_linea(linea) else {
+            recibo.ignoradas.push(ignorada);
+            continue;
+        };
+        let primera = normalizar(&nombre)
+            .split([' ', ':'])
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        if PALABRAS_IGNORADAS.contains(&primera.as_str()) {
+            recibo.ignoradas.push(ignorada);
+            continue;
+        }
+        if total < 0.0 {
+            match recibo.lineas.last_mut() {
+                Some(anterior) => anterior.total = (anterior.total + total).max(0.0),
+                None => recibo.ignoradas.push(ignorada),
+            }
+            continue;
+        }
+        recibo.lineas.push(LineaRecibo {
+            numero: indice + 1,
+            texto: linea.to_string(),
+            nombre,
+            cantidad,
+            total,
+        });
+    }
+    recibo
+}
+
+// Si la palabra abreviada del recibo corresponde a la del item: "tom" o "tomate" para "tomates",
+// "lche" para "leche"
+fn misma_palabra(del_recibo: &str, del_item: &str) -> bool {
+    if del_recibo == del_item || (del_recibo.len() >= 3 && del_item.starts_with(del_recibo)) {
+        return true;
+    }
+    if del_item.len() >= 3 && del_recibo.starts_with(del_item) {
+        return true;
+    }
+    let mut letras = del_item.chars();
+    del_recibo.len() >= 3
+        && del_recibo.chars().next() == del_item.chars().next()
+        && del_recibo
+            .chars()
+            .all(|letra| letras.any(|otra| otra == letra))
+}
+
+// Coeficiente de Dice de los pares de letras, para nombres con errores de tipeo
+fn pares_en_comun(uno: &str, otro: &str) -> f32 {
+    let pares = |texto: &str| -> Vec<(char, char)> {
+        let letras: Vec<char> = texto.chars().filter(|c| !c.is_whitespace()).collect();
+        letras.windows(2).map(|par| (par[0], par[1])).collect()
+    };
+    let de_uno = pares(uno);
+    let mut de_otro = pares(otro);
+    if de_uno.is_empty() || de_otro.is_empty() {
+        return if uno == otro { 1.0 } else { 0.0 };
+    }
+    let total = de_uno.len() + de_otro.len();
+    let mut comunes = 0;
+    for par in de_uno {
+        if let Some(posicion) = de_otro.iter().position(|otro| *otro == par) {
+            de_otro.swap_remove(posicion);
+            comunes += 1;
+        }
+    }
+    2.0 * comunes as f32 / total as f32
+}
+
+fn similitud_de_nombres(del_recibo: &str, del_item: &str) -> f32 {
+    // Las palabras muy cortas ("de", "1l") no ayudan a distinguir productos
+    let palabras = |texto: &str| -> Vec<String> {
+        texto
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|palabra| palabra.chars().count() >= 3)
+            .filter(|palabra| !palabra.chars().any(|c| c.is_ascii_digit()))
+            .map(String::from)
+            .collect()
+    };
+    let del_recibo_palabras = palabras(del_recibo);
+    let del_item_palabras = palabras(del_item);
+    let cubiertas = |unas: &[String], otras: &[String], recibo_primero: bool| -> f32 {
+        if unas.is_empty() {
+            return 0.0;
+        }
+        let encontradas = unas
+            .iter()
+            .filter(|una| {
+                otras.iter().any(|otra| {
+                    if recibo_primero {
+                        misma_palabra(una, otra)
+                    } else {
+                        misma_palabra(otra, una)
+                    }
+                })
+            })
+            .count();
+        encontradas as f32 / unas.len() as f32
+    };
+    // Importa más que estén las palabras del item que las del recibo, que suele agregar marcas
+    let por_palabras = 0.75 * cubiertas(&del_item_palabras, &del_recibo_palabras, false)
+        + 0.25 * cubiertas(&del_recibo_palabras, &del_item_palabras, true);
+    por_palabras.max(pares_en_comun(del_recibo, del_item))
+}
+
+// Qué tanto se parece el nombre del recibo al del item, de 0 a 1, contando sus sinónimos
+pub fn similitud(del_recibo: &str, del_item: &str, sinonimos: &Sinonimos) -> f32 {
+    if sinonimos.mismo_producto(del_recibo, del_item) {
+        return 1.0;
+    }
+    let del_recibo = normalizar(del_recibo);
+    sinonimos
+        .nombres(del_item)
+        .iter()
+        .map(|nombre| similitud_de_nombres(&del_recibo, nombre))
+        .fold(0.0, f32::max)
+}
+
+// Los items que se parecen a una línea del recibo y el elegido para ella
+#[derive(PartialEq, Clone, Debug)]
+pub struct Coincidencia {
+    pub linea: LineaRecibo,
+    // (id del item, similitud), del más parecido al menos parecido
+    pub candidatos: Vec<(usize, f32)>,
+    pub elegido: Option<usize>,
+}
+
+impl Coincidencia {
+    // Hay candidatos pero ninguno es claramente el correcto, así que hay que revisarla
+    pub fn ambigua(&self) -> bool {
+        self.elegido.is_none() && !self.candidatos.is_empty()
+    }
+}
+
+// Busca el item de la lista de cada línea del recibo. Las líneas más parecidas a algún item se
+// emparejan primero, y cada item se elige una sola vez.
+pub fn emparejar(recibo: &Recibo, items: &[Item], sinonimos: &Sinonimos) -> Vec<Coincidencia> {
+    let mut coincidencias: Vec<Coincidencia> = recibo
+        .lineas
+        .iter()
+        .map(|linea| {
+            let mut candidatos: Vec<(usize, f32)> = items
+                .iter()
+                .filter(|item| item.id != 0)
+                .map(|item| (item.id, similitud(&linea.nombre, &item.nombre, sinonimos)))
+                .filter(|(_, similitud)| *similitud >= SIMILITUD_MINIMA)
+                .collect();
+            candidatos.sort_by(|a, b| b.1.total_cmp(&a.1));
+            Coincidencia {
+                linea: linea.clone(),
+                candidatos,
+                elegido: None,
+            }
+        })
+        .collect();
+    let mut orden: Vec<usize> = (0..coincidencias.len()).collect();
+    let mejor = |coincidencia: &Coincidencia| {
+        coincidencia
+            .candidatos
+            .first()
+            .map(|(_, similitud)| *similitud)
+            .unwrap_or_default()
+    };
+    orden.sort_by(|a, b| mejor(&coincidencias[*b]).total_cmp(&mejor(&coincidencias[*a])));
+    let mut elegidos: Vec<usize> = Vec::new();
+    for indice in orden {
+        let coincidencia = &mut coincidencias[indice];
+        let libres: Vec<&(usize, f32)> = coincidencia
+            .candidatos
+            .iter()
+            .filter(|(id, _)| !elegidos.contains(id))
+            .collect();
+        let claro = libres.first().filter(|(_, similitud)| {
+            *similitud >= SIMILITUD_SEGURA
+                && libres
+                    .get(1)
+                    .is_none_or(|(_, siguiente)| similitud - siguiente >= VENTAJA_MINIMA)
+        });
+        if let Some((id, _)) = claro {
+            coincidencia.elegido = Some(*id);
+            elegidos.push(*id);
+        }
+    }
+    coincidencias
+}
+
+// Marca como comprados los items elegidos, con la cantidad y el precio del recibo, que ya incluye
+// el impuesto. Si varias líneas son del mismo item se suman. Devuelve cuántos items cambiaron.
+pub fn aplicar(
+    conector: &dyn DBConnector,
+    items: &[Item],
+    coincidencias: &[Coincidencia],
+) -> Result<usize, anyhow::Error> {
+    let mut cambiados = 0;
+    for item in items {
+        let (cantidad, total) = coincidencias
+            .iter()
+            .filter(|coincidencia| coincidencia.elegido == Some(item.id))
+            .fold((0.0, 0.0), |(cantidad, total), coincidencia| {
+                (
+                    cantidad + coincidencia.linea.cantidad,
+                    total + coincidencia.linea.total,
+                )
+            });
+        if cantidad <= 0.0 {
+            continue;
+        }
+        conector.update_list_item(Item {
+            cantidad_comprada: cantidad,
+            precio: total / cantidad,
+            precio_incluye_impuesto: true,
+            ..item.clone()
+        })?;
+        cambiados += 1;
+    }
+    Ok(cambiados)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::database::DirectorioPrueba;
+
+    const RECIBO: &str = "SUPERMERCADO EL SOL
+Av. Siempre Viva 742
+Fecha: 12.05.24 10:30
+7501031311309 LECHE ENTERA 1L   1.50
+2 x 1.25 PAN BLANCO 2.50
+TOMATE 1.234 kg x 2.50 /kg 3.09
+HUEVOS DOCENA 3.20A
+  DESCUENTO  -0.20
+LCHE ALMENDRA 2.80
+SUBTOTAL 10.89
+IVA 16% 1.74
+TOTAL $12.63
+EFECTIVO 20.00
+CAMBIO 7.37";
+
+    fn lineas(recibo: &Recibo) -> Vec<(String, f32, f32)> {
+        recibo
+            .lineas
+            .iter()
+            .map(|linea| {
+                (
+                    linea.nombre.clone(),
+                    linea.cantidad,
+                    (linea.total * 100.0).round() / 100.0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn leer_recibo_separa_productos_de_totales_y_pagos() {
+        let recibo = leer_recibo(RECIBO);
+        assert_eq!(
+            lineas(&recibo),
+            [
+                ("LECHE ENTERA 1L".to_string(), 1.0, 1.5),
+                ("PAN BLANCO".to_string(), 2.0, 2.5),
+                ("TOMATE".to_string(), 1.234, 3.09),
+                ("HUEVOS DOCENA".to_string(), 1.0, 3.0),
+                ("LCHE ALMENDRA".to_string(), 1.0, 2.8),
+            ]
+        );
+        let ignoradas: Vec<usize> = recibo.ignoradas.iter().map(|linea| linea.numero).collect();
+        assert_eq!(ignoradas, [1, 2, 3, 10, 11, 12, 13, 14]);
+        assert_eq!(recibo.lineas[1].precio(), 1.25);
+    }
+
+    #[test]
+    fn leer_recibo_entiende_cantidades_e_importes() {
+        let recibo = leer_recibo(
+            "3 YOGURT 4,50\nARROZ 1.234,00\n2x JUGO 3.00\nx3 SAL 1.50\nQUESO $ 5.10\nCAFE 250 G 3.50\nJAMON 500 g x 8.00 /kg 4.00",
+        );
+        assert_eq!(
+            lineas(&recibo),
+            [
+                ("YOGURT".to_string(), 3.0, 4.5),
+                ("ARROZ".to_string(), 1.0, 1234.0),
+                ("JUGO".to_string(), 2.0, 3.0),
+                ("SAL".to_string(), 3.0, 1.5),
+                ("QUESO".to_string(), 1.0, 5.1),
+                ("CAFE 250 G".to_string(), 1.0, 3.5),
+                ("JAMON".to_string(), 0.5, 4.0),
+            ]
+        );
+        // Un descuento sin producto anterior no se puede restar de nada
+        assert_eq!(leer_recibo("DESCUENTO -0.50").ignoradas.len(), 1);
+        assert!(leer_importe("12.05.24").is_none());
+        assert_eq!(leer_importe("0.50-"), Some(-0.5));
+    }
+
+    #[test]
+    fn similitud_con_abreviaturas_y_sinonimos() {
+        let mut sinonimos = Sinonimos::default();
+        assert!(similitud("LECHE ENTERA 1L", "Leche", &sinonimos) >= SIMILITUD_SEGURA);
+        assert!(similitud("LCHE ALMENDRA", "Leche de almendras", &sinonimos) >= SIMILITUD_SEGURA);
+        assert!(similitud("TOMATE", "Tomates", &sinonimos) >= SIMILITUD_SEGURA);
+        assert!(similitud("TOMATE", "Leche", &sinonimos) < SIMILITUD_MINIMA);
+        assert!(
+            similitud("PAN BLANCO", "Pan integral", &sinonimos)
+                < similitud("PAN BLANCO", "Pan", &sinonimos)
+        );
+        sinonimos.agregar("eggs", "huevos");
+        assert_eq!(similitud("EGGS", "Huevos", &sinonimos), 1.0);
+    }
+
+    fn item(id: usize, nombre: &str) -> Item {
+        Item {
+            id,
+            nombre: nombre.to_string(),
+            ..Item::default()
+        }
+    }
+
+    #[test]
+    fn emparejar_elige_cada_item_una_sola_vez() {
+        let items = [
+            item(1, "Leche"),
+            item(2, "Leche de almendras"),
+            item(3, "Tomates"),
+            item(4, "Huevos"),
+        ];
+        let recibo = leer_recibo("LECHE ENTERA 1.50\nLCHE ALMENDRA 2.80\nTOMATE 3.00\nLECHE 1.50");
+        let elegidos: Vec<Option<usize>> = emparejar(&recibo, &items, &Sinonimos::default())
+            .iter()
+            .map(|coincidencia| coincidencia.elegido)
+            .collect();
+        assert_eq!(elegidos, [None, Some(2), Some(3), Some(1)]);
+    }
+
+    #[test]
+    fn aplicar_guarda_el_precio_con_impuesto_incluido() {
+        let directorio = DirectorioPrueba::new();
+        let conector = directorio.conector("shopping_list.db");
+        let id_lista = conector.create_new_list("Súper".to_string()).unwrap();
+        for nombre in ["Pan", "Detergente"] {
+            conector
+                .create_new_list_item(
+                    id_lista,
+                    Item {
+                        precio_incluye_impuesto: false,
+                        ..item(0, nombre)
+                    },
+                )
+                .unwrap();
+        }
+        let items = conector.get_list(id_lista).unwrap().items.unwrap();
+        let coincidencias = emparejar(
+            &leer_recibo("2 x 1.25 PAN 2.50\nPAN 1.00"),
+            &items,
+            &Sinonimos::default(),
+        )
+        .into_iter()
+        .map(|coincidencia| Coincidencia {
+            elegido: coincidencia.candidatos.first().map(|(id, _)| *id),
+            ..coincidencia
+        })
+        .collect::<Vec<_>>();
+        assert_eq!(aplicar(&conector, &items, &coincidencias).unwrap(), 1);
+
+        let items = conector.get_list(id_lista).unwrap().items.unwrap();
+        let pan = items.iter().find(|item| item.nombre == "Pan").unwrap();
+        assert_eq!((pan.cantidad_comprada, pan.precio), (3.0, 3.5 / 3.0));
+        assert!(pan.precio_incluye_impuesto);
+        let detergente = items
+            .iter()
+            .find(|item| item.nombre == "Detergente")
+            .unwrap();
+        assert!(!detergente.comprado() && !detergente.precio_incluye_impuesto);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::model::{Item, Lista, Miembro};
+use std::collections::BTreeMap;
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Reparto {
+    // Los items sin asignar se reparten uno a uno
+    #[default]
+    Alternado,
+    // Cada categoría completa va a una misma persona
+    PorCategoria,
+}
+
+impl Reparto {
+    pub fn clave(&self) -> &'static str {
+        match self {
+            Reparto::Alternado => "split_alternating",
+            Reparto::PorCategoria => "split_by_category",
+        }
+    }
+}
+
+// La parte de la compra que le toca a un miembro
+#[derive(PartialEq, Clone, Debug)]
+pub struct Parte {
+    pub miembro: Miembro,
+    pub items: Vec<Item>,
+    pub subtotal: f32,
+}
+
+// Lo que costaría un item pendiente si se compra lo requerido, con impuesto
+fn estimado(item: &Item, tasa_impuesto: f32) -> f32 {
+    let mut comprado = item.clone();
+    comprado.cantidad_comprada = item.cantidad_requerida;
+    let (subtotal, impuesto) = comprado.subtotal_e_impuesto(tasa_impuesto);
+    subtotal + impuesto
+}
+
+// Divide los items pendientes entre los participantes. Los items ya asignados a uno de ellos se
+// respetan y el resto se reparte para equilibrar la cantidad de items de cada uno.
+pub fn repartir(lista: &Lista, participantes: &[Miembro], reparto: Reparto) -> Vec<Parte> {
+    let items = lista.items.as_deref().unwrap_or_default();
+    if participantes.is_empty() {
+        return Vec::new();
+    }
+    let mut partes: Vec<Parte> = participantes
+        .iter()
+        .map(|miembro| Parte {
+            miembro: miembro.clone(),
+            items: Vec::new(),
+            subtotal: 0.0,
+        })
+        .collect();
+    let mut sin_asignar = Vec::new();
+    for item in items.iter().filter(|item| !item.comprado()) {
+        match partes
+            .iter_mut()
+            .find(|parte| Some(parte.miembro.id) == item.asignado_a)
+        {
+            Some(parte) => parte.items.push(item.clone()),
+            None => sin_asignar.push(item.clone()),
+        }
+    }
+    match reparto {
+        Reparto::Alternado => {
+            for item in sin_asignar {
+                menos_cargada(&mut partes).items.push(item);
+            }
+        }
+        Reparto::PorCategoria => {
+            let mut categorias: BTreeMap<String, Vec<Item>> = BTreeMap::new();
+            for item in sin_asignar {
+                categorias
+                    .entry(item.categoria.trim().to_lowercase())
+                    .or_default()
+                    .push(item);
+            }
+            // Las categorías más grandes primero, para que queden parejas
+            let mut categorias: Vec<Vec<Item>> = categorias.into_values().collect();
+            categorias.sort_by_key(|categoria| std::cmp::Reverse(categoria.len()));
+            for categoria in categorias {
+                menos_cargada(&mut partes).items.extend(categoria);
+            }
+        }
+    }
+    for parte in partes.iter_mut() {
+        parte.subtotal = parte
+            .items
+            .iter()
+            .map(|item| estimado(item, lista.tasa_de(item)))
+            .sum();
+    }
+    partes
+}
+
+fn menos_cargada(partes: &mut [Parte]) -> &mut Parte {
+    partes
+        .iter_mut()
+        .min_by_key(|parte| parte.items.len())
+        .expect("At least one participant")
+}
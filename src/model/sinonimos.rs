@@ -0,0 +1,111 @@
+use crate::model::texto::normalizar;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Cantidad máxima de nombres que se proponen al escribir
+pub const MAXIMO_AUTOCOMPLETADO: usize = 8;
+
+// Otro nombre con el que se conoce un producto
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Sinonimo {
+    pub id: usize,
+    pub alias: String,
+    pub canonico: String,
+}
+
+#[derive(Deserialize)]
+pub struct SinonimoForm {
+    pub id: String,
+    pub alias: String,
+    pub canonico: String,
+}
+
+impl SinonimoForm {
+    pub fn into_sinonimo(self) -> Sinonimo {
+        Sinonimo {
+            id: self.id.parse().unwrap_or_default(),
+            alias: self.alias,
+            canonico: self.canonico,
+        }
+    }
+}
+
+// Nombres distintos del mismo producto ("eggs" y "huevos"). Sin sinónimos, dos nombres son el
+// mismo producto si son iguales sin contar mayúsculas, acentos ni espacios.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Sinonimos {
+    // Nombre normalizado de cada alias -> nombre normalizado del producto
+    canonicos: HashMap<String, String>,
+}
+
+impl Sinonimos {
+    pub fn desde(sinonimos: &[Sinonimo]) -> Sinonimos {
+        let mut tabla = Sinonimos::default();
+        for sinonimo in sinonimos {
+            tabla.agregar(&sinonimo.alias, &sinonimo.canonico);
+        }
+        tabla
+    }
+
+    pub fn agregar(&mut self, alias: &str, canonico: &str) {
+        self.canonicos
+            .insert(normalizar(alias), normalizar(canonico));
+    }
+
+    // Clave para comparar nombres de productos
+    pub fn clave(&self, nombre: &str) -> String {
+        let nombre = normalizar(nombre);
+        self.canonicos.get(&nombre).cloned().unwrap_or(nombre)
+    }
+
+    pub fn mismo_producto(&self, uno: &str, otro: &str) -> bool {
+        self.clave(uno) == self.clave(otro)
+    }
+
+    // Todos los nombres normalizados del producto, empezando por el canónico
+    pub fn nombres(&self, nombre: &str) -> Vec<String> {
+        let clave = self.clave(nombre);
+        let mut alias: Vec<String> = self
+            .canonicos
+            .iter()
+            .filter(|(alias, canonico)| **canonico == clave && **alias != clave)
+            .map(|(alias, _)| alias.clone())
+            .collect();
+        alias.sort();
+        let mut nombres = vec![clave];
+        nombres.append(&mut alias);
+        nombres
+    }
+}
+
+// Nombres conocidos que completan lo escrito. También se proponen los sinónimos de un producto
+// cuyo nombre empieza con lo escrito, después de los que coinciden directamente.
+pub fn autocompletar(texto: &str, conocidos: &[String], sinonimos: &Sinonimos) -> Vec<String> {
+    let escrito = normalizar(texto);
+    if escrito.is_empty() {
+        return Vec::new();
+    }
+    let claves: Vec<String> = conocidos
+        .iter()
+        .filter(|nombre| normalizar(nombre).starts_with(&escrito))
+        .map(|nombre| sinonimos.clave(nombre))
+        .collect();
+    let mut propuestos: Vec<(bool, String)> = conocidos
+        .iter()
+        .filter(|nombre| normalizar(nombre) != escrito)
+        .filter(|nombre| claves.contains(&sinonimos.clave(nombre)))
+        .map(|nombre| {
+            (
+                !normalizar(nombre).starts_with(&escrito),
+                nombre.trim().to_string(),
+            )
+        })
+        .collect();
+    propuestos.sort_by(|a, b| a.0.cmp(&b.0).then(normalizar(&a.1).cmp(&normalizar(&b.1))));
+    propuestos.dedup_by(|a, b| normalizar(&a.1) == normalizar(&b.1));
+    propuestos
+        .into_iter()
+        .map(|(_, nombre)| nombre)
+        .take(MAXIMO_AUTOCOMPLETADO)
+        .collect()
+}
@@ -0,0 +1,304 @@
+use crate::model::sinonimos::Sinonimos;
+use crate::model::texto::normalizar;
+use crate::model::{Item, Lista};
+use serde::{Deserialize, Serialize};
+
+// Se sugiere volver a comprar cuando pasó esta fracción del intervalo habitual
+pub const FRACCION_DEL_INTERVALO: f32 = 0.8;
+
+// Lo que se marcó como comprado (o se desmarcó, con cantidad negativa) en una lista
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Compra {
+    pub nombre: String,
+    pub unidad: String,
+    pub cantidad: f32,
+    // Días desde 1970-01-01
+    pub dia: i64,
+    pub id_lista: usize,
+}
+
+// Cada cuánto se compra un producto y en qué cantidad, según el historial
+#[derive(PartialEq, Clone, Debug)]
+pub struct Estimacion {
+    // Como se llamó la última vez que se compró
+    pub nombre: String,
+    pub unidad: String,
+    // Mediana de los días entre compras
+    pub intervalo: f32,
+    // Mediana de lo que se compra cada vez
+    pub cantidad: f32,
+    pub ultima: i64,
+    // Listas en las que se ha comprado
+    pub listas: Vec<usize>,
+}
+
+impl Estimacion {
+    pub fn dias_desde_ultima(&self, hoy: i64) -> i64 {
+        hoy - self.ultima
+    }
+
+    // Qué tanto del intervalo habitual ya pasó. Desde 1 probablemente se acabó
+    pub fn avance(&self, hoy: i64) -> f32 {
+        self.dias_desde_ultima(hoy) as f32 / self.intervalo
+    }
+
+    pub fn item(&self) -> Item {
+        Item {
+            nombre: self.nombre.clone(),
+            unidad: self.unidad.clone(),
+            cantidad_requerida: self.cantidad,
+            ..Default::default()
+        }
+    }
+}
+
+fn mediana(valores: &mut [f32]) -> f32 {
+    valores.sort_by(|a, b| a.total_cmp(b));
+    let mitad = valores.len() / 2;
+    if valores.len().is_multiple_of(2) {
+        (valores[mitad - 1] + valores[mitad]) / 2.0
+    } else {
+        valores[mitad]
+    }
+}
+
+// Estima el intervalo de cada producto (mismo nombre, o sinónimo, y misma unidad). Las compras
+// del mismo día se suman, y hacen falta al menos dos días con compras.
+pub fn estimar(compras: &[Compra], sinonimos: &Sinonimos) -> Vec<Estimacion> {
+    let mut productos: Vec<(String, String, Vec<&Compra>)> = Vec::new();
+    for compra in compras {
+        let nombre = sinonimos.clave(&compra.nombre);
+        match productos
+            .iter_mut()
+            .find(|(otro, unidad, _)| *otro == nombre && *unidad == compra.unidad)
+        {
+            Some((_, _, del_producto)) => del_producto.push(compra),
+            None => productos.push((nombre, compra.unidad.clone(), vec![compra])),
+        }
+    }
+    let mut estimaciones: Vec<Estimacion> = productos
+        .into_iter()
+        .filter_map(|(_, unidad, mut del_producto)| {
+            del_producto.sort_by_key(|compra| compra.dia);
+            let mut por_dia: Vec<(i64, f32)> = Vec::new();
+            for compra in del_producto.iter() {
+                match por_dia.last_mut() {
+                    Some((dia, cantidad)) if *dia == compra.dia => *cantidad += compra.cantidad,
+                    _ => por_dia.push((compra.dia, compra.cantidad)),
+                }
+            }
+            // Un día en que se compró y se desmarcó lo mismo no cuenta como compra
+            por_dia.retain(|(_, cantidad)| *cantidad >= 0.001);
+            if por_dia.len() < 2 {
+                return None;
+            }
+            let mut intervalos: Vec<f32> = por_dia
+                .windows(2)
+                .map(|par| (par[1].0 - par[0].0) as f32)
+                .collect();
+            let mut cantidades: Vec<f32> = por_dia.iter().map(|(_, cantidad)| *cantidad).collect();
+            let ultima = por_dia.last()?.0;
+            let mut listas: Vec<usize> =
+                del_producto.iter().map(|compra| compra.id_lista).collect();
+            listas.sort();
+            listas.dedup();
+            Some(Estimacion {
+                nombre: del_producto
+                    .iter()
+                    .rev()
+                    .find(|compra| compra.cantidad > 0.0)?
+                    .nombre
+                    .clone(),
+                unidad,
+                intervalo: mediana(&mut intervalos),
+                cantidad: mediana(&mut cantidades),
+                ultima,
+                listas,
+            })
+        })
+        .collect();
+    estimaciones.sort_by(|a, b| {
+        normalizar(&a.nombre)
+            .cmp(&normalizar(&b.nombre))
+            .then(a.unidad.cmp(&b.unidad))
+    });
+    estimaciones
+}
+
+// Lo que probablemente se está acabando de los productos que se han comprado en la lista y que no
+// están pendientes en ella, del más atrasado al menos atrasado
+pub fn sugerir(
+    compras: &[Compra],
+    lista: &Lista,
+    hoy: i64,
+    sinonimos: &Sinonimos,
+) -> Vec<Estimacion> {
+    let pendientes: Vec<(String, String)> = lista
+        .items
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|item| !item.comprado())
+        .map(|item| (sinonimos.clave(&item.nombre), item.unidad))
+        .collect();
+    let mut sugerencias: Vec<Estimacion> = estimar(compras, sinonimos)
+        .into_iter()
+        .filter(|estimacion| estimacion.listas.contains(&lista.id))
+        .filter(|estimacion| estimacion.avance(hoy) >= FRACCION_DEL_INTERVALO)
+        .filter(|estimacion| {
+            !pendientes.contains(&(
+                sinonimos.clave(&estimacion.nombre),
+                estimacion.unidad.clone(),
+            ))
+        })
+        .collect();
+    sugerencias.sort_by(|a, b| b.avance(hoy).total_cmp(&a.avance(hoy)));
+    sugerencias
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::DBConnector;
+    use crate::model::database::DirectorioPrueba;
+    use crate::model::fecha::{dias_desde_civil, hoy};
+
+    const ID_LISTA: usize = 1;
+
+    // Un día fijo, para que las pruebas no dependan de cuándo se ejecutan
+    fn dia(mes: u32, dia: u32) -> i64 {
+        dias_desde_civil(2024, mes, dia)
+    }
+
+    fn compra(nombre: &str, cantidad: f32, dia: i64) -> Compra {
+        Compra {
+            nombre: nombre.to_string(),
+            unidad: "unidad".to_string(),
+            cantidad,
+            dia,
+            id_lista: ID_LISTA,
+        }
+    }
+
+    fn lista(pendientes: &[&str]) -> Lista {
+        Lista {
+            id: ID_LISTA,
+            items: Some(
+                pendientes
+                    .iter()
+                    .map(|nombre| Item {
+                        nombre: nombre.to_string(),
+                        ..Item::default()
+                    })
+                    .collect(),
+            ),
+            ..Lista::default()
+        }
+    }
+
+    fn nombres(sugerencias: &[Estimacion]) -> Vec<&str> {
+        sugerencias
+            .iter()
+            .map(|sugerencia| sugerencia.nombre.as_str())
+            .collect()
+    }
+
+    // Leche cada 7 días, de a 2; pan cada 2 días; café una sola vez
+    fn historial() -> Vec<Compra> {
+        vec![
+            compra("Leche", 2.0, dia(5, 1)),
+            compra("Leche", 2.0, dia(5, 8)),
+            compra("leche", 1.0, dia(5, 15)),
+            compra("Leche", 1.0, dia(5, 15)),
+            compra("Pan", 1.0, dia(5, 11)),
+            compra("Pan", 1.0, dia(5, 13)),
+            compra("Pan", 1.0, dia(5, 15)),
+            compra("Café", 1.0, dia(5, 1)),
+        ]
+    }
+
+    #[test]
+    fn estimar_usa_la_mediana_y_suma_las_compras_del_dia() {
+        let estimaciones = estimar(&historial(), &Sinonimos::default());
+        assert_eq!(nombres(&estimaciones), ["Leche", "Pan"]);
+        let leche = &estimaciones[0];
+        assert_eq!((leche.intervalo, leche.cantidad), (7.0, 2.0));
+        assert_eq!(leche.ultima, dia(5, 15));
+        assert_eq!(leche.listas, [ID_LISTA]);
+        assert_eq!(estimaciones[1].intervalo, 2.0);
+    }
+
+    #[test]
+    fn estimar_ignora_lo_desmarcado_el_mismo_dia() {
+        let compras = [
+            compra("Pan", 1.0, dia(5, 1)),
+            compra("Pan", 1.0, dia(5, 3)),
+            compra("Pan", -1.0, dia(5, 3)),
+        ];
+        assert!(estimar(&compras, &Sinonimos::default()).is_empty());
+    }
+
+    #[test]
+    fn sugerir_cuando_paso_casi_todo_el_intervalo() {
+        let sinonimos = Sinonimos::default();
+        // El 16 de mayo el pan ya va por la mitad de su intervalo y la leche recién empieza
+        assert!(sugerir(&historial(), &lista(&[]), dia(5, 16), &sinonimos).is_empty());
+        // El 17 el pan ya cumplió su intervalo
+        let sugerencias = sugerir(&historial(), &lista(&[]), dia(5, 17), &sinonimos);
+        assert_eq!(nombres(&sugerencias), ["Pan"]);
+        assert_eq!(sugerencias[0].dias_desde_ultima(dia(5, 17)), 2);
+        // El 21 la leche pasó el 80% de su intervalo, pero el pan está más atrasado
+        let sugerencias = sugerir(&historial(), &lista(&[]), dia(5, 21), &sinonimos);
+        assert_eq!(nombres(&sugerencias), ["Pan", "Leche"]);
+        assert_eq!(sugerencias[1].item().cantidad_requerida, 2.0);
+    }
+
+    #[test]
+    fn sugerir_omite_lo_pendiente_y_lo_de_otras_listas() {
+        let mut sinonimos = Sinonimos::default();
+        sinonimos.agregar("bread", "pan");
+        let hoy = dia(5, 21);
+        let sugerencias = sugerir(&historial(), &lista(&["Bread"]), hoy, &sinonimos);
+        assert_eq!(nombres(&sugerencias), ["Leche"]);
+        let otra = Lista {
+            id: ID_LISTA + 1,
+            ..lista(&[])
+        };
+        assert!(sugerir(&historial(), &otra, hoy, &sinonimos).is_empty());
+    }
+
+    #[test]
+    fn el_conector_anota_lo_que_cambia_de_lo_comprado() {
+        let directorio = DirectorioPrueba::new();
+        let conector = directorio.conector("shopping_list.db");
+        let id_lista = conector.create_new_list("Feria".to_string()).unwrap();
+        let item = Item {
+            id_lista,
+            nombre: " Pan".to_string(),
+            cantidad_requerida: 2.0,
+            ..Item::default()
+        };
+        let id = conector
+            .create_new_list_item(id_lista, item.clone())
+            .unwrap();
+        for cantidad_comprada in [2.0, 2.0, 0.5] {
+            conector
+                .update_list_item(Item {
+                    id,
+                    cantidad_comprada,
+                    ..item.clone()
+                })
+                .unwrap();
+        }
+        let cantidades: Vec<f32> = conector
+            .get_purchases()
+            .unwrap()
+            .iter()
+            .map(|compra| compra.cantidad)
+            .collect();
+        assert_eq!(cantidades, [2.0, -1.5]);
+        let compra = &conector.get_purchases().unwrap()[0];
+        assert_eq!((compra.nombre.as_str(), compra.dia), ("Pan", hoy()));
+        assert_eq!(compra.id_lista, id_lista);
+    }
+}
@@ -0,0 +1,715 @@
+use crate::model::crdt::CambiosSync;
+use crate::model::{DBConnector, LocalSync, SQLiteConnector};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, Nonce, UnboundKey};
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+pub const PUERTO_SYNC: u16 = 45455;
+pub const PUERTO_DESCUBRIMIENTO: u16 = 45454;
+const SALUDO_DESCUBRIMIENTO: &str = "SHOPPING_LIST_DISCOVER";
+const RESPUESTA_DESCUBRIMIENTO: &str = "SHOPPING_LIST";
+const TIEMPO_ESPERA: Duration = Duration::from_secs(10);
+// Tamaño máximo de un mensaje, para que nadie pueda llenar la memoria del dispositivo
+const MAXIMO_MENSAJE: u64 = 32 * 1024 * 1024;
+// Sincronizaciones atendidas a la vez. Las demás conexiones se cierran
+const MAXIMO_CONEXIONES: usize = 4;
+// Cada cuánto revisan los hilos del servidor si tienen que terminar
+const ESPERA_SERVIDOR: Duration = Duration::from_millis(200);
+
+// Código que comparten los dispositivos emparejados. Sin él no se puede leer ni escribir nada
+pub const CLAVE_CODIGO_SYNC: &str = "sync_code";
+// Letras y números que no se confunden entre sí al copiarlos (32, así que cada uno son 5 bits)
+const ALFABETO_CODIGO: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const LARGO_CODIGO: usize = 16;
+
+// Mientras el dispositivo es visible los hilos del servidor siguen atendiendo. Al dejar de serlo
+// terminan y liberan los puertos
+static VISIBLE: AtomicBool = AtomicBool::new(false);
+static HILOS_SERVIDOR: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Par {
+    pub dispositivo: String,
+    pub direccion: SocketAddr,
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct ResumenSync {
+    pub dispositivo: String,
+    pub enviados: usize,
+    pub recibidos: usize,
+}
+
+// El saludo va sin cifrar. Los demás mensajes se cifran con una clave que sale del código de
+// emparejamiento y de los retos de ambos lados, así que cambia en cada conexión
+#[derive(Deserialize, Serialize)]
+enum Mensaje {
+    Hola { dispositivo: String, reto: String },
+    Cambios(CambiosSync),
+    Listo,
+}
+
+// Código de emparejamiento de este dispositivo. Se crea la primera vez que se pide
+pub fn codigo_sync(conector: &dyn DBConnector) -> Result<String, anyhow::Error> {
+    if let Some(codigo) = conector
+        .get_setting(CLAVE_CODIGO_SYNC)?
+        .filter(|codigo| !codigo.is_empty())
+    {
+        return Ok(codigo);
+    }
+    let mut azar = [0_u8; LARGO_CODIGO];
+    SystemRandom::new().fill(&mut azar)?;
+    let codigo: String = azar
+        .iter()
+        .map(|byte| ALFABETO_CODIGO[*byte as usize % ALFABETO_CODIGO.len()] as char)
+        .collect();
+    conector.set_setting(CLAVE_CODIGO_SYNC, &codigo)?;
+    Ok(codigo)
+}
+
+// Empareja este dispositivo con otro usando el código de ese otro. Devuelve el código guardado
+pub fn usar_codigo_sync(conector: &dyn DBConnector, texto: &str) -> Result<String, anyhow::Error> {
+    let codigo: String = texto
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if codigo.len() != LARGO_CODIGO || !codigo.bytes().all(|c| ALFABETO_CODIGO.contains(&c)) {
+        anyhow::bail!("Invalid pairing code");
+    }
+    conector.set_setting(CLAVE_CODIGO_SYNC, &codigo)?;
+    Ok(codigo)
+}
+
+// En grupos de cuatro, para leerlo y copiarlo más fácil
+pub fn mostrar_codigo_sync(codigo: &str) -> String {
+    codigo
+        .as_bytes()
+        .chunks(4)
+        .map(|grupo| String::from_utf8_lossy(grupo).to_string())
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+fn clave_sync(codigo: &str) -> hmac::Key {
+    hmac::Key::new(hmac::HMAC_SHA256, codigo.as_bytes())
+}
+
+fn firmar(clave: &hmac::Key, partes: &[&str]) -> hmac::Tag {
+    hmac::sign(clave, partes.join("\n").as_bytes())
+}
+
+fn reto() -> Result<String, anyhow::Error> {
+    let mut azar = [0_u8; 16];
+    SystemRandom::new().fill(&mut azar)?;
+    Ok(URL_SAFE_NO_PAD.encode(azar))
+}
+
+// Cifrado de los mensajes en un sentido de la conexión. El nonce es el número del mensaje
+struct Cifrado {
+    clave: LessSafeKey,
+    mensajes: u64,
+}
+
+impl Cifrado {
+    fn new(clave: &hmac::Key, sentido: &str, retos: [&str; 2]) -> Result<Cifrado, anyhow::Error> {
+        let derivada = firmar(clave, &["sync", sentido, retos[0], retos[1]]);
+        Ok(Cifrado {
+            clave: LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, derivada.as_ref())?),
+            mensajes: 0,
+        })
+    }
+
+    fn nonce(&mut self) -> Nonce {
+        let mut nonce = [0_u8; 12];
+        nonce[4..].copy_from_slice(&self.mensajes.to_be_bytes());
+        self.mensajes += 1;
+        Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+struct Conexion {
+    lector: BufReader<TcpStream>,
+    escritor: TcpStream,
+    // Para enviar y para recibir, después del saludo
+    cifrado: Option<(Cifrado, Cifrado)>,
+}
+
+impl Conexion {
+    fn new(stream: TcpStream) -> Result<Conexion, anyhow::Error> {
+        // En algunos sistemas la conexión hereda el modo del listener
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(TIEMPO_ESPERA))?;
+        stream.set_write_timeout(Some(TIEMPO_ESPERA))?;
+        Ok(Conexion {
+            lector: BufReader::new(stream.try_clone()?),
+            escritor: stream,
+            cifrado: None,
+        })
+    }
+
+    // Desde aquí todo va cifrado. Sólo quien tenga el mismo código puede leerlo o responder
+    fn cifrar(
+        &mut self,
+        codigo: &str,
+        reto_cliente: &str,
+        reto_servidor: &str,
+        es_cliente: bool,
+    ) -> Result<(), anyhow::Error> {
+        let clave = clave_sync(codigo);
+        let retos = [reto_cliente, reto_servidor];
+        let del_cliente = Cifrado::new(&clave, "cliente", retos)?;
+        let del_servidor = Cifrado::new(&clave, "servidor", retos)?;
+        self.cifrado = Some(if es_cliente {
+            (del_cliente, del_servidor)
+        } else {
+            (del_servidor, del_cliente)
+        });
+        Ok(())
+    }
+
+    fn enviar(&mut self, mensaje: &Mensaje) -> Result<(), anyhow::Error> {
+        let mut linea = serde_json::to_string(mensaje)?;
+        if let Some((envio, _)) = &mut self.cifrado {
+            let mut datos = linea.into_bytes();
+            let nonce = envio.nonce();
+            envio
+                .clave
+                .seal_in_place_append_tag(nonce, Aad::empty(), &mut datos)?;
+            linea = URL_SAFE_NO_PAD.encode(datos);
+        }
+        linea.push('\n');
+        self.escritor.write_all(linea.as_bytes())?;
+        Ok(())
+    }
+
+    fn recibir(&mut self) -> Result<Mensaje, anyhow::Error> {
+        let mut linea = String::new();
+        let leidos = (&mut self.lector)
+            .take(MAXIMO_MENSAJE)
+            .read_line(&mut linea)?;
+        if leidos == 0 {
+            anyhow::bail!("Connection closed by the other device");
+        }
+        if !linea.ends_with('\n') {
+            anyhow::bail!("Sync message too large");
+        }
+        let Some((_, recepcion)) = &mut self.cifrado else {
+            return Ok(serde_json::from_str(&linea)?);
+        };
+        let mut datos = URL_SAFE_NO_PAD.decode(linea.trim_end())?;
+        let nonce = recepcion.nonce();
+        let Ok(texto) = recepcion
+            .clave
+            .open_in_place(nonce, Aad::empty(), &mut datos)
+        else {
+            anyhow::bail!("The other device is not paired with this one");
+        };
+        Ok(serde_json::from_slice(texto)?)
+    }
+
+    fn recibir_hola(&mut self) -> Result<(String, String), anyhow::Error> {
+        match self.recibir()? {
+            Mensaje::Hola { dispositivo, reto } => Ok((dispositivo, reto)),
+            _ => anyhow::bail!("Unexpected sync message, expected a greeting"),
+        }
+    }
+
+    fn recibir_cambios(&mut self) -> Result<CambiosSync, anyhow::Error> {
+        match self.recibir()? {
+            Mensaje::Cambios(cambios) => Ok(cambios),
+            _ => anyhow::bail!("Unexpected sync message, expected changes"),
+        }
+    }
+}
+
+// Intercambia cambios con otro dispositivo emparejado. Cada lado envía lo que cambió desde la
+// última sincronización exitosa con ese par, y guarda la marca sólo cuando el otro confirma. Se
+// termina cuando el otro también guardó su marca, así otra sincronización inmediata no repite
+// lo enviado.
+pub fn sincronizar_con(
+    conector: &SQLiteConnector,
+    direccion: SocketAddr,
+) -> Result<ResumenSync, anyhow::Error> {
+    let codigo = codigo_sync(conector)?;
+    let mut conexion = Conexion::new(TcpStream::connect_timeout(&direccion, TIEMPO_ESPERA)?)?;
+    let reto_propio = reto()?;
+    conexion.enviar(&Mensaje::Hola {
+        dispositivo: conector.device_id()?,
+        reto: reto_propio.clone(),
+    })?;
+    let (dispositivo, reto_ajeno) = conexion.recibir_hola()?;
+    conexion.cifrar(&codigo, &reto_propio, &reto_ajeno, true)?;
+
+    let (propios, hasta) =
+        conector.changes_since(conector.peer_watermark(&dispositivo)?, &dispositivo)?;
+    conexion.enviar(&Mensaje::Cambios(propios.clone()))?;
+    let ajenos = conexion.recibir_cambios()?;
+    conector.apply_changes(&ajenos)?;
+    conector.set_peer_watermark(&dispositivo, hasta)?;
+    conexion.enviar(&Mensaje::Listo)?;
+    match conexion.recibir()? {
+        Mensaje::Listo => {}
+        _ => anyhow::bail!("Unexpected sync message, expected confirmation"),
+    }
+
+    Ok(ResumenSync {
+        dispositivo,
+        enviados: propios.cantidad(),
+        recibidos: ajenos.cantidad(),
+    })
+}
+
+// Atiende a un dispositivo que inició la sincronización. Si no tiene el mismo código, no se
+// puede leer su primer mensaje y no se le envía nada
+pub fn atender(
+    conector: &SQLiteConnector,
+    stream: TcpStream,
+) -> Result<ResumenSync, anyhow::Error> {
+    let codigo = codigo_sync(conector)?;
+    let mut conexion = Conexion::new(stream)?;
+    let (dispositivo, reto_ajeno) = conexion.recibir_hola()?;
+    let reto_propio = reto()?;
+    conexion.enviar(&Mensaje::Hola {
+        dispositivo: conector.device_id()?,
+        reto: reto_propio.clone(),
+    })?;
+    conexion.cifrar(&codigo, &reto_ajeno, &reto_propio, false)?;
+
+    let ajenos = conexion.recibir_cambios()?;
+    let (propios, hasta) =
+        conector.changes_since(conector.peer_watermark(&dispositivo)?, &dispositivo)?;
+    conector.apply_changes(&ajenos)?;
+    conexion.enviar(&Mensaje::Cambios(propios.clone()))?;
+    match conexion.recibir()? {
+        Mensaje::Listo => conector.set_peer_watermark(&dispositivo, hasta)?,
+        _ => anyhow::bail!("Unexpected sync message, expected confirmation"),
+    }
+    conexion.enviar(&Mensaje::Listo)?;
+
+    Ok(ResumenSync {
+        dispositivo,
+        enviados: propios.cantidad(),
+        recibidos: ajenos.cantidad(),
+    })
+}
+
+// Cuenta una conexión atendida mientras existe
+struct Atendiendo(Arc<AtomicUsize>);
+
+impl Drop for Atendiendo {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// Acepta conexiones de sincronización en segundo plano mientras `activo` sea verdadero. Cada
+// una se atiende en su propio hilo, con su propia conexión a la base de datos. Devuelve la
+// dirección en la que quedó escuchando y el hilo, que termina (cerrando el puerto) cuando
+// `activo` pasa a falso.
+pub fn escuchar(
+    path: PathBuf,
+    direccion: SocketAddr,
+    activo: &'static AtomicBool,
+) -> Result<(SocketAddr, JoinHandle<()>), anyhow::Error> {
+    let listener = TcpListener::bind(direccion)?;
+    let direccion = listener.local_addr()?;
+    listener.set_nonblocking(true)?;
+    let hilo = std::thread::spawn(move || {
+        let atendiendo = Arc::new(AtomicUsize::new(0));
+        while activo.load(Ordering::Relaxed) {
+            let Ok((stream, _)) = listener.accept() else {
+                std::thread::sleep(ESPERA_SERVIDOR);
+                continue;
+            };
+            if atendiendo.fetch_add(1, Ordering::Relaxed) >= MAXIMO_CONEXIONES {
+                atendiendo.fetch_sub(1, Ordering::Relaxed);
+                continue;
+            }
+            let atendida = Atendiendo(atendiendo.clone());
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let _atendida = atendida;
+                _ = atender(&SQLiteConnector::with_path(&path), stream);
+            });
+        }
+    });
+    Ok((direccion, hilo))
+}
+
+// Responde a las búsquedas de dispositivos emparejados con este en la red local, mientras
+// `activo` sea verdadero. A los demás no les responde
+pub fn anunciar(
+    path: PathBuf,
+    puerto_sync: u16,
+    direccion: SocketAddr,
+    activo: &'static AtomicBool,
+) -> Result<(SocketAddr, JoinHandle<()>), anyhow::Error> {
+    let socket = UdpSocket::bind(direccion)?;
+    let direccion = socket.local_addr()?;
+    socket.set_read_timeout(Some(ESPERA_SERVIDOR))?;
+    let hilo = std::thread::spawn(move || {
+        let conector = SQLiteConnector::with_path(&path);
+        let mut buffer = [0; 512];
+        while activo.load(Ordering::Relaxed) {
+            let Ok((leidos, origen)) = socket.recv_from(&mut buffer) else {
+                continue;
+            };
+            let (Ok(codigo), Ok(dispositivo)) = (codigo_sync(&conector), conector.device_id())
+            else {
+                continue;
+            };
+            let clave = clave_sync(&codigo);
+            let saludo = String::from_utf8_lossy(&buffer[..leidos]).to_string();
+            let mut partes = saludo.split_whitespace();
+            let (Some(SALUDO_DESCUBRIMIENTO), Some(reto), Some(firma)) =
+                (partes.next(), partes.next(), partes.next())
+            else {
+                continue;
+            };
+            let Ok(firma) = URL_SAFE_NO_PAD.decode(firma) else {
+                continue;
+            };
+            if hmac::verify(
+                &clave,
+                [SALUDO_DESCUBRIMIENTO, reto].join("\n").as_bytes(),
+                &firma,
+            )
+            .is_err()
+            {
+                continue;
+            }
+            let puerto = puerto_sync.to_string();
+            let firma = firmar(
+                &clave,
+                &[RESPUESTA_DESCUBRIMIENTO, reto, &dispositivo, &puerto],
+            );
+            let respuesta = format!(
+                "{RESPUESTA_DESCUBRIMIENTO} {dispositivo} {puerto} {}",
+                URL_SAFE_NO_PAD.encode(firma.as_ref())
+            );
+            _ = socket.send_to(respuesta.as_bytes(), origen);
+        }
+    });
+    Ok((direccion, hilo))
+}
+
+pub fn descubrir_pares(
+    destino: SocketAddr,
+    propio: &str,
+    codigo: &str,
+    espera: Duration,
+) -> Result<Vec<Par>, anyhow::Error> {
+    let clave = clave_sync(codigo);
+    let reto = reto()?;
+    let firma = firmar(&clave, &[SALUDO_DESCUBRIMIENTO, &reto]);
+    let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+    socket.set_broadcast(true)?;
+    socket.send_to(
+        format!(
+            "{SALUDO_DESCUBRIMIENTO} {reto} {}",
+            URL_SAFE_NO_PAD.encode(firma.as_ref())
+        )
+        .as_bytes(),
+        destino,
+    )?;
+
+    let mut pares: Vec<Par> = Vec::new();
+    let limite = Instant::now() + espera;
+    let mut buffer = [0; 512];
+    while let Some(restante) = limite.checked_duration_since(Instant::now()) {
+        socket.set_read_timeout(Some(restante.max(Duration::from_millis(1))))?;
+        let Ok((leidos, origen)) = socket.recv_from(&mut buffer) else {
+            break;
+        };
+        let respuesta = String::from_utf8_lossy(&buffer[..leidos]);
+        let mut partes = respuesta.split_whitespace();
+        let (Some(RESPUESTA_DESCUBRIMIENTO), Some(dispositivo), Some(puerto), Some(firma)) =
+            (partes.next(), partes.next(), partes.next(), partes.next())
+        else {
+            continue;
+        };
+        // Sólo se muestran los dispositivos que tienen el mismo código
+        let firmado = [RESPUESTA_DESCUBRIMIENTO, &reto, dispositivo, puerto].join("\n");
+        let Ok(firma) = URL_SAFE_NO_PAD.decode(firma) else {
+            continue;
+        };
+        if hmac::verify(&clave, firmado.as_bytes(), &firma).is_err() {
+            continue;
+        }
+        let Ok(puerto) = puerto.parse() else {
+            continue;
+        };
+        if dispositivo != propio && pares.iter().all(|par| par.dispositivo != dispositivo) {
+            pares.push(Par {
+                dispositivo: dispositivo.to_string(),
+                direccion: SocketAddr::new(origen.ip(), puerto),
+            });
+        }
+    }
+    Ok(pares)
+}
+
+pub fn buscar_en_red(propio: &str, codigo: &str) -> Result<Vec<Par>, anyhow::Error> {
+    descubrir_pares(
+        SocketAddr::from(([255, 255, 255, 255], PUERTO_DESCUBRIMIENTO)),
+        propio,
+        codigo,
+        Duration::from_millis(1500),
+    )
+}
+
+// Hace visible este dispositivo en la red local, si no lo es ya
+pub fn iniciar_servidor(path: PathBuf) -> Result<(), anyhow::Error> {
+    let mut hilos = HILOS_SERVIDOR
+        .lock()
+        .map_err(|_| anyhow::anyhow!("Sync server lock poisoned"))?;
+    if VISIBLE.load(Ordering::Relaxed) && !hilos.is_empty() {
+        return Ok(());
+    }
+    // Los hilos de la vez anterior terminan al ver que el dispositivo dejó de ser visible, y
+    // hay que esperarlos para volver a usar los puertos
+    VISIBLE.store(false, Ordering::Relaxed);
+    for hilo in hilos.drain(..) {
+        _ = hilo.join();
+    }
+    VISIBLE.store(true, Ordering::Relaxed);
+    let iniciados = escuchar(
+        path.clone(),
+        SocketAddr::from(([0, 0, 0, 0], PUERTO_SYNC)),
+        &VISIBLE,
+    )
+    .and_then(|(direccion, escucha)| {
+        hilos.push(escucha);
+        let (_, anuncio) = anunciar(
+            path,
+            direccion.port(),
+            SocketAddr::from(([0, 0, 0, 0], PUERTO_DESCUBRIMIENTO)),
+            &VISIBLE,
+        )?;
+        hilos.push(anuncio);
+        Ok(())
+    });
+    // Si algo falla se cierra lo que alcanzó a abrirse, para poder intentarlo de nuevo
+    if iniciados.is_err() {
+        VISIBLE.store(false, Ordering::Relaxed);
+        for hilo in hilos.drain(..) {
+            _ = hilo.join();
+        }
+    }
+    iniciados
+}
+
+// Deja de atender a otros dispositivos. Los puertos se liberan en cuanto los hilos lo notan
+pub fn detener_servidor() {
+    VISIBLE.store(false, Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Item;
+    use crate::model::database::DirectorioPrueba;
+    use std::path::Path;
+
+    static ACTIVO: AtomicBool = AtomicBool::new(true);
+
+    // Base de datos de un dispositivo dentro del directorio temporal de la prueba
+    fn conector(directorio: &DirectorioPrueba, nombre: &str) -> (SQLiteConnector, PathBuf) {
+        let path = directorio.join(format!("{nombre}.db"));
+        (SQLiteConnector::with_path(&path), path)
+    }
+
+    fn servidor(path: &Path) -> SocketAddr {
+        escuchar(
+            path.to_path_buf(),
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            &ACTIVO,
+        )
+        .unwrap()
+        .0
+    }
+
+    fn emparejar(a: &SQLiteConnector, b: &SQLiteConnector) {
+        usar_codigo_sync(b, &codigo_sync(a).unwrap()).unwrap();
+    }
+
+    fn item(conector: &SQLiteConnector, nombre: &str) -> Item {
+        conector
+            .get_list_of_lists()
+            .unwrap()
+            .into_iter()
+            .flat_map(|lista| {
+                conector
+                    .get_list(lista.id)
+                    .unwrap()
+                    .items
+                    .unwrap_or_default()
+            })
+            .find(|item| item.nombre == nombre)
+            .unwrap()
+    }
+
+    #[test]
+    fn sincroniza_dos_dispositivos_emparejados() {
+        let directorio = DirectorioPrueba::new();
+        let (a, _) = conector(&directorio, "a");
+        let (b, path_b) = conector(&directorio, "b");
+        emparejar(&a, &b);
+        let direccion = servidor(&path_b);
+
+        let id_lista = a.create_new_list("Feria".to_string()).unwrap();
+        a.create_new_list_item(
+            id_lista,
+            Item {
+                nombre: "Leche".to_string(),
+                cantidad_requerida: 1.0,
+                ..Item::default()
+            },
+        )
+        .unwrap();
+        let resumen = sincronizar_con(&a, direccion).unwrap();
+        assert_eq!(resumen.dispositivo, b.device_id().unwrap());
+        assert_eq!(item(&b, "Leche").cantidad_requerida, 1.0);
+
+        // Los dos marcan el mismo item sin conexión: queda una sola compra
+        for conector in [&a, &b] {
+            conector
+                .update_list_item(Item {
+                    cantidad_comprada: 1.0,
+                    ..item(conector, "Leche")
+                })
+                .unwrap();
+        }
+        b.update_list(item(&b, "Leche").id_lista, "Verdulería".to_string(), 0)
+            .unwrap();
+        sincronizar_con(&a, direccion).unwrap();
+        for conector in [&a, &b] {
+            assert_eq!(item(conector, "Leche").cantidad_comprada, 1.0);
+            assert_eq!(
+                conector.get_list_of_lists().unwrap()[0].nombre,
+                "Verdulería"
+            );
+        }
+
+        // Sin cambios nuevos no se vuelve a enviar nada
+        let resumen = sincronizar_con(&a, direccion).unwrap();
+        assert_eq!((resumen.enviados, resumen.recibidos), (0, 0));
+    }
+
+    #[test]
+    fn sincroniza_las_tasas_de_cada_categoria() {
+        let directorio = DirectorioPrueba::new();
+        let (a, _) = conector(&directorio, "a");
+        let (b, path_b) = conector(&directorio, "b");
+        emparejar(&a, &b);
+        let direccion = servidor(&path_b);
+
+        let id_lista = a.create_new_list("Feria".to_string()).unwrap();
+        a.set_category_tax_rate(id_lista, "Alimentos".to_string(), Some(5.0))
+            .unwrap();
+        a.set_category_tax_rate(id_lista, "Limpieza".to_string(), Some(19.0))
+            .unwrap();
+        sincronizar_con(&a, direccion).unwrap();
+        let tasas = |conector: &SQLiteConnector| {
+            let id_lista = conector.get_list_of_lists().unwrap()[0].id;
+            conector.get_list(id_lista).unwrap().tasas_categoria
+        };
+        assert_eq!(tasas(&b), tasas(&a));
+        assert_eq!(tasas(&b).get("Alimentos"), Some(&5.0));
+
+        // Quitar la tasa de una categoría también se sincroniza
+        let id_lista_b = b.get_list_of_lists().unwrap()[0].id;
+        b.set_category_tax_rate(id_lista_b, "Limpieza".to_string(), None)
+            .unwrap();
+        sincronizar_con(&a, direccion).unwrap();
+        assert_eq!(
+            tasas(&a).into_iter().collect::<Vec<_>>(),
+            vec![("Alimentos".to_string(), 5.0)]
+        );
+        assert_eq!(tasas(&b), tasas(&a));
+    }
+
+    #[test]
+    fn rechaza_dispositivos_no_emparejados() {
+        let directorio = DirectorioPrueba::new();
+        let (a, _) = conector(&directorio, "a");
+        let (b, path_b) = conector(&directorio, "b");
+        codigo_sync(&a).unwrap();
+        codigo_sync(&b).unwrap();
+        let direccion = servidor(&path_b);
+
+        a.create_new_list("Feria".to_string()).unwrap();
+        assert!(sincronizar_con(&a, direccion).is_err());
+        assert!(b.get_list_of_lists().unwrap().is_empty());
+        assert_eq!(a.peer_watermark(&b.device_id().unwrap()).unwrap(), 0);
+    }
+
+    #[test]
+    fn descubre_solo_dispositivos_emparejados() {
+        let directorio = DirectorioPrueba::new();
+        let (a, _) = conector(&directorio, "a");
+        let (b, path_b) = conector(&directorio, "b");
+        let (c, path_c) = conector(&directorio, "c");
+        emparejar(&a, &b);
+        codigo_sync(&c).unwrap();
+        let anuncio_b = anunciar(path_b, 1234, SocketAddr::from(([127, 0, 0, 1], 0)), &ACTIVO);
+        let anuncio_c = anunciar(path_c, 1234, SocketAddr::from(([127, 0, 0, 1], 0)), &ACTIVO);
+
+        let codigo = codigo_sync(&a).unwrap();
+        let propio = a.device_id().unwrap();
+        let espera = Duration::from_millis(300);
+        let pares = descubrir_pares(anuncio_b.unwrap().0, &propio, &codigo, espera).unwrap();
+        assert_eq!(
+            pares,
+            vec![Par {
+                dispositivo: b.device_id().unwrap(),
+                direccion: SocketAddr::from(([127, 0, 0, 1], 1234)),
+            }]
+        );
+        assert!(
+            descubrir_pares(anuncio_c.unwrap().0, &propio, &codigo, espera)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn libera_los_puertos_al_detenerse() {
+        static DETENIBLE: AtomicBool = AtomicBool::new(true);
+        let directorio = DirectorioPrueba::new();
+        let (_, path) = conector(&directorio, "a");
+        let local = SocketAddr::from(([127, 0, 0, 1], 0));
+        let (direccion, escucha) = escuchar(path.clone(), local, &DETENIBLE).unwrap();
+        let (anuncio, anuncia) = anunciar(path, direccion.port(), local, &DETENIBLE).unwrap();
+
+        DETENIBLE.store(false, Ordering::Relaxed);
+        escucha.join().unwrap();
+        anuncia.join().unwrap();
+        assert!(TcpListener::bind(direccion).is_ok());
+        assert!(UdpSocket::bind(anuncio).is_ok());
+    }
+
+    #[test]
+    fn valida_el_codigo_de_emparejamiento() {
+        let directorio = DirectorioPrueba::new();
+        let (a, _) = conector(&directorio, "a");
+        let codigo = codigo_sync(&a).unwrap();
+        assert_eq!(codigo.len(), LARGO_CODIGO);
+        assert_eq!(codigo_sync(&a).unwrap(), codigo);
+        let mostrado = mostrar_codigo_sync(&codigo).to_lowercase();
+        assert_eq!(usar_codigo_sync(&a, &mostrado).unwrap(), codigo);
+        assert!(usar_codigo_sync(&a, "ABCD-EFGH").is_err());
+        assert!(usar_codigo_sync(&a, "ABCD-EFGH-IJKL-MNOP").is_err());
+    }
+}
@@ -0,0 +1,21 @@
+// Pasa a minúsculas, quita acentos y colapsa los espacios para comparar nombres
+pub fn normalizar(texto: &str) -> String {
+    texto
+        .split_whitespace()
+        .map(|palabra| palabra.to_lowercase().chars().map(sin_acento).collect())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn sin_acento(letra: char) -> char {
+    match letra {
+        'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'ö' | 'õ' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ñ' => 'n',
+        'ç' => 'c',
+        _ => letra,
+    }
+}
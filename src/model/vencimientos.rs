@@ -0,0 +1,89 @@
+use crate::model::fecha::formatear_fecha;
+use crate::model::texto::normalizar;
+use crate::model::{DBConnector, Item};
+use serde::{Deserialize, Serialize};
+
+// Se avisa de lo que vence dentro de esta cantidad de días
+pub const DIAS_DE_AVISO: i64 = 7;
+
+// Algo comprado que tiene fecha de vencimiento
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Vencimiento {
+    pub id: usize,
+    pub nombre: String,
+    pub unidad: String,
+    pub cantidad: f32,
+    // Días desde 1970-01-01
+    pub vence: i64,
+    // Lista en la que se compró, para volver a agregarlo
+    pub id_lista: Option<usize>,
+    // Si se vuelve a agregar a la lista automáticamente cuando vence
+    #[serde(default)]
+    pub reponer: bool,
+}
+
+impl Vencimiento {
+    pub fn fecha(&self) -> String {
+        formatear_fecha(self.vence)
+    }
+
+    pub fn dias_restantes(&self, hoy: i64) -> i64 {
+        self.vence - hoy
+    }
+
+    pub fn vencido(&self, hoy: i64) -> bool {
+        self.vence < hoy
+    }
+
+    fn item(&self) -> Item {
+        Item {
+            nombre: self.nombre.clone(),
+            unidad: self.unidad.clone(),
+            cantidad_requerida: self.cantidad,
+            ..Default::default()
+        }
+    }
+}
+
+// Lo que vence en los próximos días (o ya venció), del más próximo al más lejano
+pub fn por_vencer(vencimientos: &[Vencimiento], hoy: i64) -> Vec<Vencimiento> {
+    let mut por_vencer: Vec<Vencimiento> = vencimientos
+        .iter()
+        .filter(|vencimiento| vencimiento.dias_restantes(hoy) <= DIAS_DE_AVISO)
+        .cloned()
+        .collect();
+    por_vencer.sort_by(|a, b| a.vence.cmp(&b.vence).then(a.nombre.cmp(&b.nombre)));
+    por_vencer
+}
+
+// Vuelve a agregar el producto a su lista y deja de recordarlo. Si la lista ya lo tiene
+// pendiente no se repite.
+pub fn reponer(conector: &dyn DBConnector, vencimiento: &Vencimiento) -> Result<(), anyhow::Error> {
+    if let Some(lista) = vencimiento
+        .id_lista
+        .and_then(|id_lista| conector.get_list(id_lista).ok())
+    {
+        let pendiente = lista.items.unwrap_or_default().iter().any(|item| {
+            !item.comprado()
+                && item.unidad == vencimiento.unidad
+                && normalizar(&item.nombre) == normalizar(&vencimiento.nombre)
+        });
+        if !pendiente {
+            conector.create_new_list_item(lista.id, vencimiento.item())?;
+        }
+    }
+    conector.delete_expiration(vencimiento.id)
+}
+
+// Repone lo que ya venció y se marcó para reponer. Devuelve cuántos productos se repusieron.
+pub fn reponer_vencidos(conector: &dyn DBConnector, hoy: i64) -> Result<usize, anyhow::Error> {
+    let vencidos: Vec<Vencimiento> = conector
+        .get_expirations()?
+        .into_iter()
+        .filter(|vencimiento| vencimiento.reponer && vencimiento.vencido(hoy))
+        .collect();
+    for vencimiento in vencidos.iter() {
+        reponer(conector, vencimiento)?;
+    }
+    Ok(vencidos.len())
+}
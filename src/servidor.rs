@@ -0,0 +1,660 @@
+use crate::model::api::{
+    Busqueda, CambioAsignados, CambioImpuesto, CambioImpuestoCategoria, CambioLista,
+    CambioPropietario, Creado, ErrorApi, ImagenApi, NombreMiembro, NuevaLista, PREFIJO_API, Valor,
+};
+use crate::model::cuentas::Gasto;
+use crate::model::despensa::Producto;
+use crate::model::menu::Comida;
+use crate::model::recetas::Receta;
+use crate::model::sinonimos::Sinonimo;
+use crate::model::sync::CLAVE_CODIGO_SYNC;
+use crate::model::vencimientos::Vencimiento;
+use crate::model::{DBConnector, Item, LocalSync, SQLiteConnector, validar_tasa};
+use rusqlite_migration::{M, Migrations};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tiny_http::{Header, Method, Request, Response};
+
+// Hilos que atienden peticiones. Cada hogar atiende una a la vez, pero hogares distintos no
+// se esperan entre sí
+const HILOS: usize = 4;
+// Tamaño máximo del cuerpo de una petición. Alcanza para restaurar un respaldo con fotos
+const MAXIMO_CUERPO: u64 = 64 * 1024 * 1024;
+// Configuración propia de la base de datos del hogar, que no se lee ni se cambia por la API
+const CLAVES_PROTEGIDAS: &[&str] = &["id_dispositivo", CLAVE_CODIGO_SYNC];
+
+const MIGRACIONES_SERVIDOR: &[M<'_>] = &[M::up(
+    "CREATE TABLE IF NOT EXISTS hogares (
+        id INTEGER PRIMARY KEY,
+        nombre TEXT NOT NULL,
+        token TEXT NOT NULL UNIQUE
+    );",
+)];
+
+#[derive(PartialEq, Clone, Debug, Deserialize, Serialize)]
+pub struct Hogar {
+    pub id: usize,
+    pub nombre: String,
+    pub token: String,
+}
+
+// Servidor HTTP que expone las operaciones de DBConnector. Cada hogar tiene su propio token y
+// su propio directorio, con su base de datos y sus respaldos.
+pub struct Servidor {
+    directorio: PathBuf,
+    connection: Mutex<rusqlite::Connection>,
+    conectores: Mutex<HashMap<usize, Arc<Mutex<SQLiteConnector>>>>,
+}
+
+impl Servidor {
+    pub fn new(directorio: &Path) -> Result<Servidor, anyhow::Error> {
+        std::fs::create_dir_all(directorio)?;
+        let mut connection = rusqlite::Connection::open(directorio.join("servidor.db"))?;
+        Migrations::from_slice(MIGRACIONES_SERVIDOR).to_latest(&mut connection)?;
+        Ok(Servidor {
+            directorio: directorio.to_path_buf(),
+            connection: Mutex::new(connection),
+            conectores: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn crear_hogar(&self, nombre: &str) -> Result<Hogar, anyhow::Error> {
+        if nombre.trim().is_empty() {
+            anyhow::bail!("The household name can't be empty");
+        }
+        Ok(self.connection.lock().unwrap().query_row(
+            "INSERT INTO hogares (nombre, token) VALUES (?1, lower(hex(randomblob(24))))
+            RETURNING id, nombre, token;",
+            [nombre.trim()],
+            |row| {
+                Ok(Hogar {
+                    id: row.get(0)?,
+                    nombre: row.get(1)?,
+                    token: row.get(2)?,
+                })
+            },
+        )?)
+    }
+
+    pub fn hogares(&self) -> Result<Vec<Hogar>, anyhow::Error> {
+        Ok(self
+            .connection
+            .lock()
+            .unwrap()
+            .prepare("SELECT id, nombre, token FROM hogares ORDER BY id;")?
+            .query_map([], |row| {
+                Ok(Hogar {
+                    id: row.get(0)?,
+                    nombre: row.get(1)?,
+                    token: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<Hogar>, rusqlite::Error>>()?)
+    }
+
+    fn hogar_con_token(&self, token: &str) -> Result<Option<usize>, anyhow::Error> {
+        Ok(self
+            .connection
+            .lock()
+            .unwrap()
+            .prepare_cached("SELECT id FROM hogares WHERE token = ?1;")?
+            .query_map([token], |row| row.get(0))?
+            .next()
+            .transpose()?)
+    }
+
+    fn conector(&self, id_hogar: usize) -> Result<Arc<Mutex<SQLiteConnector>>, anyhow::Error> {
+        let mut conectores = self.conectores.lock().unwrap();
+        if let Some(conector) = conectores.get(&id_hogar) {
+            return Ok(conector.clone());
+        }
+        let directorio = self.directorio.join(format!("hogar-{id_hogar}"));
+        std::fs::create_dir_all(&directorio)?;
+        let path = directorio.join("shopping_list.db");
+        // Antes todos los hogares compartían el directorio de datos
+        let anterior = self.directorio.join(format!("hogar-{id_hogar}.db"));
+        if anterior.exists() && !path.exists() {
+            for sufijo in ["", "-wal", "-shm"] {
+                let archivo = PathBuf::from(format!("{}{sufijo}", anterior.display()));
+                if archivo.exists() {
+                    std::fs::rename(&archivo, format!("{}{sufijo}", path.display()))?;
+                }
+            }
+        }
+        let conector = Arc::new(Mutex::new(SQLiteConnector::with_path(&path)));
+        conectores.insert(id_hogar, conector.clone());
+        Ok(conector)
+    }
+
+    // Atiende las peticiones en segundo plano, con varios hilos. Devuelve la dirección en la que
+    // quedó escuchando.
+    pub fn iniciar(self, direccion: &str) -> Result<SocketAddr, anyhow::Error> {
+        let servidor =
+            tiny_http::Server::http(direccion).map_err(|error| anyhow::anyhow!(error))?;
+        let Some(direccion) = servidor.server_addr().to_ip() else {
+            anyhow::bail!("The server is not listening on an IP address");
+        };
+        let servidor = Arc::new(servidor);
+        let estado = Arc::new(self);
+        for _ in 0..HILOS {
+            let servidor = servidor.clone();
+            let estado = estado.clone();
+            std::thread::spawn(move || {
+                while let Ok(peticion) = servidor.recv() {
+                    estado.atender(peticion);
+                }
+            });
+        }
+        Ok(direccion)
+    }
+
+    fn atender(&self, mut peticion: Request) {
+        let token = peticion
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Authorization"))
+            .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+            .map(str::to_string);
+        let mut cuerpo = Vec::new();
+        let resultado = match peticion
+            .as_reader()
+            .take(MAXIMO_CUERPO + 1)
+            .read_to_end(&mut cuerpo)
+        {
+            Err(error) => Err(error.into()),
+            Ok(leidos) if leidos as u64 > MAXIMO_CUERPO => {
+                Ok((413, error_json("The request body is too large")))
+            }
+            Ok(_) => match token.map(|token| self.hogar_con_token(&token)) {
+                Some(Ok(Some(id_hogar))) => {
+                    let metodo = peticion.method().clone();
+                    let url = peticion.url().to_string();
+                    let ruta: Vec<&str> = url
+                        .strip_prefix(PREFIJO_API)
+                        .unwrap_or_default()
+                        .split('/')
+                        .filter(|segmento| !segmento.is_empty())
+                        .collect();
+                    self.conector(id_hogar).and_then(|conector| {
+                        responder(&conector.lock().unwrap(), &metodo, &ruta, cuerpo)
+                    })
+                }
+                Some(Err(error)) => Err(error),
+                _ => Ok((401, error_json("Invalid or missing token"))),
+            },
+        };
+        let (estado, json) = resultado
+            .unwrap_or_else(|error| (estado_de_error(&error), error_json(&error.to_string())));
+        let respuesta = Response::from_string(json)
+            .with_status_code(estado)
+            .with_header(
+                Header::from_bytes("Content-Type", "application/json").expect("Valid header"),
+            );
+        _ = peticion.respond(respuesta);
+    }
+}
+
+fn error_json(error: &str) -> String {
+    serde_json::to_string(&ErrorApi {
+        error: error.to_string(),
+    })
+    .unwrap_or_default()
+}
+
+// Los errores de formato son del cliente, y las listas, items o respaldos que no existen no se
+// encuentran
+fn estado_de_error(error: &anyhow::Error) -> u16 {
+    if error.is::<serde_json::Error>() {
+        400
+    } else if error.is::<std::num::ParseIntError>()
+        || matches!(
+            error.downcast_ref::<rusqlite::Error>(),
+            Some(rusqlite::Error::QueryReturnedNoRows)
+        )
+        || error
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|error| error.kind() == std::io::ErrorKind::NotFound)
+    {
+        404
+    } else {
+        500
+    }
+}
+
+fn json<T: Serialize>(valor: &T) -> Result<(u16, String), anyhow::Error> {
+    Ok((200, serde_json::to_string(valor)?))
+}
+
+fn vacio() -> Result<(u16, String), anyhow::Error> {
+    Ok((204, String::new()))
+}
+
+fn responder(
+    conector: &SQLiteConnector,
+    metodo: &Method,
+    ruta: &[&str],
+    cuerpo: Vec<u8>,
+) -> Result<(u16, String), anyhow::Error> {
+    match (metodo, ruta) {
+        // Listas
+        (Method::Get, ["listas"]) => json(&conector.get_list_of_lists()?),
+        (Method::Post, ["listas"]) => {
+            let nueva: NuevaLista = serde_json::from_slice(&cuerpo)?;
+            json(&Creado {
+                id: conector.create_new_list(nueva.nombre)?,
+            })
+        }
+        (Method::Get, ["listas", id]) => json(&conector.get_list(id.parse()?)?),
+        (Method::Put, ["listas", id]) => {
+            let cambio: CambioLista = serde_json::from_slice(&cuerpo)?;
+            conector.update_list(id.parse()?, cambio.nombre, cambio.modo_simple)?;
+            vacio()
+        }
+        (Method::Delete, ["listas", id]) => {
+            conector.delete_list(id.parse()?)?;
+            vacio()
+        }
+        (Method::Put, ["listas", id, "impuesto"]) => {
+            let cambio: CambioImpuesto = serde_json::from_slice(&cuerpo)?;
+            if let Err(error) = validar_tasa(cambio.tasa_impuesto) {
+                return Ok((400, error_json(&error.to_string())));
+            }
+            conector.update_list_tax_rate(id.parse()?, cambio.tasa_impuesto)?;
+            vacio()
+        }
+        (Method::Put, ["listas", id, "impuestos"]) => {
+            let cambio: CambioImpuestoCategoria = serde_json::from_slice(&cuerpo)?;
+            if let Some(Err(error)) = cambio.tasa_impuesto.map(validar_tasa) {
+                return Ok((400, error_json(&error.to_string())));
+            }
+            conector.set_category_tax_rate(id.parse()?, cambio.categoria, cambio.tasa_impuesto)?;
+            vacio()
+        }
+        (Method::Put, ["listas", id, "propietario"]) => {
+            let cambio: CambioPropietario = serde_json::from_slice(&cuerpo)?;
+            conector.set_list_owner(id.parse()?, cambio.propietario)?;
+            vacio()
+        }
+        (Method::Put, ["listas", id, "asignados"]) => {
+            let cambio: CambioAsignados = serde_json::from_slice(&cuerpo)?;
+            conector.set_list_assignees(id.parse()?, cambio.asignados)?;
+            vacio()
+        }
+        (Method::Post, ["listas", id, "items"]) => {
+            let item: Item = serde_json::from_slice(&cuerpo)?;
+            json(&Creado {
+                id: conector.create_new_list_item(id.parse()?, item)?,
+            })
+        }
+        (Method::Post, ["listas", id, "limpiar"]) => {
+            conector.clear_list_items(id.parse()?)?;
+            vacio()
+        }
+        // Items
+        (Method::Put, ["items", id]) => {
+            let mut item: Item = serde_json::from_slice(&cuerpo)?;
+            item.id = id.parse()?;
+            conector.update_list_item(item)?;
+            vacio()
+        }
+        (Method::Delete, ["items", id]) => {
+            conector.delete_item(id.parse()?)?;
+            vacio()
+        }
+        (Method::Get, ["items", id, "imagen"]) => {
+            json(&conector.get_item_image(id.parse()?)?.map(ImagenApi::from))
+        }
+        (Method::Get, ["items", id, "miniatura"]) => json(
+            &conector
+                .get_item_thumbnail(id.parse()?)?
+                .map(ImagenApi::from),
+        ),
+        (Method::Put, ["items", id, "imagen"]) => {
+            let imagen: ImagenApi = serde_json::from_slice(&cuerpo)?;
+            conector.set_item_image(id.parse()?, imagen.try_into()?)?;
+            vacio()
+        }
+        (Method::Delete, ["items", id, "imagen"]) => {
+            conector.delete_item_image(id.parse()?)?;
+            vacio()
+        }
+        (Method::Post, ["buscar"]) => {
+            let busqueda: Busqueda = serde_json::from_slice(&cuerpo)?;
+            json(&conector.search_items(busqueda.texto)?)
+        }
+        // Miembros del hogar
+        (Method::Get, ["miembros"]) => json(&conector.get_members()?),
+        (Method::Post, ["miembros"]) => {
+            let nuevo: NombreMiembro = serde_json::from_slice(&cuerpo)?;
+            json(&Creado {
+                id: conector.create_member(nuevo.nombre)?,
+            })
+        }
+        (Method::Put, ["miembros", id]) => {
+            let cambio: NombreMiembro = serde_json::from_slice(&cuerpo)?;
+            conector.rename_member(id.parse()?, cambio.nombre)?;
+            vacio()
+        }
+        (Method::Delete, ["miembros", id]) => {
+            conector.delete_member(id.parse()?)?;
+            vacio()
+        }
+        // Gastos compartidos
+        (Method::Get, ["gastos"]) => json(&conector.get_expenses()?),
+        (Method::Post, ["gastos"]) => {
+            let gasto: Gasto = serde_json::from_slice(&cuerpo)?;
+            json(&Creado {
+                id: conector.record_expense(gasto)?,
+            })
+        }
+        (Method::Delete, ["gastos", id]) => {
+            conector.delete_expense(id.parse()?)?;
+            vacio()
+        }
+        // Despensa
+        (Method::Get, ["despensa"]) => json(&conector.get_pantry()?),
+        (Method::Post, ["despensa"]) => {
+            let producto: Producto = serde_json::from_slice(&cuerpo)?;
+            json(&Creado {
+                id: conector.save_pantry_item(Producto { id: 0, ..producto })?,
+            })
+        }
+        (Method::Put, ["despensa", id]) => {
+            let mut producto: Producto = serde_json::from_slice(&cuerpo)?;
+            producto.id = id.parse()?;
+            conector.save_pantry_item(producto)?;
+            vacio()
+        }
+        (Method::Delete, ["despensa", id]) => {
+            conector.delete_pantry_item(id.parse()?)?;
+            vacio()
+        }
+        // Vencimientos
+        (Method::Get, ["vencimientos"]) => json(&conector.get_expirations()?),
+        (Method::Post, ["vencimientos"]) => {
+            let vencimiento: Vencimiento = serde_json::from_slice(&cuerpo)?;
+            json(&Creado {
+                id: conector.add_expiration(vencimiento)?,
+            })
+        }
+        (Method::Delete, ["vencimientos", id]) => {
+            conector.delete_expiration(id.parse()?)?;
+            vacio()
+        }
+        // Recetas
+        (Method::Get, ["recetas"]) => json(&conector.get_recipes()?),
+        (Method::Post, ["recetas"]) => {
+            let receta: Receta = serde_json::from_slice(&cuerpo)?;
+            json(&Creado {
+                id: conector.save_recipe(Receta { id: 0, ..receta })?,
+            })
+        }
+        (Method::Put, ["recetas", id]) => {
+            let mut receta: Receta = serde_json::from_slice(&cuerpo)?;
+            receta.id = id.parse()?;
+            conector.save_recipe(receta)?;
+            vacio()
+        }
+        (Method::Delete, ["recetas", id]) => {
+            conector.delete_recipe(id.parse()?)?;
+            vacio()
+        }
+        // Menú
+        (Method::Get, ["menu"]) => json(&conector.get_meals()?),
+        (Method::Post, ["menu"]) => {
+            let comida: Comida = serde_json::from_slice(&cuerpo)?;
+            json(&Creado {
+                id: conector.save_meal(Comida { id: 0, ..comida })?,
+            })
+        }
+        (Method::Put, ["menu", id]) => {
+            let mut comida: Comida = serde_json::from_slice(&cuerpo)?;
+            comida.id = id.parse()?;
+            conector.save_meal(comida)?;
+            vacio()
+        }
+        (Method::Delete, ["menu", id]) => {
+            conector.delete_meal(id.parse()?)?;
+            vacio()
+        }
+        // Historial de compras
+        (Method::Get, ["compras"]) => json(&conector.get_purchases()?),
+        // Sinónimos y nombres de productos
+        (Method::Get, ["sinonimos"]) => json(&conector.get_synonyms()?),
+        (Method::Post, ["sinonimos"]) => {
+            let sinonimo: Sinonimo = serde_json::from_slice(&cuerpo)?;
+            json(&Creado {
+                id: conector.save_synonym(Sinonimo { id: 0, ..sinonimo })?,
+            })
+        }
+        (Method::Put, ["sinonimos", id]) => {
+            let mut sinonimo: Sinonimo = serde_json::from_slice(&cuerpo)?;
+            sinonimo.id = id.parse()?;
+            conector.save_synonym(sinonimo)?;
+            vacio()
+        }
+        (Method::Delete, ["sinonimos", id]) => {
+            conector.delete_synonym(id.parse()?)?;
+            vacio()
+        }
+        (Method::Get, ["nombres"]) => json(&conector.get_product_names()?),
+        // Códigos de barras
+        (Method::Get, ["codigos", codigo]) => json(&conector.find_item_by_barcode(codigo)?),
+        // Respaldos
+        (Method::Get, ["respaldos"]) => json(&conector.list_backups()?),
+        (Method::Post, ["respaldos"]) => json(&conector.create_backup()?),
+        (Method::Post, ["respaldos", "restaurar"]) => {
+            conector.restore_backup_data(cuerpo)?;
+            vacio()
+        }
+        (Method::Post, ["respaldos", nombre, "restaurar"]) => {
+            conector.restore_backup(nombre.to_string())?;
+            vacio()
+        }
+        // Configuración
+        (Method::Get, ["configuracion", clave]) if CLAVES_PROTEGIDAS.contains(clave) => {
+            Ok((403, error_json("This setting can't be read")))
+        }
+        (Method::Get, ["configuracion", clave]) => json(&Valor {
+            valor: conector.get_setting(clave)?,
+        }),
+        (Method::Put, ["configuracion", clave]) if CLAVES_PROTEGIDAS.contains(clave) => {
+            Ok((403, error_json("This setting can't be changed")))
+        }
+        (Method::Put, ["configuracion", clave]) => {
+            let valor: Valor = serde_json::from_slice(&cuerpo)?;
+            conector.set_setting(clave, &valor.valor.unwrap_or_default())?;
+            vacio()
+        }
+        (Method::Get, ["dispositivo"]) => json(&Valor {
+            valor: Some(conector.device_id()?),
+        }),
+        _ => Ok((404, error_json("Not found"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Lista;
+    use crate::model::backup::Respaldo;
+    use crate::model::database::DirectorioPrueba;
+
+    struct Cliente {
+        url: String,
+        agente: ureq::Agent,
+        // Datos del servidor, que se borran con el cliente
+        _directorio: DirectorioPrueba,
+    }
+
+    impl Cliente {
+        fn new(direccion: SocketAddr, directorio: DirectorioPrueba) -> Cliente {
+            Cliente {
+                url: format!("http://{direccion}{PREFIJO_API}"),
+                agente: ureq::Agent::config_builder()
+                    .http_status_as_error(false)
+                    .build()
+                    .into(),
+                _directorio: directorio,
+            }
+        }
+
+        fn pedir(&self, token: &str, metodo: &str, ruta: &str, cuerpo: Vec<u8>) -> (u16, Vec<u8>) {
+            let peticion = ureq::http::Request::builder()
+                .method(metodo)
+                .uri(format!("{}{ruta}", self.url))
+                .header("Authorization", format!("Bearer {token}"))
+                .body(cuerpo)
+                .unwrap();
+            let mut respuesta = self.agente.run(peticion).unwrap();
+            (
+                respuesta.status().as_u16(),
+                respuesta.body_mut().read_to_vec().unwrap(),
+            )
+        }
+    }
+
+    fn servidor() -> (Cliente, Hogar, Hogar) {
+        let directorio = DirectorioPrueba::new();
+        let servidor = Servidor::new(&directorio).unwrap();
+        let casa = servidor.crear_hogar("Casa").unwrap();
+        let playa = servidor.crear_hogar("Playa").unwrap();
+        let direccion = servidor.iniciar("127.0.0.1:0").unwrap();
+        (Cliente::new(direccion, directorio), casa, playa)
+    }
+
+    #[test]
+    fn separa_los_datos_de_cada_hogar() {
+        let (cliente, casa, playa) = servidor();
+        assert_eq!(cliente.pedir("otro", "GET", "/listas", Vec::new()).0, 401);
+
+        let nueva = serde_json::to_vec(&NuevaLista {
+            nombre: "Feria".to_string(),
+        })
+        .unwrap();
+        assert_eq!(cliente.pedir(&casa.token, "POST", "/listas", nueva).0, 200);
+        let (_, listas) = cliente.pedir(&playa.token, "GET", "/listas", Vec::new());
+        assert!(
+            serde_json::from_slice::<Vec<Lista>>(&listas)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Los respaldos de un hogar no se ven ni se restauran desde otro
+        let (estado, respaldo) = cliente.pedir(&casa.token, "POST", "/respaldos", Vec::new());
+        assert_eq!(estado, 200);
+        let respaldo: Respaldo = serde_json::from_slice(&respaldo).unwrap();
+        let (_, respaldos) = cliente.pedir(&playa.token, "GET", "/respaldos", Vec::new());
+        assert!(
+            serde_json::from_slice::<Vec<Respaldo>>(&respaldos)
+                .unwrap()
+                .is_empty()
+        );
+        let restaurar = format!("/respaldos/{}/restaurar", respaldo.nombre);
+        assert_eq!(
+            cliente
+                .pedir(&playa.token, "POST", &restaurar, Vec::new())
+                .0,
+            404
+        );
+        assert_eq!(
+            cliente.pedir(&casa.token, "POST", &restaurar, Vec::new()).0,
+            204
+        );
+
+        // Dos respaldos en el mismo segundo no se sobrescriben
+        let (_, otro) = cliente.pedir(&casa.token, "POST", "/respaldos", Vec::new());
+        let otro: Respaldo = serde_json::from_slice(&otro).unwrap();
+        assert_ne!(otro.nombre, respaldo.nombre);
+    }
+
+    #[test]
+    fn protege_la_identidad_del_hogar() {
+        let (cliente, casa, _) = servidor();
+        let (_, antes) = cliente.pedir(&casa.token, "GET", "/dispositivo", Vec::new());
+        let valor = serde_json::to_vec(&Valor {
+            valor: Some("otro".to_string()),
+        })
+        .unwrap();
+        let ruta = "/configuracion/id_dispositivo";
+        assert_eq!(cliente.pedir(&casa.token, "PUT", ruta, valor).0, 403);
+        let (_, despues) = cliente.pedir(&casa.token, "GET", "/dispositivo", Vec::new());
+        assert_eq!(antes, despues);
+
+        // Ni se pueden leer, para que nadie obtenga el código de emparejamiento
+        for clave in CLAVES_PROTEGIDAS {
+            let ruta = format!("/configuracion/{clave}");
+            assert_eq!(cliente.pedir(&casa.token, "GET", &ruta, Vec::new()).0, 403);
+        }
+
+        let grande = vec![b' '; MAXIMO_CUERPO as usize + 1];
+        assert_eq!(cliente.pedir(&casa.token, "POST", "/buscar", grande).0, 413);
+    }
+
+    #[test]
+    fn devuelve_el_id_del_item_y_no_encuentra_los_borrados() {
+        let (cliente, casa, _) = servidor();
+        let nueva = serde_json::to_vec(&NuevaLista {
+            nombre: "Feria".to_string(),
+        })
+        .unwrap();
+        let (_, lista) = cliente.pedir(&casa.token, "POST", "/listas", nueva);
+        let lista: Creado = serde_json::from_slice(&lista).unwrap();
+        let item = Item {
+            nombre: "Pan".to_string(),
+            ..Item::default()
+        };
+        let ruta = format!("/listas/{}/items", lista.id);
+        let cuerpo = serde_json::to_vec(&item).unwrap();
+        let (estado, creado) = cliente.pedir(&casa.token, "POST", &ruta, cuerpo);
+        assert_eq!(estado, 200);
+        let creado: Creado = serde_json::from_slice(&creado).unwrap();
+
+        let ruta = format!("/items/{}", creado.id);
+        let cuerpo = serde_json::to_vec(&item).unwrap();
+        assert_eq!(cliente.pedir(&casa.token, "PUT", &ruta, cuerpo).0, 204);
+        assert_eq!(
+            cliente.pedir(&casa.token, "DELETE", &ruta, Vec::new()).0,
+            204
+        );
+        assert_eq!(
+            cliente.pedir(&casa.token, "DELETE", &ruta, Vec::new()).0,
+            404
+        );
+        let cuerpo = serde_json::to_vec(&item).unwrap();
+        assert_eq!(cliente.pedir(&casa.token, "PUT", &ruta, cuerpo).0, 404);
+    }
+
+    #[test]
+    fn atiende_varias_peticiones_a_la_vez() {
+        let (cliente, casa, playa) = servidor();
+        let cliente = Arc::new(cliente);
+        let hilos: Vec<_> = (0..8)
+            .map(|numero| {
+                let cliente = cliente.clone();
+                let token = [&casa.token, &playa.token][numero % 2].clone();
+                std::thread::spawn(move || {
+                    let nueva = serde_json::to_vec(&NuevaLista {
+                        nombre: format!("Lista {numero}"),
+                    })
+                    .unwrap();
+                    cliente.pedir(&token, "POST", "/listas", nueva).0
+                })
+            })
+            .collect();
+        for hilo in hilos {
+            assert_eq!(hilo.join().unwrap(), 200);
+        }
+        for hogar in [&casa, &playa] {
+            let (_, listas) = cliente.pedir(&hogar.token, "GET", "/listas", Vec::new());
+            assert_eq!(
+                serde_json::from_slice::<Vec<Lista>>(&listas).unwrap().len(),
+                4
+            );
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::DATABASE;
+use crate::Route;
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+
+#[component]
+pub fn Busqueda() -> Element {
+    let mut texto = use_signal(|| "".to_string());
+    let resultados = use_memo(move || {
+        DATABASE
+            .with(|f| f.search_items(texto()))
+            .unwrap_or_default()
+    });
+
+    rsx! {
+        div { id: "busqueda", class: "space-y-4 my-5 px-2",
+            input {
+                r#type: "search",
+                class: "bg-gray-50 border border-gray-300 text-md rounded-lg block w-full p-2.5",
+                placeholder: tid!("search_placeholder"),
+                value: texto,
+                oninput: move |event| texto.set(event.value()),
+            }
+            if !texto().trim().is_empty() && resultados.read().is_empty() {
+                p { class: "text-sm px-1", {tid!("search_no_results")} }
+            }
+            div { class: "columns-1 md:columns-2 lg:columns-3 xl:columns-4 2xl:columns-5",
+                for resultado in resultados() {
+                    Link {
+                        key: "{resultado.item.id}",
+                        class: "flex justify-between p-3 mb-2 rounded-lg break-inside-avoid-column bg-gray-300",
+                        to: Route::ListaView {
+                            id: resultado.item.id_lista,
+                        },
+                        span { class: "font-bold", "{resultado.item.nombre}" }
+                        span { class: "text-sm", "{resultado.nombre_lista}" }
+                    }
+                }
+            }
+        }
+    }
+}
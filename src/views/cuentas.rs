@@ -0,0 +1,97 @@
+use crate::DATABASE;
+use crate::model::cuentas::{gasto_de_liquidacion, liquidar, saldos};
+use crate::model::fecha::ahora;
+use crate::model::nombre_miembro;
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+// Saldo de cada miembro, pagos propuestos para saldar cuentas e historial de gastos
+#[component]
+pub fn Cuentas() -> Element {
+    let miembros = use_signal(|| DATABASE.with(|f| f.get_members()).unwrap_or_default());
+    let mut gastos = use_signal(|| DATABASE.with(|f| f.get_expenses()).unwrap_or_default());
+    let nombre = move |id: usize| {
+        nombre_miembro(&miembros.read(), Some(id)).unwrap_or_else(|| tid!("member_unknown"))
+    };
+
+    rsx! {
+        div { id: "cuentas", class: "space-y-4 my-5 px-2",
+            h3 { class: "text-sm font-medium", {tid!("balances")} }
+            for saldo in saldos(&gastos()) {
+                div {
+                    key: "{saldo.miembro}",
+                    class: "flex justify-between p-3 text-lg rounded-lg bg-gray-300",
+                    span { {nombre(saldo.miembro)} }
+                    span { class: if saldo.monto < 0.0 { "text-red-600" } else { "text-green-700" },
+                        "{saldo.monto:.2}"
+                    }
+                }
+            }
+            h3 { class: "text-sm font-medium", {tid!("settle_up")} }
+            for transferencia in liquidar(&gastos()) {
+                div {
+                    key: "{transferencia.de}-{transferencia.para}",
+                    class: "flex items-center justify-between p-3 rounded-lg bg-gray-300",
+                    span {
+                        {
+                            tid!(
+                                "settle_up_transfer",
+                                from: nombre(transferencia.de),
+                                to: nombre(transferencia.para),
+                                amount: format!("{:.2}", transferencia.monto)
+                            )
+                        }
+                    }
+                    button {
+                        r#type: "button",
+                        class: "text-green-600 rounded-full px-5 text-center",
+                        onclick: move |_| {
+                            _ = DATABASE
+                                .with(|f| f.record_expense(gasto_de_liquidacion(&transferencia, ahora())));
+                            gastos.set(DATABASE.with(|f| f.get_expenses()).unwrap_or_default());
+                        },
+                        MaterialIcon { name: "check", size: 24 }
+                    }
+                }
+            }
+            if liquidar(&gastos()).is_empty() {
+                p { class: "text-sm px-1", {tid!("settle_up_nothing")} }
+            }
+            h3 { class: "text-sm font-medium", {tid!("expenses")} }
+            for gasto in gastos() {
+                div {
+                    key: "{gasto.id}",
+                    class: "flex items-center justify-between p-3 rounded-lg bg-gray-300",
+                    div { class: "flex flex-col",
+                        if gasto.liquidacion {
+                            span {
+                                {
+                                    tid!(
+                                        "settle_up_payment",
+                                        from: nombre(gasto.pagado_por),
+                                        to: nombre(gasto.cuotas.first().map(|cuota| cuota.miembro).unwrap_or_default())
+                                    )
+                                }
+                            }
+                        } else {
+                            span { "{gasto.descripcion}" }
+                            span { class: "text-xs", {tid!("expense_paid_by_name", name: nombre(gasto.pagado_por))} }
+                        }
+                        span { class: "text-xs", "{gasto.fecha_legible()}" }
+                    }
+                    span { "{gasto.total():.2}" }
+                    button {
+                        r#type: "button",
+                        class: "text-red-600 rounded-full px-5 text-center",
+                        onclick: move |_| {
+                            _ = DATABASE.with(|f| f.delete_expense(gasto.id));
+                            gastos.set(DATABASE.with(|f| f.get_expenses()).unwrap_or_default());
+                        },
+                        MaterialIcon { name: "delete", size: 24 }
+                    }
+                }
+            }
+        }
+    }
+}
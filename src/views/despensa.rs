@@ -0,0 +1,116 @@
+use crate::DATABASE;
+use crate::model::despensa::{Producto, ProductoForm, generar_lista};
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[component]
+pub fn Despensa() -> Element {
+    let productos = use_signal(|| DATABASE.with(|f| f.get_pantry()).unwrap_or_default());
+    let listas = use_signal(|| DATABASE.with(|f| f.get_list_of_lists()).unwrap_or_default());
+    let mut id_lista = use_signal(|| listas.read().first().map(|lista| lista.id).unwrap_or_default());
+    let mut mensaje = use_signal(|| "".to_string());
+
+    rsx! {
+        div { id: "despensa", class: "space-y-4 my-5 px-2",
+            if !listas.is_empty() {
+                div { class: "flex gap-x-2 items-center",
+                    select {
+                        class: "bg-gray-50 border border-gray-300 rounded-lg block w-full p-2.5",
+                        onchange: move |event| id_lista.set(event.value().parse().unwrap_or_default()),
+                        for lista in listas() {
+                            option {
+                                key: "{lista.id}",
+                                value: "{lista.id}",
+                                selected: id_lista() == lista.id,
+                                "{lista.nombre}"
+                            }
+                        }
+                    }
+                    button {
+                        class: "basis-1/3 text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                        onclick: move |_| {
+                            match DATABASE.with(|f| generar_lista(f.as_ref(), id_lista())) {
+                                Ok(cantidad) => mensaje.set(tid!("pantry_list_generated", count: cantidad)),
+                                Err(error) => mensaje.set(error.to_string()),
+                            }
+                        },
+                        {tid!("pantry_generate_list")}
+                    }
+                }
+                if !mensaje().is_empty() {
+                    p { class: "text-sm px-1", "{mensaje}" }
+                }
+            }
+            div { class: "grid grid-cols-[1fr_auto_auto_auto_auto] gap-x-2 px-1 text-xs",
+                span { {tid!("pantry_product")} }
+                span { {tid!("pantry_quantity")} }
+                span {}
+                span { {tid!("pantry_minimum")} }
+                span {}
+            }
+            for producto in productos() {
+                ProductoDespensa { key: "{producto.id}", producto, productos }
+            }
+            ProductoDespensa { key: "nuevo-{productos.len()}", producto: Producto::default(), productos }
+        }
+    }
+}
+
+#[component]
+fn ProductoDespensa(producto: Producto, mut productos: Signal<Vec<Producto>>) -> Element {
+    let clase_fondo = if producto.faltante() >= 0.001 {
+        "bg-yellow-200"
+    } else {
+        "bg-gray-300"
+    };
+
+    rsx! {
+        form {
+            class: "grid grid-cols-[1fr_auto_auto_auto_auto] gap-x-2 items-center p-2 rounded-lg {clase_fondo}",
+            onchange: move |event: Event<FormData>| {
+                let producto = event.parsed_values::<ProductoForm>().unwrap().into_producto();
+                _ = DATABASE.with(|f| f.save_pantry_item(producto));
+                productos.set(DATABASE.with(|f| f.get_pantry()).unwrap_or_default());
+            },
+            input { r#type: "hidden", name: "id", value: "{producto.id}" }
+            input {
+                r#type: "text",
+                class: "min-w-0",
+                name: "nombre",
+                placeholder: tid!("pantry_product"),
+                value: producto.nombre,
+            }
+            input {
+                r#type: "number",
+                class: "w-[6ch]",
+                name: "cantidad",
+                value: "{producto.cantidad:.3}",
+            }
+            select { class: "w-[8ch]", name: "unidad",
+                for unidad in ["unidad", "kg", "docena"] {
+                    option { value: unidad, selected: producto.unidad == unidad, {tid!(unidad)} }
+                }
+            }
+            input {
+                r#type: "number",
+                class: "w-[6ch]",
+                name: "minimo",
+                value: "{producto.minimo:.3}",
+            }
+            if producto.id != 0 {
+                button {
+                    r#type: "button",
+                    class: "text-red-600 rounded-full text-center",
+                    onclick: move |_| {
+                        _ = DATABASE.with(|f| f.delete_pantry_item(producto.id));
+                        productos.set(DATABASE.with(|f| f.get_pantry()).unwrap_or_default());
+                    },
+                    MaterialIcon { name: "delete" }
+                }
+            } else {
+                span {}
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::DATABASE;
+use crate::model::database::CLAVE_MIEMBRO_ACTUAL;
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[component]
+pub fn Hogar() -> Element {
+    let mut miembros = use_signal(|| DATABASE.with(|f| f.get_members()).unwrap_or_default());
+    let mut miembro_actual = use_signal(|| {
+        DATABASE
+            .with(|f| f.get_setting(CLAVE_MIEMBRO_ACTUAL))
+            .unwrap_or_default()
+            .and_then(|valor| valor.parse::<usize>().ok())
+            .unwrap_or_default()
+    });
+    let mut nombre_nuevo = use_signal(|| "".to_string());
+    let mut editando_id = use_signal(|| 0);
+    let mut editando_nombre = use_signal(|| "".to_string());
+
+    rsx! {
+        div { id: "hogar", class: "space-y-4 my-5 px-2",
+            div { class: "flex gap-x-2",
+                input {
+                    r#type: "text",
+                    class: "bg-gray-50 border border-gray-300 text-md rounded-lg block w-full p-2.5",
+                    placeholder: tid!("member_name"),
+                    value: nombre_nuevo,
+                    oninput: move |event| nombre_nuevo.set(event.value()),
+                }
+                button {
+                    class: "basis-1/6 text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                    onclick: move |_| {
+                        _ = DATABASE.with(|f| f.create_member(nombre_nuevo()));
+                        nombre_nuevo.set("".to_string());
+                        miembros.set(DATABASE.with(|f| f.get_members()).unwrap_or_default());
+                    },
+                    MaterialIcon { name: "person_add", size: 24 }
+                }
+            }
+            if !miembros.is_empty() {
+                div { class: "flex items-center justify-between gap-x-2 px-1 text-sm",
+                    label { r#for: "miembro_actual", {tid!("member_this_device")} }
+                    select {
+                        id: "miembro_actual",
+                        class: "bg-gray-50 border border-gray-300 rounded-lg p-2",
+                        onchange: move |event| {
+                            let id = event.value().parse::<usize>().unwrap_or_default();
+                            let valor = if id == 0 { String::new() } else { id.to_string() };
+                            _ = DATABASE.with(|f| f.set_setting(CLAVE_MIEMBRO_ACTUAL, &valor));
+                            miembro_actual.set(id);
+                        },
+                        option { value: "0", selected: miembro_actual() == 0, {tid!("member_nobody")} }
+                        for miembro in miembros() {
+                            option {
+                                key: "{miembro.id}",
+                                value: "{miembro.id}",
+                                selected: miembro_actual() == miembro.id,
+                                "{miembro.nombre}"
+                            }
+                        }
+                    }
+                }
+            }
+            for miembro in miembros() {
+                div {
+                    key: "{miembro.id}",
+                    class: "flex flex-row p-3 text-lg rounded-lg items-center justify-between bg-gray-300",
+                    if editando_id() != miembro.id {
+                        span { class: "flex-1", "{miembro.nombre}" }
+                        button {
+                            r#type: "button",
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            onclick: move |_| {
+                                editando_id.set(miembro.id);
+                                editando_nombre.set(miembro.nombre.clone());
+                            },
+                            MaterialIcon { name: "edit", size: 24 }
+                        }
+                        button {
+                            r#type: "button",
+                            class: "text-red-600 rounded-full px-5 text-center",
+                            onclick: move |_| {
+                                _ = DATABASE.with(|f| f.delete_member(miembro.id));
+                                if miembro_actual() == miembro.id {
+                                    miembro_actual.set(0);
+                                }
+                                miembros.set(DATABASE.with(|f| f.get_members()).unwrap_or_default());
+                            },
+                            MaterialIcon { name: "delete", size: 24 }
+                        }
+                    } else {
+                        input {
+                            r#type: "text",
+                            class: "flex-1",
+                            value: editando_nombre,
+                            oninput: move |event| editando_nombre.set(event.value()),
+                        }
+                        button {
+                            r#type: "button",
+                            class: "text-green-600 rounded-full px-5 text-center",
+                            onclick: move |_| {
+                                _ = DATABASE.with(|f| f.rename_member(miembro.id, editando_nombre()));
+                                editando_id.set(0);
+                                miembros.set(DATABASE.with(|f| f.get_members()).unwrap_or_default());
+                            },
+                            MaterialIcon { name: "check", size: 24 }
+                        }
+                        button {
+                            r#type: "button",
+                            class: "text-red-600 rounded-full px-5 text-center",
+                            onclick: move |_| editando_id.set(0),
+                            MaterialIcon { name: "clear", size: 24 }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use crate::DATABASE;
 use crate::Route;
+use crate::components::ExportDialog;
+use crate::model::fecha::hoy;
+use crate::model::vencimientos::{por_vencer, reponer, reponer_vencidos};
 use dioxus::prelude::*;
 use dioxus_i18n::tid;
 use dioxus_material_icons::MaterialIcon;
@@ -10,6 +13,12 @@ pub fn Home() -> Element {
     let mut listas = use_signal(|| DATABASE.with(|f| f.get_list_of_lists()).unwrap_or_default());
     let mut editing_list_id = use_signal(|| 0);
     let mut editing_list_name = use_signal(|| "".to_string());
+    let mut exportando = use_signal(|| false);
+    let mut vencimientos = use_signal(|| {
+        // Lo que ya venció y se marcó para reponer vuelve a su lista al abrir la app
+        _ = DATABASE.with(|f| reponer_vencidos(f.as_ref(), hoy()));
+        DATABASE.with(|f| f.get_expirations()).unwrap_or_default()
+    });
     rsx! {
         div { id: "home", class: "space-y-6",
             div { class: "my-5 columns-1 md:columns-2 lg:columns-3 xl:columns-4 2xl:columns-5",
@@ -41,7 +50,57 @@ pub fn Home() -> Element {
                 }
             }
             if listas.len() > 0 {
-                h3 { class: "my-5 text-sm px-1 row-start-2 font-medium", {tid!("created_lists")} }
+                div { class: "flex justify-between items-center px-1 row-start-2",
+                    h3 { class: "my-5 text-sm font-medium", {tid!("created_lists")} }
+                    div {
+                        Link {
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            to: Route::Hogar {},
+                            MaterialIcon { name: "group", size: 24 }
+                        }
+                        Link {
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            to: Route::Cuentas {},
+                            MaterialIcon { name: "account_balance_wallet", size: 24 }
+                        }
+                        Link {
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            to: Route::Despensa {},
+                            MaterialIcon { name: "kitchen", size: 24 }
+                        }
+                        Link {
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            to: Route::Recetas {},
+                            MaterialIcon { name: "menu_book", size: 24 }
+                        }
+                        Link {
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            to: Route::MenuSemanal {},
+                            MaterialIcon { name: "calendar_month", size: 24 }
+                        }
+                        Link {
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            to: Route::EditorSinonimos {},
+                            MaterialIcon { name: "translate", size: 24 }
+                        }
+                        Link {
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            to: Route::Respaldos {},
+                            MaterialIcon { name: "backup", size: 24 }
+                        }
+                        Link {
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            to: Route::Importar {},
+                            MaterialIcon { name: "playlist_add", size: 24 }
+                        }
+                        button {
+                            r#type: "button",
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            onclick: move |_| exportando.set(true),
+                            MaterialIcon { name: "ios_share", size: 24 }
+                        }
+                    }
+                }
                 div { class: "px-2 space-y-6 columns-1 md:columns-2 lg:columns-3 xl:columns-4 2xl:columns-5 row-start-3",
                     for lista in listas.cloned() {
                         div {
@@ -101,6 +160,72 @@ pub fn Home() -> Element {
                     }
                 }
             }
+            if !por_vencer(&vencimientos(), hoy()).is_empty() {
+                div { class: "px-2 space-y-2",
+                    h3 { class: "text-sm font-medium", {tid!("expiring_soon")} }
+                    for vencimiento in por_vencer(&vencimientos(), hoy()) {
+                        div {
+                            key: "{vencimiento.id}",
+                            class: if vencimiento.vencido(hoy()) { "flex flex-row p-3 rounded-lg items-center justify-between bg-red-200" } else { "flex flex-row p-3 rounded-lg items-center justify-between bg-yellow-200" },
+                            div { class: "flex flex-col flex-1",
+                                span { class: "text-lg",
+                                    {format!("{} {:.3} {}", vencimiento.nombre, vencimiento.cantidad, tid!(& vencimiento.unidad))}
+                                }
+                                span { class: "text-xs",
+                                    if vencimiento.vencido(hoy()) {
+                                        {tid!("expired", date: vencimiento.fecha())}
+                                    } else {
+                                        {
+                                            tid!(
+                                                "expires_in",
+                                                days: vencimiento.dias_restantes(hoy()),
+                                                date: vencimiento.fecha()
+                                            )
+                                        }
+                                    }
+                                }
+                            }
+                            if vencimiento.id_lista.is_some() {
+                                button {
+                                    r#type: "button",
+                                    class: "text-blue-600 rounded-full px-5 text-center",
+                                    title: tid!("expiration_readd_now"),
+                                    onclick: move |_| {
+                                        if let Some(vencimiento) = vencimientos()
+                                            .into_iter()
+                                            .find(|otro| otro.id == vencimiento.id)
+                                        {
+                                            _ = DATABASE.with(|f| reponer(f.as_ref(), &vencimiento));
+                                        }
+                                        vencimientos.set(DATABASE.with(|f| f.get_expirations()).unwrap_or_default());
+                                    },
+                                    MaterialIcon { name: "add_shopping_cart", size: 24 }
+                                }
+                            }
+                            button {
+                                r#type: "button",
+                                class: "text-green-600 rounded-full px-5 text-center",
+                                title: tid!("expiration_done"),
+                                onclick: move |_| {
+                                    _ = DATABASE.with(|f| f.delete_expiration(vencimiento.id));
+                                    vencimientos.set(DATABASE.with(|f| f.get_expirations()).unwrap_or_default());
+                                },
+                                MaterialIcon { name: "check", size: 24 }
+                            }
+                        }
+                    }
+                }
+            }
+            if exportando() {
+                ExportDialog {
+                    listas: listas
+                        .read()
+                        .iter()
+                        .filter_map(|lista| DATABASE.with(|f| f.get_list(lista.id)).ok())
+                        .collect::<Vec<_>>(),
+                    onclose: move |_| exportando.set(false),
+                }
+            }
         }
     }
 }
@@ -0,0 +1,189 @@
+use crate::DATABASE;
+use crate::Route;
+use crate::model::compartir::leer_codigo_qr;
+use crate::model::import::{FormatoImportacion, importar};
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[component]
+pub fn Importar() -> Element {
+    let mut texto = use_signal(|| "".to_string());
+    let mut formato = use_signal(|| FormatoImportacion::Texto);
+    let mut error_qr = use_signal(|| "".to_string());
+    let mut id_lista_destino = use_signal(|| 0);
+    let mut nombre_lista_nueva = use_signal(|| "".to_string());
+    let listas = use_signal(|| DATABASE.with(|f| f.get_list_of_lists()).unwrap_or_default());
+    let importaciones =
+        use_memo(move || importar(&texto(), formato()).map_err(|error| error.to_string()));
+    // Con varias listas cada una se importa en una lista nueva con su nombre
+    let varias_listas = importaciones
+        .read()
+        .as_ref()
+        .is_ok_and(|importaciones| importaciones.len() > 1);
+    let reconocidos: usize = importaciones
+        .read()
+        .as_ref()
+        .map(|importaciones| {
+            importaciones
+                .iter()
+                .map(|importacion| importacion.items.len())
+                .sum()
+        })
+        .unwrap_or_default();
+    let navigator = use_navigator();
+
+    rsx! {
+        div { id: "importar", class: "space-y-4 my-5 px-2",
+            div { class: "flex gap-x-2",
+                select {
+                    class: "bg-gray-50 border border-gray-300 rounded-lg p-2",
+                    onchange: move |event| {
+                        if let Some(nuevo_formato) = FormatoImportacion::TODOS
+                            .into_iter()
+                            .find(|formato| formato.clave() == event.value())
+                        {
+                            formato.set(nuevo_formato);
+                        }
+                    },
+                    for opcion in FormatoImportacion::TODOS {
+                        option {
+                            value: opcion.clave(),
+                            selected: opcion == formato(),
+                            {tid!(opcion.clave())}
+                        }
+                    }
+                }
+                label { class: "flex items-center text-blue-600 cursor-pointer",
+                    MaterialIcon { name: "upload_file", size: 24 }
+                    input {
+                        r#type: "file",
+                        class: "hidden",
+                        accept: ".json,.csv,.txt,.md,text/*,image/*",
+                        onchange: move |event: Event<FormData>| async move {
+                            for archivo in event.files() {
+                                let Ok(contenido) = archivo.read_bytes().await else {
+                                    continue;
+                                };
+                                // Una foto se toma como un código QR de una lista compartida
+                                error_qr.set("".to_string());
+                                if image::guess_format(&contenido).is_ok() {
+                                    match leer_codigo_qr(&contenido) {
+                                        Ok(codigo) => {
+                                            formato.set(FormatoImportacion::Compartida);
+                                            texto.set(codigo);
+                                        }
+                                        Err(error) => error_qr.set(error.to_string()),
+                                    }
+                                } else {
+                                    texto.set(String::from_utf8_lossy(&contenido).to_string());
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+            if !error_qr().is_empty() {
+                p { class: "text-sm px-1 text-red-600", "{error_qr}" }
+            }
+            textarea {
+                class: "h-48 w-full bg-gray-50 border border-gray-300 rounded-lg p-2 text-sm",
+                placeholder: tid!("import_placeholder"),
+                value: texto,
+                oninput: move |event| texto.set(event.value()),
+            }
+            {
+                match &*importaciones.read() {
+                    Ok(importaciones) => rsx! {
+                        p { class: "text-sm px-1",
+                            {tid!("import_recognized", count: reconocidos)}
+                        }
+                        if importaciones.len() > 1 {
+                            p { class: "text-sm px-1",
+                                {tid!("import_several_lists", count: importaciones.len())}
+                            }
+                        }
+                        for error in importaciones.iter().flat_map(|importacion| importacion.errores.iter()) {
+                            p { class: "text-sm px-1 text-red-600",
+                                {tid!("import_line_not_understood", line: error.numero, text: error.texto.clone())}
+                            }
+                        }
+                    },
+                    Err(error) => rsx! {
+                        p { class: "text-sm px-1 text-red-600", "{error}" }
+                    },
+                }
+            }
+            div { class: if varias_listas { "hidden" } else { "flex gap-x-2" },
+                select {
+                    class: "bg-gray-50 border border-gray-300 rounded-lg p-2",
+                    onchange: move |event| id_lista_destino.set(event.value().parse().unwrap_or_default()),
+                    option { value: "0", selected: id_lista_destino() == 0, {tid!("import_new_list")} }
+                    for lista in listas() {
+                        option {
+                            key: "{lista.id}",
+                            value: "{lista.id}",
+                            selected: id_lista_destino() == lista.id,
+                            "{lista.nombre}"
+                        }
+                    }
+                }
+                if id_lista_destino() == 0 {
+                    input {
+                        r#type: "text",
+                        class: "bg-gray-50 border border-gray-300 rounded-lg block w-full p-2",
+                        placeholder: importaciones
+                            .read()
+                            .as_ref()
+                            .ok()
+                            .and_then(|importaciones| importaciones.first())
+                            .and_then(|importacion| importacion.nombre_lista.clone())
+                            .unwrap_or_else(|| tid!("new_list_name")),
+                        value: nombre_lista_nueva,
+                        oninput: move |event| nombre_lista_nueva.set(event.value()),
+                    }
+                }
+            }
+            button {
+                class: "text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                disabled: !importaciones
+                    .read()
+                    .as_ref()
+                    .is_ok_and(|importaciones| {
+                        importaciones.iter().any(|importacion| !importacion.items.is_empty())
+                    }),
+                onclick: move |_| {
+                    let Ok(importaciones) = importaciones() else {
+                        return;
+                    };
+                    let mut id_lista = 0;
+                    for importacion in importaciones.iter().filter(|importacion| !importacion.items.is_empty()) {
+                        id_lista = if varias_listas { 0 } else { id_lista_destino() };
+                        if id_lista == 0 {
+                            let mut nombre = if varias_listas { "".to_string() } else { nombre_lista_nueva() };
+                            if nombre.trim().is_empty() {
+                                nombre = importacion
+                                    .nombre_lista
+                                    .clone()
+                                    .unwrap_or_else(|| tid!("import_default_list_name"));
+                            }
+                            id_lista = DATABASE.with(|f| f.create_new_list(nombre)).unwrap_or_default();
+                        }
+                        if id_lista == 0 {
+                            return;
+                        }
+                        for item in importacion.items.iter().cloned() {
+                            _ = DATABASE.with(|f| f.create_new_list_item(id_lista, item));
+                        }
+                    }
+                    if varias_listas {
+                        navigator.push(Route::Home {});
+                    } else {
+                        navigator.push(Route::ListaView { id: id_lista });
+                    }
+                },
+                {tid!("import")}
+            }
+        }
+    }
+}
@@ -1,25 +1,91 @@
 use crate::DATABASE;
-use crate::components::Toggle;
-use crate::model::{Item, ItemForm, Lista};
+use crate::Route;
+use crate::components::{ExportDialog, ShareDialog, SplitCostDialog, Toggle};
+use crate::model::codigo_barras::{
+    agregar_por_codigo, es_valido, leer_codigo_de_barras, normalizar_codigo,
+};
+use crate::model::database::CLAVE_MIEMBRO_ACTUAL;
+use crate::model::duplicados::{duplicado_de, fusionar, limpiar_duplicados};
+use crate::model::fecha::{hoy, leer_fecha};
+use crate::model::reparto::{Reparto, repartir};
+use crate::model::sinonimos::{Sinonimos, autocompletar};
+use crate::model::sugerencias::sugerir;
+use crate::model::texto::normalizar;
+use crate::model::vencimientos::Vencimiento;
+use crate::model::{
+    EstadoItem, FiltroItems, Imagen, Item, ItemForm, Lista, Miembro, Prioridad, nombre_miembro,
+};
 use dioxus::prelude::*;
 use dioxus_i18n::tid;
 use dioxus_material_icons::MaterialIcon;
+use std::collections::BTreeMap;
 
 const CLASE_COLOR_ITEM_COMPRADO: &str = "bg-green-300";
 const CLASE_COLOR_ITEM_NO_COMPRADO: &str = "bg-gray-300";
+const CLASE_ITEM_IMPRESCINDIBLE: &str = "ring-2 ring-red-500";
 
 #[derive(Clone, Copy)]
 struct ListaViewState {
     lista: Signal<Lista>,
+    miembros: Signal<Vec<Miembro>>,
+    sinonimos: Signal<Sinonimos>,
+    // Nombres de productos conocidos, para autocompletar
+    nombres: Signal<Vec<String>>,
+}
+
+// Vuelve a leer la lista. Si ya no se puede leer (por ejemplo, porque se borró en otro
+// dispositivo), vuelve a la lista de listas
+fn recargar(mut lista: Signal<Lista>) {
+    match DATABASE.with(|f| f.get_list(lista().id)) {
+        Ok(actual) => lista.set(actual),
+        Err(_) => {
+            navigator().replace(Route::Home {});
+        }
+    }
+}
+
+// Guarda el item del formulario. Si el formulario no se puede leer se descarta el cambio, y al
+// recargar la lista se ve lo que quedó guardado
+fn handle_change(event: Event<FormData>) {
+    let Ok(formulario) = event.parsed_values::<ItemForm>() else {
+        return;
+    };
+    let item = formulario.into_item();
+    if item.id == 0 {
+        _ = DATABASE.with(|f| f.create_new_list_item(item.id_lista, item));
+    } else {
+        _ = DATABASE.with(|f| f.update_list_item(item));
+    };
 }
 
 #[component]
 pub fn ListaView(id: usize) -> Element {
+    let leida = use_hook(|| DATABASE.with(|f| f.get_list(id)).ok());
+    let existe = leida.is_some();
     use_context_provider(|| ListaViewState {
-        lista: Signal::new(DATABASE.with(|f| f.get_list(id).unwrap())),
+        lista: Signal::new(leida.unwrap_or_default()),
+        miembros: Signal::new(DATABASE.with(|f| f.get_members()).unwrap_or_default()),
+        sinonimos: Signal::new(Sinonimos::desde(
+            &DATABASE.with(|f| f.get_synonyms()).unwrap_or_default(),
+        )),
+        nombres: Signal::new(DATABASE.with(|f| f.get_product_names()).unwrap_or_default()),
     });
     let mut lista = use_context::<ListaViewState>().lista;
     let mut modo_simple = use_signal(|| lista().modo_simple);
+    let filtro = use_signal(FiltroItems::default);
+    let mut exportando = use_signal(|| false);
+    let mut compartiendo = use_signal(|| false);
+    let mut repartiendo = use_signal(|| false);
+    let mut dividiendo = use_signal(|| false);
+    let mut error_impuesto = use_signal(|| "".to_string());
+    let miembros = use_context::<ListaViewState>().miembros;
+    let sinonimos = use_context::<ListaViewState>().sinonimos;
+    let navigator = use_navigator();
+    use_effect(move || {
+        if !existe {
+            navigator.replace(Route::Home {});
+        }
+    });
 
     rsx! {
         div { class: "sticky top-14 text-white bg-blue-600 flex justify-between p-1 text-xl mb-2 items-center",
@@ -40,8 +106,60 @@ pub fn ListaView(id: usize) -> Element {
                 {format!("{} {:.2}", tid!("grand_total"), lista().total)}
             }
         }
+        if !modo_simple() {
+            div { class: "flex justify-between items-center px-2 mb-2 text-sm",
+                div { class: "flex items-center gap-x-1",
+                    label { r#for: "tasa_impuesto", {tid!("tax_rate")} }
+                    input {
+                        r#type: "number",
+                        id: "tasa_impuesto",
+                        class: "w-[6ch] bg-gray-50 border border-gray-300 rounded-lg px-1",
+                        min: "0",
+                        max: "100",
+                        value: "{lista().tasa_impuesto:.2}",
+                        onchange: move |event| {
+                            // Si no es un número no se guarda, y al recargar vuelve la anterior
+                            let resultado = match event.value().trim().parse::<f32>() {
+                                Ok(tasa_impuesto) => {
+                                    DATABASE
+                                        .with(|f| f.update_list_tax_rate(lista().id, tasa_impuesto))
+                                }
+                                Err(_) => Ok(()),
+                            };
+                            error_impuesto
+                                .set(
+                                    resultado
+                                        .err()
+                                        .map(|error| error.to_string())
+                                        .unwrap_or_default(),
+                                );
+                            recargar(lista);
+                        },
+                    }
+                    "%"
+                }
+                span { {format!("{} {:.2}", tid!("subtotal"), lista().subtotal)} }
+                span { {format!("{} {:.2}", tid!("tax"), lista().impuesto)} }
+            }
+            if !error_impuesto().is_empty() {
+                p { class: "text-sm px-2 text-red-600", "{error_impuesto}" }
+            }
+            TasasCategoria {}
+        }
+        MiembrosLista {}
+        FilterBar { filtro }
+        Sugerencias {}
+        CodigoDeBarras {}
+        if repartiendo() {
+            RepartoCompra {}
+        }
         div { class: "px-2 mb-2 columns-1 md:columns-2 lg:columns-3 xl:columns-4 2xl:columns-5",
-            for item in lista().items.unwrap() {
+            for item in lista()
+                .items
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|item| item.id == 0 || filtro.read().acepta(item))
+            {
                 if modo_simple() {
                     ItemCardSimple { key: "{item.id}", item }
                 } else {
@@ -52,7 +170,7 @@ pub fn ListaView(id: usize) -> Element {
         button {
             class: "text-white bg-blue-700 font-medium rounded-lg text-sm m-2 px-5 py-2.5 text-center",
             onclick: move |_| {
-                let mut items = lista().items.unwrap();
+                let mut items = lista().items.unwrap_or_default();
                 items.push(Item::default());
                 lista.write().items = Some(items);
             },
@@ -62,45 +180,572 @@ pub fn ListaView(id: usize) -> Element {
             class: "text-white bg-blue-700 font-medium rounded-lg text-sm m-2 px-5 py-2.5 text-center",
             onclick: move |_| {
                 _ = DATABASE.with(|f| f.clear_list_items(lista().id));
-                lista.set(DATABASE.with(|f| f.get_list(lista().id).unwrap()));
+                recargar(lista);
             },
             MaterialIcon { name: "remove_shopping_cart", size: 24 }
         }
+        button {
+            class: "text-white bg-blue-700 font-medium rounded-lg text-sm m-2 px-5 py-2.5 text-center",
+            title: tid!("clean_up_duplicates"),
+            onclick: move |_| {
+                _ = DATABASE.with(|f| limpiar_duplicados(f.as_ref(), lista().id, &sinonimos.read()));
+                recargar(lista);
+            },
+            MaterialIcon { name: "cleaning_services", size: 24 }
+        }
+        button {
+            class: "text-white bg-blue-700 font-medium rounded-lg text-sm m-2 px-5 py-2.5 text-center",
+            title: tid!("receipt_import"),
+            onclick: move |_| {
+                navigator.push(Route::ImportarRecibo { id: lista().id });
+            },
+            MaterialIcon { name: "receipt_long", size: 24 }
+        }
+        button {
+            class: "text-white bg-blue-700 font-medium rounded-lg text-sm m-2 px-5 py-2.5 text-center",
+            onclick: move |_| exportando.set(true),
+            MaterialIcon { name: "ios_share", size: 24 }
+        }
+        button {
+            class: "text-white bg-blue-700 font-medium rounded-lg text-sm m-2 px-5 py-2.5 text-center",
+            onclick: move |_| compartiendo.set(true),
+            MaterialIcon { name: "qr_code", size: 24 }
+        }
+        if !miembros.read().is_empty() {
+            button {
+                class: "text-white bg-blue-700 font-medium rounded-lg text-sm m-2 px-5 py-2.5 text-center",
+                onclick: move |_| repartiendo.set(!repartiendo()),
+                MaterialIcon { name: "call_split", size: 24 }
+            }
+            button {
+                class: "text-white bg-blue-700 font-medium rounded-lg text-sm m-2 px-5 py-2.5 text-center",
+                onclick: move |_| dividiendo.set(true),
+                MaterialIcon { name: "payments", size: 24 }
+            }
+        }
+        if exportando() {
+            ExportDialog {
+                listas: vec![lista()],
+                onclose: move |_| exportando.set(false),
+            }
+        }
+        if compartiendo() {
+            ShareDialog {
+                lista: lista(),
+                onclose: move |_| compartiendo.set(false),
+            }
+        }
+        if dividiendo() {
+            SplitCostDialog {
+                lista: lista(),
+                miembros: miembros(),
+                onclose: move |_| dividiendo.set(false),
+            }
+        }
+    }
+}
+
+// Dueño de la lista y miembros asignados a ella
+#[component]
+fn MiembrosLista() -> Element {
+    let ListaViewState { lista, miembros, .. } = use_context::<ListaViewState>();
+    if miembros.read().is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "flex flex-wrap items-center gap-x-3 px-2 mb-2 text-sm",
+            label { class: "flex items-center gap-x-1",
+                {tid!("list_owner")}
+                select {
+                    class: "bg-gray-50 border border-gray-300 rounded-lg p-1",
+                    onchange: move |event| {
+                        let propietario = event.value().parse::<usize>().ok().filter(|id| *id != 0);
+                        _ = DATABASE.with(|f| f.set_list_owner(lista().id, propietario));
+                        recargar(lista);
+                    },
+                    option { value: "0", selected: lista().propietario.is_none(), {tid!("member_nobody")} }
+                    for miembro in miembros() {
+                        option {
+                            key: "{miembro.id}",
+                            value: "{miembro.id}",
+                            selected: lista().propietario == Some(miembro.id),
+                            "{miembro.nombre}"
+                        }
+                    }
+                }
+            }
+            span { {tid!("list_assignees")} }
+            for miembro in miembros() {
+                label { key: "{miembro.id}", class: "flex items-center gap-x-1",
+                    input {
+                        r#type: "checkbox",
+                        checked: lista().asignados.contains(&miembro.id),
+                        onchange: move |event| {
+                            let mut asignados = lista().asignados;
+                            asignados.retain(|id| *id != miembro.id);
+                            if event.checked() {
+                                asignados.push(miembro.id);
+                            }
+                            _ = DATABASE.with(|f| f.set_list_assignees(lista().id, asignados));
+                            recargar(lista);
+                        },
+                    }
+                    "{miembro.nombre}"
+                }
+            }
+        }
+    }
+}
+
+// Tasas de impuesto propias de las categorías de la lista. Vacía, la categoría usa la de la lista
+#[component]
+fn TasasCategoria() -> Element {
+    let ListaViewState { lista, .. } = use_context::<ListaViewState>();
+    let mut error = use_signal(|| "".to_string());
+    let mut categorias: BTreeMap<String, String> = BTreeMap::new();
+    for item in lista().items.unwrap_or_default() {
+        let categoria = item.categoria.trim().to_string();
+        if !categoria.is_empty() {
+            categorias.entry(normalizar(&categoria)).or_insert(categoria);
+        }
+    }
+    if categorias.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        details { class: "px-2 mb-2 text-sm",
+            summary { class: "cursor-pointer", {tid!("category_tax_rates")} }
+            div { class: "flex flex-wrap gap-x-4 gap-y-1 mt-1",
+                for categoria in categorias.into_values() {
+                    div { key: "{categoria}", class: "flex items-center gap-x-1",
+                        span { "{categoria}" }
+                        input {
+                            r#type: "number",
+                            class: "w-[6ch] bg-gray-50 border border-gray-300 rounded-lg px-1",
+                            min: "0",
+                            max: "100",
+                            placeholder: "{lista().tasa_impuesto:.2}",
+                            value: lista()
+                                .tasas_categoria
+                                .iter()
+                                .find(|(nombre, _)| normalizar(nombre) == normalizar(&categoria))
+                                .map(|(_, tasa)| format!("{tasa:.2}"))
+                                .unwrap_or_default(),
+                            onchange: move |event| {
+                                // Vacía vuelve a la tasa de la lista, y si no es un número no
+                                // se guarda
+                                let valor = event.value();
+                                let tasa_impuesto = match valor.trim() {
+                                    "" => None,
+                                    texto => match texto.parse::<f32>() {
+                                        Ok(tasa_impuesto) => Some(tasa_impuesto),
+                                        Err(_) => {
+                                            recargar(lista);
+                                            return;
+                                        }
+                                    },
+                                };
+                                let categoria = categoria.clone();
+                                let resultado = DATABASE
+                                    .with(|f| {
+                                        f.set_category_tax_rate(lista().id, categoria, tasa_impuesto)
+                                    });
+                                error
+                                    .set(
+                                        resultado
+                                            .err()
+                                            .map(|error| error.to_string())
+                                            .unwrap_or_default(),
+                                    );
+                                recargar(lista);
+                            },
+                        }
+                        "%"
+                    }
+                }
+            }
+            if !error().is_empty() {
+                p { class: "text-red-600", "{error}" }
+            }
+        }
+    }
+}
+
+// Lo que probablemente se está acabando, según cada cuánto se suele comprar
+#[component]
+fn Sugerencias() -> Element {
+    let ListaViewState { lista, sinonimos, .. } = use_context::<ListaViewState>();
+    let compras = use_signal(|| DATABASE.with(|f| f.get_purchases()).unwrap_or_default());
+    let sugerencias = sugerir(&compras.read(), &lista(), hoy(), &sinonimos.read());
+    if sugerencias.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "flex flex-wrap items-center gap-2 px-2 mb-2 text-sm",
+            span { {tid!("suggestions_running_out")} }
+            for (sugerencia, item) in sugerencias
+                .into_iter()
+                .map(|sugerencia| {
+                    let item = sugerencia.item();
+                    (sugerencia, item)
+                })
+            {
+                button {
+                    key: "{sugerencia.nombre}-{sugerencia.unidad}",
+                    r#type: "button",
+                    class: "flex items-center gap-x-1 rounded-full px-3 py-1 bg-yellow-200",
+                    title: tid!(
+                        "suggestion_detail",
+                        interval: sugerencia.intervalo.round() as i64,
+                        days: sugerencia.dias_desde_ultima(hoy())
+                    ),
+                    onclick: move |_| {
+                        _ = DATABASE.with(|f| f.create_new_list_item(lista().id, item.clone()));
+                        recargar(lista);
+                    },
+                    MaterialIcon { name: "add", size: 16 }
+                    {format!("{} {:.3} {}", sugerencia.nombre, sugerencia.cantidad, tid!(& sugerencia.unidad))}
+                }
+            }
+        }
+    }
+}
+
+// Agrega un producto por su código de barras, escrito o leído de una foto
+#[component]
+fn CodigoDeBarras() -> Element {
+    let mut lista = use_context::<ListaViewState>().lista;
+    let mut codigo = use_signal(|| "".to_string());
+    let mut mensaje = use_signal(|| "".to_string());
+
+    let mut agregar = move |escrito: String| {
+        let escrito = normalizar_codigo(&escrito);
+        mensaje.set("".to_string());
+        if !es_valido(&escrito) {
+            mensaje.set(tid!("barcode_invalid"));
+            return;
+        }
+        match DATABASE.with(|f| agregar_por_codigo(f.as_ref(), lista().id, &escrito)) {
+            Ok(Some(_)) => {
+                codigo.set("".to_string());
+                recargar(lista);
+            }
+            // Un código nuevo queda en un item sin guardar, para que se le ponga nombre
+            Ok(None) => {
+                mensaje.set(tid!("barcode_unknown"));
+                codigo.set("".to_string());
+                let mut items = lista().items.unwrap_or_default();
+                items.push(Item {
+                    codigo_barras: escrito,
+                    ..Default::default()
+                });
+                lista.write().items = Some(items);
+            }
+            Err(error) => mensaje.set(error.to_string()),
+        }
+    };
+
+    rsx! {
+        div { class: "flex items-center gap-x-2 px-2 mb-2 text-sm",
+            input {
+                r#type: "text",
+                inputmode: "numeric",
+                class: "w-[16ch] bg-gray-50 border border-gray-300 rounded-lg p-1",
+                placeholder: tid!("barcode"),
+                value: codigo,
+                oninput: move |event| codigo.set(event.value()),
+                onkeydown: move |event| {
+                    if event.key() == Key::Enter {
+                        agregar(codigo());
+                    }
+                },
+            }
+            button {
+                r#type: "button",
+                class: "text-blue-600 rounded-full text-center",
+                title: tid!("barcode_add"),
+                disabled: codigo().trim().is_empty(),
+                onclick: move |_| agregar(codigo()),
+                MaterialIcon { name: "add" }
+            }
+            label {
+                class: "flex items-center text-blue-600 cursor-pointer",
+                title: tid!("barcode_scan"),
+                MaterialIcon { name: "qr_code_scanner" }
+                input {
+                    r#type: "file",
+                    class: "hidden",
+                    accept: "image/*",
+                    onchange: move |event: Event<FormData>| async move {
+                        for archivo in event.files() {
+                            let Ok(datos) = archivo.read_bytes().await else {
+                                continue;
+                            };
+                            match leer_codigo_de_barras(&datos) {
+                                Ok(leido) => agregar(leido),
+                                Err(_) => mensaje.set(tid!("barcode_not_found")),
+                            }
+                        }
+                    },
+                }
+            }
+            if !mensaje().is_empty() {
+                span { "{mensaje}" }
+            }
+        }
+    }
+}
+
+// Divide los items pendientes entre los miembros que van a comprar
+#[component]
+fn RepartoCompra() -> Element {
+    let ListaViewState { lista, miembros, .. } = use_context::<ListaViewState>();
+    let mut participantes: Signal<Vec<usize>> =
+        use_signal(|| miembros.read().iter().map(|miembro| miembro.id).collect());
+    let mut reparto = use_signal(Reparto::default);
+
+    let elegidos: Vec<Miembro> = miembros
+        .read()
+        .iter()
+        .filter(|miembro| participantes.read().contains(&miembro.id))
+        .cloned()
+        .collect();
+    let partes = repartir(&lista(), &elegidos, reparto());
+    let partes_a_guardar = partes.clone();
+
+    rsx! {
+        div { class: "bg-blue-50 rounded-lg p-2 mx-2 mb-2 text-sm",
+            div { class: "flex flex-wrap items-center gap-x-3 mb-2",
+                span { {tid!("split_between")} }
+                for miembro in miembros() {
+                    label { key: "{miembro.id}", class: "flex items-center gap-x-1",
+                        input {
+                            r#type: "checkbox",
+                            checked: participantes.read().contains(&miembro.id),
+                            onchange: move |event| {
+                                participantes.write().retain(|id| *id != miembro.id);
+                                if event.checked() {
+                                    participantes.write().push(miembro.id);
+                                }
+                            },
+                        }
+                        "{miembro.nombre}"
+                    }
+                }
+                select {
+                    class: "bg-gray-50 border border-gray-300 rounded-lg p-1",
+                    onchange: move |event| {
+                        reparto.set(match event.value().as_str() {
+                            "categoria" => Reparto::PorCategoria,
+                            _ => Reparto::Alternado,
+                        });
+                    },
+                    option { value: "alternado", selected: reparto() == Reparto::Alternado, {tid!(Reparto::Alternado.clave())} }
+                    option {
+                        value: "categoria",
+                        selected: reparto() == Reparto::PorCategoria,
+                        {tid!(Reparto::PorCategoria.clave())}
+                    }
+                }
+            }
+            div { class: "columns-1 md:columns-2 lg:columns-3",
+                for parte in partes {
+                    div { key: "{parte.miembro.id}", class: "break-inside-avoid-column mb-2",
+                        div { class: "flex justify-between font-bold",
+                            span { "{parte.miembro.nombre}" }
+                            span { {format!("{} {:.2}", tid!("subtotal"), parte.subtotal)} }
+                        }
+                        ul { class: "list-disc list-inside",
+                            for item in parte.items {
+                                li { key: "{item.id}",
+                                    {format!("{:.3} {} {}", item.cantidad_requerida, tid!(& item.unidad), item.nombre)}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            button {
+                class: "text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                onclick: move |_| {
+                    for parte in partes_a_guardar.iter() {
+                        for item in parte.items.iter().filter(|item| item.asignado_a != Some(parte.miembro.id)) {
+                            let mut item = item.clone();
+                            item.asignado_a = Some(parte.miembro.id);
+                            _ = DATABASE.with(|f| f.update_list_item(item));
+                        }
+                    }
+                    recargar(lista);
+                },
+                {tid!("split_assign")}
+            }
+        }
+    }
+}
+
+#[component]
+fn FilterBar(mut filtro: Signal<FiltroItems>) -> Element {
+    fn parse_precio(valor: String) -> Option<f32> {
+        valor.trim().parse().ok()
+    }
+    let miembro_actual = DATABASE
+        .with(|f| f.get_setting(CLAVE_MIEMBRO_ACTUAL))
+        .unwrap_or_default()
+        .and_then(|valor| valor.parse::<usize>().ok());
+
+    rsx! {
+        div { class: "flex flex-wrap items-center gap-2 px-2 mb-2 text-sm",
+            input {
+                r#type: "search",
+                class: "bg-gray-50 border border-gray-300 rounded-lg p-1",
+                placeholder: tid!("filter_text"),
+                value: "{filtro.read().texto}",
+                oninput: move |event| filtro.write().texto = event.value(),
+            }
+            select {
+                class: "bg-gray-50 border border-gray-300 rounded-lg p-1",
+                onchange: move |event| {
+                    filtro.write().estado = match event.value().as_str() {
+                        "pendientes" => EstadoItem::Pendientes,
+                        "comprados" => EstadoItem::Comprados,
+                        _ => EstadoItem::Todos,
+                    };
+                },
+                option { value: "todos", selected: filtro.read().estado == EstadoItem::Todos, {tid!("filter_all")} }
+                option {
+                    value: "pendientes",
+                    selected: filtro.read().estado == EstadoItem::Pendientes,
+                    {tid!("filter_pending")}
+                }
+                option {
+                    value: "comprados",
+                    selected: filtro.read().estado == EstadoItem::Comprados,
+                    {tid!("filter_bought")}
+                }
+            }
+            select {
+                class: "bg-gray-50 border border-gray-300 rounded-lg p-1",
+                onchange: move |event| {
+                    let unidad = event.value();
+                    filtro.write().unidad = if unidad.is_empty() { None } else { Some(unidad) };
+                },
+                option { value: "", {tid!("filter_any_unit")} }
+                for unidad in ["unidad", "kg", "docena"] {
+                    option {
+                        value: unidad,
+                        selected: filtro.read().unidad.as_deref() == Some(unidad),
+                        {tid!(unidad)}
+                    }
+                }
+            }
+            input {
+                r#type: "number",
+                class: "w-[8ch] bg-gray-50 border border-gray-300 rounded-lg p-1",
+                placeholder: tid!("filter_min_price"),
+                onchange: move |event| filtro.write().precio_minimo = parse_precio(event.value()),
+            }
+            input {
+                r#type: "number",
+                class: "w-[8ch] bg-gray-50 border border-gray-300 rounded-lg p-1",
+                placeholder: tid!("filter_max_price"),
+                onchange: move |event| filtro.write().precio_maximo = parse_precio(event.value()),
+            }
+            div { class: "flex items-center gap-x-1",
+                input {
+                    r#type: "checkbox",
+                    id: "solo_imprescindibles",
+                    checked: filtro.read().solo_imprescindibles,
+                    onchange: move |event| filtro.write().solo_imprescindibles = event.checked(),
+                }
+                label { r#for: "solo_imprescindibles", {tid!("only_must_have")} }
+            }
+            if let Some(miembro) = miembro_actual {
+                div { class: "flex items-center gap-x-1",
+                    input {
+                        r#type: "checkbox",
+                        id: "mis_items",
+                        checked: filtro.read().asignados_a.is_some(),
+                        onchange: move |event| {
+                            filtro.write().asignados_a = event.checked().then_some(miembro);
+                        },
+                    }
+                    label { r#for: "mis_items", {tid!("filter_my_items")} }
+                }
+            }
+        }
     }
 }
 
 #[component]
 fn ItemCard(item: Item) -> Element {
-    let mut lista: Signal<Lista> = use_context::<ListaViewState>().lista;
+    let lista: Signal<Lista> = use_context::<ListaViewState>().lista;
+    let miembros = use_context::<ListaViewState>().miembros;
+    let agregado_por = nombre_miembro(&miembros.read(), item.agregado_por);
+    let comprado_por = nombre_miembro(&miembros.read(), item.comprado_por);
 
     let bg_card_color = if item.cantidad_comprada > 0 as f32 {
         CLASE_COLOR_ITEM_COMPRADO
     } else {
         CLASE_COLOR_ITEM_NO_COMPRADO
     };
+    let clase_prioridad = if item.prioridad == Prioridad::Imprescindible {
+        CLASE_ITEM_IMPRESCINDIBLE
+    } else {
+        ""
+    };
 
-    let precio_total = item.cantidad_comprada * item.precio;
+    let (subtotal, impuesto) = item.subtotal_e_impuesto(lista().tasa_de(&item));
+    let precio_total = subtotal + impuesto;
 
-    fn handle_change(event: Event<FormData>) {
-        let item = event.parsed_values::<ItemForm>().unwrap().into_item();
-        if item.id == 0 {
-            _ = DATABASE.with(|f| f.create_new_list_item(item.id_lista, item));
-        } else {
-            _ = DATABASE.with(|f| f.update_list_item(item));
-        };
-    }
+    let mut miniatura = use_signal(|| {
+        DATABASE
+            .with(|f| f.get_item_thumbnail(item.id))
+            .unwrap_or_default()
+    });
+    let mut imagen_completa: Signal<Option<Imagen>> = use_signal(|| None);
+    let mut vence = use_signal(|| "".to_string());
+    let mut reponer = use_signal(|| false);
+    let mut vence_guardado: Signal<Option<String>> = use_signal(|| None);
+    let item_comprado = item.clone();
+    let ListaViewState { sinonimos, nombres, .. } = use_context::<ListaViewState>();
+    let duplicado = duplicado_de(&item, &lista().items.unwrap_or_default(), &sinonimos.read());
+    let mut nombre_escrito = use_signal(|| "".to_string());
+    let codigo_invalido = !item.codigo_barras.is_empty() && !es_valido(&item.codigo_barras);
 
     rsx! {
         form {
-            class: "{bg_card_color} rounded-lg p-2 break-inside-avoid-column mb-2",
+            class: "{bg_card_color} {clase_prioridad} rounded-lg p-2 break-inside-avoid-column mb-2",
             onchange: {
                 move |event: Event<FormData>| {
                     handle_change(event);
-                    lista.set(DATABASE.with(|f| f.get_list(lista().id).unwrap()));
+                    recargar(lista);
                 }
             },
             input { r#type: "hidden", name: "id", value: "{item.id}" }
             input { r#type: "hidden", name: "id_lista", value: "{lista().id}" }
+            if let Some(existente) = duplicado {
+                div { class: "flex items-center justify-between gap-x-2 mb-1 px-2 rounded-lg text-sm bg-yellow-200",
+                    span { {tid!("duplicate_of", name: existente.nombre.clone())} }
+                    button {
+                        r#type: "button",
+                        class: "text-blue-600 font-medium",
+                        onclick: move |_| {
+                            let items = lista().items.unwrap_or_default();
+                            if let (Some(existente), Some(duplicado)) = (
+                                items.iter().find(|otro| otro.id == existente.id),
+                                items.iter().find(|otro| otro.id == item.id),
+                            ) {
+                                _ = DATABASE.with(|f| fusionar(f.as_ref(), existente, duplicado));
+                            }
+                            recargar(lista);
+                        },
+                        {tid!("merge_duplicate")}
+                    }
+                }
+            }
             div { class: "flex text-md justify-between",
                 div { class: "flex",
                     input {
@@ -131,13 +776,22 @@ fn ItemCard(item: Item) -> Element {
                         value: "{item.precio:.2}",
                     }
                     {format!(" {} {}", tid!("per"), tid!(& item.unidad))}
+                    label { class: "flex items-center ml-1 text-xs",
+                        input {
+                            r#type: "checkbox",
+                            name: "precio_incluye_impuesto",
+                            value: "1",
+                            checked: item.precio_incluye_impuesto,
+                        }
+                        {tid!("price_includes_tax")}
+                    }
                 }
                 button {
                     r#type: "button",
                     class: "text-red-600 rounded-full px-5 text-center",
                     onclick: move |_| {
                         let _ = DATABASE.with(|f| f.delete_item(item.id));
-                        lista.set(DATABASE.with(|f| f.get_list(lista().id).unwrap()));
+                        recargar(lista);
                     },
                     MaterialIcon { name: "delete" }
                 }
@@ -148,7 +802,14 @@ fn ItemCard(item: Item) -> Element {
                         r#type: "text",
                         class: "w-42",
                         name: "nombre",
+                        list: "nombres-{item.id}",
                         value: item.nombre,
+                        oninput: move |event| nombre_escrito.set(event.value()),
+                    }
+                    datalist { id: "nombres-{item.id}",
+                        for nombre in autocompletar(&nombre_escrito(), &nombres.read(), &sinonimos.read()) {
+                            option { key: "{nombre}", value: "{nombre}" }
+                        }
                     }
                 }
                 div {
@@ -161,36 +822,209 @@ fn ItemCard(item: Item) -> Element {
                     {format!("{} {:.2}", tid!("total"), precio_total)}
                 }
             }
+            div { class: "flex flex-wrap gap-x-2 text-sm",
+                select { name: "prioridad",
+                    for prioridad in Prioridad::TODAS {
+                        option {
+                            value: "{prioridad as usize}",
+                            selected: item.prioridad == prioridad,
+                            {tid!(prioridad.clave())}
+                        }
+                    }
+                }
+                input {
+                    r#type: "text",
+                    class: "w-[14ch] bg-gray-50 rounded-lg px-1",
+                    name: "categoria",
+                    placeholder: tid!("category"),
+                    value: item.categoria,
+                }
+                input {
+                    r#type: "text",
+                    inputmode: "numeric",
+                    class: if codigo_invalido { "w-[15ch] bg-gray-50 rounded-lg px-1 ring-2 ring-red-500" } else { "w-[15ch] bg-gray-50 rounded-lg px-1" },
+                    name: "codigo_barras",
+                    placeholder: tid!("barcode"),
+                    title: if codigo_invalido { tid!("barcode_invalid") } else { tid!("barcode") },
+                    value: item.codigo_barras,
+                }
+                if !miembros.read().is_empty() {
+                    select { name: "asignado_a",
+                        option { value: "0", selected: item.asignado_a.is_none(), {tid!("item_unassigned")} }
+                        for miembro in miembros() {
+                            option {
+                                key: "{miembro.id}",
+                                value: "{miembro.id}",
+                                selected: item.asignado_a == Some(miembro.id),
+                                "{miembro.nombre}"
+                            }
+                        }
+                    }
+                } else if let Some(asignado_a) = item.asignado_a {
+                    input { r#type: "hidden", name: "asignado_a", value: "{asignado_a}" }
+                }
+            }
+            if agregado_por.is_some() || comprado_por.is_some() {
+                div { class: "flex gap-x-3 text-xs",
+                    if let Some(nombre) = agregado_por {
+                        span { {tid!("added_by", name: nombre)} }
+                    }
+                    if let Some(nombre) = comprado_por {
+                        span { {tid!("bought_by", name: nombre)} }
+                    }
+                }
+            }
+            // La fecha de vencimiento no es parte del item, así que sus campos no tienen nombre y
+            // sus cambios no llegan al formulario
+            if item.comprado() && item.id != 0 {
+                div { class: "flex flex-wrap items-center gap-x-2 text-sm",
+                    label { class: "flex items-center gap-x-1",
+                        {tid!("best_before")}
+                        input {
+                            r#type: "date",
+                            class: "bg-gray-50 rounded-lg px-1",
+                            value: vence,
+                            onchange: move |event| {
+                                event.stop_propagation();
+                                vence.set(event.value());
+                            },
+                        }
+                    }
+                    label { class: "flex items-center gap-x-1",
+                        input {
+                            r#type: "checkbox",
+                            checked: reponer(),
+                            onchange: move |event| {
+                                event.stop_propagation();
+                                reponer.set(event.checked());
+                            },
+                        }
+                        {tid!("expiration_readd")}
+                    }
+                    button {
+                        r#type: "button",
+                        class: "text-blue-600 rounded-full text-center",
+                        disabled: leer_fecha(&vence()).is_none(),
+                        onclick: move |_| {
+                            let Some(dias) = leer_fecha(&vence()) else {
+                                return;
+                            };
+                            let vencimiento = Vencimiento {
+                                id: 0,
+                                nombre: item_comprado.nombre.clone(),
+                                unidad: item_comprado.unidad.clone(),
+                                cantidad: item_comprado.cantidad_comprada,
+                                vence: dias,
+                                id_lista: Some(item_comprado.id_lista),
+                                reponer: reponer(),
+                            };
+                            if DATABASE.with(|f| f.add_expiration(vencimiento)).is_ok() {
+                                vence_guardado.set(Some(vence()));
+                                vence.set("".to_string());
+                            }
+                        },
+                        MaterialIcon { name: "event_available" }
+                    }
+                    if let Some(fecha) = vence_guardado() {
+                        span { class: "text-xs", {tid!("expiration_saved", date: fecha)} }
+                    }
+                }
+            }
+            details { class: "text-sm", open: !item.notas.is_empty(),
+                summary { class: "cursor-pointer", {tid!("notes")} }
+                textarea {
+                    class: "w-full bg-gray-50 rounded-lg p-1",
+                    name: "notas",
+                    placeholder: tid!("notes_placeholder"),
+                    value: item.notas,
+                }
+            }
+            if item.id != 0 {
+                div { class: "flex items-center gap-x-2 mt-1",
+                    if let Some(imagen_miniatura) = miniatura() {
+                        img {
+                            class: "w-16 h-16 object-cover rounded-lg cursor-pointer",
+                            alt: tid!("product_photo"),
+                            src: imagen_miniatura.data_url(),
+                            onclick: move |_| {
+                                imagen_completa
+                                    .set(DATABASE.with(|f| f.get_item_image(item.id)).unwrap_or_default());
+                            },
+                        }
+                        button {
+                            r#type: "button",
+                            class: "text-red-600 rounded-full text-center",
+                            onclick: move |_| {
+                                _ = DATABASE.with(|f| f.delete_item_image(item.id));
+                                miniatura.set(None);
+                            },
+                            MaterialIcon { name: "hide_image" }
+                        }
+                    }
+                    label { class: "text-blue-600 cursor-pointer",
+                        MaterialIcon { name: "add_a_photo" }
+                        input {
+                            r#type: "file",
+                            class: "hidden",
+                            accept: "image/*",
+                            onchange: move |event: Event<FormData>| async move {
+                                event.stop_propagation();
+                                for archivo in event.files() {
+                                    let Ok(datos) = archivo.read_bytes().await else {
+                                        continue;
+                                    };
+                                    if let Ok(imagen) = Imagen::nueva(datos.to_vec()) {
+                                        _ = DATABASE.with(|f| f.set_item_image(item.id, imagen));
+                                        miniatura
+                                            .set(
+                                                DATABASE
+                                                    .with(|f| f.get_item_thumbnail(item.id))
+                                                    .unwrap_or_default(),
+                                            );
+                                    }
+                                }
+                            },
+                        }
+                    }
+                }
+            }
+            if let Some(imagen) = imagen_completa() {
+                div {
+                    class: "fixed inset-0 z-50 flex items-center justify-center bg-black/80 p-2",
+                    onclick: move |_| imagen_completa.set(None),
+                    img {
+                        class: "max-w-full max-h-full rounded-lg",
+                        alt: tid!("product_photo"),
+                        src: imagen.data_url(),
+                    }
+                }
+            }
         }
     }
 }
 
 #[component]
 fn ItemCardSimple(item: Item) -> Element {
-    let mut lista: Signal<Lista> = use_context::<ListaViewState>().lista;
+    let lista: Signal<Lista> = use_context::<ListaViewState>().lista;
 
     let bg_card_color = if item.cantidad_comprada > 0 as f32 {
         CLASE_COLOR_ITEM_COMPRADO
     } else {
         CLASE_COLOR_ITEM_NO_COMPRADO
     };
-
-    fn handle_change(event: Event<FormData>) {
-        let item = event.parsed_values::<ItemForm>().unwrap().into_item();
-        if item.id == 0 {
-            _ = DATABASE.with(|f| f.create_new_list_item(item.id_lista, item));
-        } else {
-            _ = DATABASE.with(|f| f.update_list_item(item));
-        };
-    }
+    let clase_prioridad = if item.prioridad == Prioridad::Imprescindible {
+        CLASE_ITEM_IMPRESCINDIBLE
+    } else {
+        ""
+    };
 
     rsx! {
         form {
-            class: "{bg_card_color} rounded-lg p-2 break-inside-avoid-column mb-2 flex text-lg font-bold justify-between",
+            class: "{bg_card_color} {clase_prioridad} rounded-lg p-2 break-inside-avoid-column mb-2 flex text-lg font-bold justify-between",
             onchange: {
                 move |event: Event<FormData>| {
                     handle_change(event);
-                    lista.set(DATABASE.with(|f| f.get_list(lista().id).unwrap()));
+                    recargar(lista);
                 }
             },
             input { r#type: "hidden", name: "id", value: "{item.id}" }
@@ -202,11 +1036,38 @@ fn ItemCardSimple(item: Item) -> Element {
                 value: "{item.cantidad_requerida}",
             }
             input { r#type: "hidden", name: "precio", value: "{item.precio}" }
+            input { r#type: "hidden", name: "notas", value: "{item.notas}" }
             input {
-                r#type: "text",
-                class: "w-42",
-                name: "nombre",
-                value: item.nombre,
+                r#type: "hidden",
+                name: "prioridad",
+                value: "{item.prioridad as usize}",
+            }
+            input { r#type: "hidden", name: "categoria", value: "{item.categoria}" }
+            input {
+                r#type: "hidden",
+                name: "codigo_barras",
+                value: "{item.codigo_barras}",
+            }
+            if let Some(asignado_a) = item.asignado_a {
+                input { r#type: "hidden", name: "asignado_a", value: "{asignado_a}" }
+            }
+            if item.precio_incluye_impuesto {
+                input {
+                    r#type: "hidden",
+                    name: "precio_incluye_impuesto",
+                    value: "1",
+                }
+            }
+            div { class: "flex flex-col",
+                input {
+                    r#type: "text",
+                    class: "w-42",
+                    name: "nombre",
+                    value: item.nombre,
+                }
+                if !item.notas.is_empty() {
+                    span { class: "text-sm font-normal", "{item.notas}" }
+                }
             }
             input {
                 r#type: "checkbox",
@@ -221,7 +1082,7 @@ fn ItemCardSimple(item: Item) -> Element {
                 class: "text-red-600 rounded-full px-5 text-center",
                 onclick: move |_| {
                     let _ = DATABASE.with(|f| f.delete_item(item.id));
-                    lista.set(DATABASE.with(|f| f.get_list(lista().id).unwrap()));
+                    recargar(lista);
                 },
                 MaterialIcon { name: "delete" }
             }
@@ -0,0 +1,206 @@
+use crate::DATABASE;
+use crate::Route;
+use crate::model::fecha::{formatear_fecha, hoy};
+use crate::model::menu::{
+    Comida, DIAS_POR_SEMANA, Momento, clave_dia_de_semana, de_la_semana, generar_lista,
+    inicio_de_semana,
+};
+use crate::model::recetas::Receta;
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[component]
+pub fn MenuSemanal() -> Element {
+    let mut inicio = use_signal(|| inicio_de_semana(hoy()));
+    let comidas = use_signal(|| DATABASE.with(|f| f.get_meals()).unwrap_or_default());
+    let recetas = use_signal(|| DATABASE.with(|f| f.get_recipes()).unwrap_or_default());
+    let listas = use_signal(|| DATABASE.with(|f| f.get_list_of_lists()).unwrap_or_default());
+    let mut id_lista = use_signal(|| 0);
+    let mut mensaje = use_signal(|| "".to_string());
+    let navigator = use_navigator();
+
+    rsx! {
+        div { id: "menu", class: "space-y-4 my-5 px-2",
+            div { class: "flex justify-between items-center",
+                button {
+                    r#type: "button",
+                    class: "text-blue-600 rounded-full px-5 text-center",
+                    onclick: move |_| inicio -= DIAS_POR_SEMANA,
+                    MaterialIcon { name: "chevron_left", size: 24 }
+                }
+                span { class: "text-sm font-medium",
+                    {
+                        tid!(
+                            "meal_plan_week",
+                            from: formatear_fecha(inicio()),
+                            to: formatear_fecha(inicio() + DIAS_POR_SEMANA - 1)
+                        )
+                    }
+                }
+                button {
+                    r#type: "button",
+                    class: "text-blue-600 rounded-full px-5 text-center",
+                    onclick: move |_| inicio += DIAS_POR_SEMANA,
+                    MaterialIcon { name: "chevron_right", size: 24 }
+                }
+            }
+            if recetas.is_empty() {
+                Link { class: "block text-sm px-1 text-blue-600", to: Route::Recetas {},
+                    {tid!("meal_plan_no_recipes")}
+                }
+            }
+            div { class: "columns-1 md:columns-2 lg:columns-3 xl:columns-4",
+                for dia in inicio()..inicio() + DIAS_POR_SEMANA {
+                    DiaDelMenu { key: "{dia}", dia, comidas, recetas }
+                }
+            }
+            if !de_la_semana(&comidas(), inicio()).is_empty() {
+                div { class: "flex gap-x-2 items-center",
+                    select {
+                        class: "bg-gray-50 border border-gray-300 rounded-lg block w-full p-2.5",
+                        onchange: move |event| id_lista.set(event.value().parse().unwrap_or_default()),
+                        option { value: "0", selected: id_lista() == 0, {tid!("import_new_list")} }
+                        for lista in listas() {
+                            option {
+                                key: "{lista.id}",
+                                value: "{lista.id}",
+                                selected: id_lista() == lista.id,
+                                "{lista.nombre}"
+                            }
+                        }
+                    }
+                    button {
+                        class: "basis-1/3 text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                        onclick: move |_| {
+                            match DATABASE.with(|f| generar_lista(f.as_ref(), inicio(), id_lista())) {
+                                Ok((_, 0)) => mensaje.set(tid!("meal_plan_nothing_needed")),
+                                Ok((id, _)) => {
+                                    navigator.push(Route::ListaView { id });
+                                }
+                                Err(error) => mensaje.set(error.to_string()),
+                            }
+                        },
+                        {tid!("meal_plan_generate_list")}
+                    }
+                }
+                if !mensaje().is_empty() {
+                    p { class: "text-sm px-1", "{mensaje}" }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn DiaDelMenu(dia: i64, mut comidas: Signal<Vec<Comida>>, recetas: Signal<Vec<Receta>>) -> Element {
+    let mut momento = use_signal(Momento::default);
+    let mut id_receta = use_signal(|| recetas.read().first().map(|receta| receta.id).unwrap_or_default());
+    let mut porciones = use_signal(|| recetas.read().first().map(|receta| receta.porciones).unwrap_or(1));
+    let del_dia: Vec<Comida> = comidas.read().iter().filter(|comida| comida.dia == dia).cloned().collect();
+
+    rsx! {
+        div { class: "break-inside-avoid-column space-y-2 p-3 mb-2 rounded-lg bg-gray-300",
+            h3 { class: "text-sm font-medium",
+                {format!("{} {}", tid!(clave_dia_de_semana(dia)), formatear_fecha(dia))}
+            }
+            for comida in del_dia {
+                div { key: "{comida.id}", class: "flex gap-x-2 items-center",
+                    span { class: "text-xs w-[10ch]", {tid!(comida.momento.clave())} }
+                    span { class: "flex-1",
+                        {
+                            recetas
+                                .read()
+                                .iter()
+                                .find(|receta| receta.id == comida.id_receta)
+                                .map(|receta| receta.nombre.clone())
+                                .unwrap_or_default()
+                        }
+                    }
+                    input {
+                        r#type: "number",
+                        class: "w-[6ch]",
+                        min: "1",
+                        title: tid!("recipe_servings"),
+                        value: "{comida.porciones}",
+                        onchange: move |event| {
+                            if let Some(comida) = comidas().into_iter().find(|otra| otra.id == comida.id) {
+                                let porciones = event.value().parse().unwrap_or(comida.porciones);
+                                _ = DATABASE.with(|f| f.save_meal(Comida { porciones, ..comida }));
+                            }
+                            comidas.set(DATABASE.with(|f| f.get_meals()).unwrap_or_default());
+                        },
+                    }
+                    button {
+                        r#type: "button",
+                        class: "text-red-600 rounded-full text-center",
+                        onclick: move |_| {
+                            _ = DATABASE.with(|f| f.delete_meal(comida.id));
+                            comidas.set(DATABASE.with(|f| f.get_meals()).unwrap_or_default());
+                        },
+                        MaterialIcon { name: "delete" }
+                    }
+                }
+            }
+            if !recetas.is_empty() {
+                div { class: "flex gap-x-2 items-center",
+                    select {
+                        class: "w-[10ch]",
+                        onchange: move |event| momento.set(event.value().parse::<usize>().unwrap_or_default().into()),
+                        for opcion in Momento::TODOS {
+                            option {
+                                value: "{opcion as usize}",
+                                selected: opcion == momento(),
+                                {tid!(opcion.clave())}
+                            }
+                        }
+                    }
+                    select {
+                        class: "flex-1 min-w-0",
+                        onchange: move |event| {
+                            id_receta.set(event.value().parse().unwrap_or_default());
+                            if let Some(receta) = recetas.read().iter().find(|receta| receta.id == id_receta()) {
+                                porciones.set(receta.porciones);
+                            }
+                        },
+                        for receta in recetas() {
+                            option {
+                                key: "{receta.id}",
+                                value: "{receta.id}",
+                                selected: id_receta() == receta.id,
+                                "{receta.nombre}"
+                            }
+                        }
+                    }
+                    input {
+                        r#type: "number",
+                        class: "w-[6ch]",
+                        min: "1",
+                        title: tid!("recipe_servings"),
+                        value: "{porciones}",
+                        oninput: move |event| porciones.set(event.value().parse().unwrap_or(1)),
+                    }
+                    button {
+                        r#type: "button",
+                        class: "text-blue-600 rounded-full text-center",
+                        title: tid!("meal_plan_add"),
+                        onclick: move |_| {
+                            _ = DATABASE
+                                .with(|f| {
+                                    f.save_meal(Comida {
+                                        id: 0,
+                                        dia,
+                                        momento: momento(),
+                                        id_receta: id_receta(),
+                                        porciones: porciones(),
+                                    })
+                                });
+                            comidas.set(DATABASE.with(|f| f.get_meals()).unwrap_or_default());
+                        },
+                        MaterialIcon { name: "add" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,38 @@
+mod busqueda;
+pub use busqueda::Busqueda;
+
+mod cuentas;
+pub use cuentas::Cuentas;
+
+mod despensa;
+pub use despensa::Despensa;
+
+mod hogar;
+pub use hogar::Hogar;
+
 mod home;
 pub use home::Home;
 
+mod importar;
+pub use importar::Importar;
+
+mod menu;
+pub use menu::MenuSemanal;
+
+mod recetas;
+pub use recetas::Recetas;
+
+mod recibo;
+pub use recibo::ImportarRecibo;
+
+mod respaldos;
+pub use respaldos::Respaldos;
+
+mod sinonimos;
+pub use sinonimos::EditorSinonimos;
+
+mod sincronizacion;
+pub use sincronizacion::{CLAVE_VISIBLE_EN_RED, Sincronizacion};
+
 mod lista;
 pub use lista::ListaView;
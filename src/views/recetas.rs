@@ -0,0 +1,157 @@
+use crate::DATABASE;
+use crate::model::Lista;
+use crate::model::recetas::{Receta, agregar_a_lista, desde_texto};
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[component]
+pub fn Recetas() -> Element {
+    let mut recetas = use_signal(|| DATABASE.with(|f| f.get_recipes()).unwrap_or_default());
+    let listas = use_signal(|| DATABASE.with(|f| f.get_list_of_lists()).unwrap_or_default());
+    let mut texto = use_signal(|| "".to_string());
+    let importacion = use_memo(move || desde_texto(&texto()));
+
+    rsx! {
+        div { id: "recetas", class: "space-y-4 my-5 px-2",
+            textarea {
+                class: "h-48 w-full bg-gray-50 border border-gray-300 rounded-lg p-2 text-sm",
+                placeholder: tid!("recipe_import_placeholder"),
+                value: texto,
+                oninput: move |event| texto.set(event.value()),
+            }
+            if !texto().trim().is_empty() {
+                p { class: "text-sm px-1",
+                    {tid!("import_recognized", count: importacion.read().0.ingredientes.len())}
+                }
+                for error in importacion.read().1.iter() {
+                    p { class: "text-sm px-1 text-red-600",
+                        {tid!("import_line_not_understood", line: error.numero, text: error.texto.clone())}
+                    }
+                }
+                button {
+                    class: "text-white bg-blue-700 font-medium rounded-lg text-sm w-full px-5 py-2.5 text-center",
+                    onclick: move |_| {
+                        let (mut receta, _) = importacion();
+                        if receta.nombre.trim().is_empty() {
+                            receta.nombre = tid!("recipe_default_name");
+                        }
+                        _ = DATABASE.with(|f| f.save_recipe(receta));
+                        texto.set("".to_string());
+                        recetas.set(DATABASE.with(|f| f.get_recipes()).unwrap_or_default());
+                    },
+                    {tid!("recipe_save_new")}
+                }
+            }
+            for receta in recetas() {
+                RecetaCard { key: "{receta.id}", receta, recetas, listas }
+            }
+        }
+    }
+}
+
+#[component]
+fn RecetaCard(receta: Receta, mut recetas: Signal<Vec<Receta>>, listas: Signal<Vec<Lista>>) -> Element {
+    let mut nombre = use_signal(|| receta.nombre.clone());
+    let mut porciones = use_signal(|| receta.porciones);
+    let mut ingredientes = use_signal(|| receta.ingredientes_como_texto());
+    let mut porciones_a_preparar = use_signal(|| receta.porciones);
+    let mut id_lista = use_signal(|| listas.read().first().map(|lista| lista.id).unwrap_or_default());
+    let mut mensaje = use_signal(|| "".to_string());
+
+    rsx! {
+        div { class: "space-y-2 p-3 rounded-lg bg-gray-300",
+            div { class: "flex gap-x-2 items-center",
+                input {
+                    r#type: "text",
+                    class: "flex-1 min-w-0 text-lg",
+                    value: nombre,
+                    oninput: move |event| nombre.set(event.value()),
+                }
+                input {
+                    r#type: "number",
+                    class: "w-[6ch]",
+                    min: "1",
+                    title: tid!("recipe_servings"),
+                    value: "{porciones}",
+                    oninput: move |event| porciones.set(event.value().parse().unwrap_or(1)),
+                }
+                button {
+                    r#type: "button",
+                    class: "text-green-600 rounded-full text-center",
+                    title: tid!("recipe_save"),
+                    onclick: move |_| {
+                        let receta = Receta {
+                            id: receta.id,
+                            nombre: nombre(),
+                            porciones: porciones(),
+                            ingredientes: desde_texto(&ingredientes()).0.ingredientes,
+                        };
+                        _ = DATABASE.with(|f| f.save_recipe(receta));
+                        recetas.set(DATABASE.with(|f| f.get_recipes()).unwrap_or_default());
+                    },
+                    MaterialIcon { name: "check" }
+                }
+                button {
+                    r#type: "button",
+                    class: "text-red-600 rounded-full text-center",
+                    onclick: move |_| {
+                        _ = DATABASE.with(|f| f.delete_recipe(receta.id));
+                        recetas.set(DATABASE.with(|f| f.get_recipes()).unwrap_or_default());
+                    },
+                    MaterialIcon { name: "delete" }
+                }
+            }
+            textarea {
+                class: "h-32 w-full bg-gray-50 border border-gray-300 rounded-lg p-2 text-sm",
+                value: ingredientes,
+                oninput: move |event| ingredientes.set(event.value()),
+            }
+            if !listas.is_empty() {
+                div { class: "flex gap-x-2 items-center",
+                    input {
+                        r#type: "number",
+                        class: "w-[6ch]",
+                        min: "1",
+                        title: tid!("recipe_servings"),
+                        value: "{porciones_a_preparar}",
+                        oninput: move |event| porciones_a_preparar.set(event.value().parse().unwrap_or(1)),
+                    }
+                    select {
+                        class: "bg-gray-50 border border-gray-300 rounded-lg block w-full p-2.5",
+                        onchange: move |event| id_lista.set(event.value().parse().unwrap_or_default()),
+                        for lista in listas() {
+                            option {
+                                key: "{lista.id}",
+                                value: "{lista.id}",
+                                selected: id_lista() == lista.id,
+                                "{lista.nombre}"
+                            }
+                        }
+                    }
+                    button {
+                        class: "basis-1/3 text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                        onclick: move |_| {
+                            let receta = Receta {
+                                id: receta.id,
+                                nombre: nombre(),
+                                porciones: porciones(),
+                                ingredientes: desde_texto(&ingredientes()).0.ingredientes,
+                            };
+                            match DATABASE
+                                .with(|f| agregar_a_lista(f.as_ref(), &receta, porciones_a_preparar(), id_lista()))
+                            {
+                                Ok(cantidad) => mensaje.set(tid!("recipe_added_to_list", count: cantidad)),
+                                Err(error) => mensaje.set(error.to_string()),
+                            }
+                        },
+                        {tid!("recipe_add_to_list")}
+                    }
+                }
+                if !mensaje().is_empty() {
+                    p { class: "text-sm px-1", "{mensaje}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use crate::DATABASE;
+use crate::Route;
+use crate::model::recibo::{Coincidencia, Recibo, aplicar, emparejar, leer_recibo};
+use crate::model::sinonimos::Sinonimos;
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+const CLASE_LINEA_AMBIGUA: &str = "bg-yellow-200";
+const CLASE_LINEA_EMPAREJADA: &str = "bg-green-300";
+const CLASE_LINEA_SIN_ITEM: &str = "bg-gray-300";
+
+fn clase_linea(coincidencia: &Coincidencia) -> &'static str {
+    if coincidencia.ambigua() {
+        CLASE_LINEA_AMBIGUA
+    } else if coincidencia.elegido.is_some() {
+        CLASE_LINEA_EMPAREJADA
+    } else {
+        CLASE_LINEA_SIN_ITEM
+    }
+}
+
+// Completa los precios y cantidades compradas de una lista con el texto de un recibo
+#[component]
+pub fn ImportarRecibo(id: usize) -> Element {
+    let leida = use_hook(|| DATABASE.with(|f| f.get_list(id)).ok());
+    let existe = leida.is_some();
+    let lista = use_signal(|| leida.unwrap_or_default());
+    let sinonimos = use_signal(|| {
+        Sinonimos::desde(&DATABASE.with(|f| f.get_synonyms()).unwrap_or_default())
+    });
+    let mut texto = use_signal(|| "".to_string());
+    let mut coincidencias: Signal<Vec<Coincidencia>> = use_signal(Vec::new);
+    let mut recibo = use_signal(Recibo::default);
+    let mut mensaje = use_signal(|| "".to_string());
+    let navigator = use_navigator();
+    use_effect(move || {
+        if !existe {
+            navigator.replace(Route::Home {});
+        }
+    });
+
+    // Al cambiar el texto se vuelve a emparejar, así que se pierde lo elegido a mano
+    let mut leer = move |nuevo: String| {
+        recibo.set(leer_recibo(&nuevo));
+        texto.set(nuevo);
+        let items = lista().items.unwrap_or_default();
+        coincidencias.set(emparejar(&recibo.read(), &items, &sinonimos.read()));
+    };
+    let items = lista().items.unwrap_or_default();
+    let emparejadas = coincidencias
+        .read()
+        .iter()
+        .filter(|coincidencia| coincidencia.elegido.is_some())
+        .count();
+    let ambiguas = coincidencias
+        .read()
+        .iter()
+        .filter(|coincidencia| coincidencia.ambigua())
+        .count();
+
+    rsx! {
+        div { id: "recibo", class: "space-y-4 my-5 px-2",
+            div { class: "flex justify-between items-center",
+                h1 { class: "text-xl", {tid!("receipt_title", name: lista().nombre)} }
+                label {
+                    class: "flex items-center text-blue-600 cursor-pointer",
+                    title: tid!("receipt_load_file"),
+                    MaterialIcon { name: "upload_file", size: 24 }
+                    input {
+                        r#type: "file",
+                        class: "hidden",
+                        accept: ".txt,text/*",
+                        onchange: move |event: Event<FormData>| async move {
+                            for archivo in event.files() {
+                                let Ok(contenido) = archivo.read_bytes().await else {
+                                    continue;
+                                };
+                                leer(String::from_utf8_lossy(&contenido).to_string());
+                            }
+                        },
+                    }
+                }
+            }
+            textarea {
+                class: "h-48 w-full bg-gray-50 border border-gray-300 rounded-lg p-2 text-sm font-mono",
+                placeholder: tid!("receipt_placeholder"),
+                value: texto,
+                oninput: move |event| leer(event.value()),
+            }
+            if !coincidencias.read().is_empty() {
+                p { class: "text-sm px-1",
+                    {tid!("receipt_summary", matched: emparejadas, review: ambiguas)}
+                }
+            }
+            div { class: "columns-1 md:columns-2 lg:columns-3",
+                for (indice, coincidencia) in coincidencias().into_iter().enumerate() {
+                    div {
+                        key: "{coincidencia.linea.numero}",
+                        class: "{clase_linea(&coincidencia)} rounded-lg p-2 mb-2 break-inside-avoid-column text-sm",
+                        p { class: "font-mono", "{coincidencia.linea.texto}" }
+                        div { class: "flex gap-x-2 items-center",
+                            span { class: "flex-none",
+                                {format!("{:.3} × {:.2}", coincidencia.linea.cantidad, coincidencia.linea.precio())}
+                            }
+                            select {
+                                class: "flex-1 min-w-0 bg-gray-50 rounded-lg p-1",
+                                onchange: move |event| {
+                                    coincidencias.write()[indice].elegido = event
+                                        .value()
+                                        .parse::<usize>()
+                                        .ok()
+                                        .filter(|id| *id != 0);
+                                },
+                                option {
+                                    value: "0",
+                                    selected: coincidencia.elegido.is_none(),
+                                    {tid!("receipt_not_on_list")}
+                                }
+                                // Primero los parecidos, después el resto de la lista
+                                for (id_item, similitud) in coincidencia.candidatos.iter().copied() {
+                                    option {
+                                        key: "{id_item}",
+                                        value: "{id_item}",
+                                        selected: coincidencia.elegido == Some(id_item),
+                                        {
+                                            format!(
+                                                "{} ({:.0}%)",
+                                                items
+                                                    .iter()
+                                                    .find(|item| item.id == id_item)
+                                                    .map(|item| item.nombre.clone())
+                                                    .unwrap_or_default(),
+                                                similitud * 100.0,
+                                            )
+                                        }
+                                    }
+                                }
+                                for item in items
+                                    .iter()
+                                    .filter(|item| {
+                                        !coincidencia.candidatos.iter().any(|(id_item, _)| *id_item == item.id)
+                                    })
+                                {
+                                    option {
+                                        key: "{item.id}",
+                                        value: "{item.id}",
+                                        selected: coincidencia.elegido == Some(item.id),
+                                        "{item.nombre}"
+                                    }
+                                }
+                            }
+                        }
+                        if coincidencia.ambigua() {
+                            p { class: "text-xs", {tid!("receipt_check_line")} }
+                        }
+                    }
+                }
+            }
+            if !recibo.read().ignoradas.is_empty() {
+                details { class: "text-sm px-1",
+                    summary { class: "cursor-pointer",
+                        {tid!("receipt_ignored_lines", count: recibo.read().ignoradas.len())}
+                    }
+                    for linea in recibo.read().ignoradas.iter() {
+                        p { key: "{linea.numero}", class: "font-mono", "{linea.texto}" }
+                    }
+                }
+            }
+            button {
+                class: "text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                disabled: emparejadas == 0,
+                onclick: move |_| {
+                    let items = lista().items.unwrap_or_default();
+                    match DATABASE.with(|f| aplicar(f.as_ref(), &items, &coincidencias.read())) {
+                        Ok(_) => {
+                            navigator.push(Route::ListaView { id });
+                        }
+                        Err(error) => mensaje.set(error.to_string()),
+                    }
+                },
+                {tid!("receipt_apply")}
+            }
+            if !mensaje().is_empty() {
+                p { class: "text-sm px-1 text-red-600", "{mensaje}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::DATABASE;
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[component]
+pub fn Respaldos() -> Element {
+    let mut respaldos = use_signal(|| DATABASE.with(|f| f.list_backups()).unwrap_or_default());
+    let mut mensaje = use_signal(|| "".to_string());
+
+    let mut mostrar_resultado = move |resultado: Result<(), anyhow::Error>| {
+        match resultado {
+            Ok(()) => mensaje.set(tid!("backup_restored")),
+            Err(error) => mensaje.set(tid!("backup_error", error: error.to_string())),
+        }
+        respaldos.set(DATABASE.with(|f| f.list_backups()).unwrap_or_default());
+    };
+
+    rsx! {
+        div { id: "respaldos", class: "space-y-4 my-5 px-2",
+            div { class: "flex gap-x-2",
+                button {
+                    class: "text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                    onclick: move |_| {
+                        match DATABASE.with(|f| f.create_backup()) {
+                            Ok(respaldo) => mensaje.set(tid!("backup_created", name: respaldo.nombre)),
+                            Err(error) => mensaje.set(tid!("backup_error", error: error.to_string())),
+                        }
+                        respaldos.set(DATABASE.with(|f| f.list_backups()).unwrap_or_default());
+                    },
+                    {tid!("backup_now")}
+                }
+                label { class: "flex items-center gap-x-1 text-blue-600 cursor-pointer text-sm",
+                    MaterialIcon { name: "upload_file", size: 24 }
+                    {tid!("backup_restore_from_file")}
+                    input {
+                        r#type: "file",
+                        class: "hidden",
+                        accept: ".db",
+                        onchange: move |event: Event<FormData>| async move {
+                            for archivo in event.files() {
+                                match archivo.read_bytes().await {
+                                    Ok(datos) => {
+                                        mostrar_resultado(
+                                            DATABASE.with(|f| f.restore_backup_data(datos.to_vec())),
+                                        )
+                                    }
+                                    Err(error) => {
+                                        mensaje.set(tid!("backup_error", error: error.to_string()))
+                                    }
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+            if !mensaje().is_empty() {
+                p { class: "text-sm px-1", "{mensaje}" }
+            }
+            h3 { class: "text-sm px-1 font-medium", {tid!("backups")} }
+            for respaldo in respaldos() {
+                div {
+                    key: "{respaldo.nombre}",
+                    class: "flex flex-row p-3 rounded-lg items-center justify-between bg-gray-300",
+                    span { class: "flex-1", {respaldo.fecha_legible()} }
+                    span { class: "text-sm px-2", {format!("{} KB", respaldo.tamano / 1024)} }
+                    button {
+                        r#type: "button",
+                        class: "text-blue-600 rounded-full px-5 text-center",
+                        onclick: move |_| {
+                            let nombre = respaldo.nombre.clone();
+                            mostrar_resultado(DATABASE.with(|f| f.restore_backup(nombre)));
+                        },
+                        MaterialIcon { name: "settings_backup_restore", size: 24 }
+                    }
+                }
+            }
+        }
+    }
+}
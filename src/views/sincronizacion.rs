@@ -0,0 +1,238 @@
+use crate::DATABASE;
+use crate::components::Toggle;
+use crate::model::LocalSync;
+use crate::model::http::{CLAVE_TOKEN_SERVIDOR, CLAVE_URL_SERVIDOR};
+use crate::model::sync::{
+    PUERTO_SYNC, Par, ResumenSync, codigo_sync, mostrar_codigo_sync, usar_codigo_sync,
+};
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+use std::net::SocketAddr;
+
+pub const CLAVE_VISIBLE_EN_RED: &str = "sync_visible";
+
+// Acepta "192.168.1.20" o "192.168.1.20:45455"
+fn leer_direccion(texto: &str) -> Option<SocketAddr> {
+    let texto = texto.trim();
+    texto
+        .parse()
+        .ok()
+        .or_else(|| format!("{texto}:{PUERTO_SYNC}").parse().ok())
+}
+
+// Ejecuta una acción con la sincronización local, que no existe con un servidor configurado
+fn con_sincronizacion<T>(
+    accion: impl FnOnce(&dyn LocalSync) -> Result<T, anyhow::Error>,
+) -> Result<T, anyhow::Error> {
+    DATABASE.with(|f| match f.local_sync() {
+        Some(sincronizacion) => accion(sincronizacion),
+        None => Err(anyhow::anyhow!("Not available while using a server")),
+    })
+}
+
+#[component]
+pub fn Sincronizacion() -> Element {
+    let sincroniza_en_red = use_hook(|| DATABASE.with(|f| f.local_sync().is_some()));
+    let dispositivo = use_hook(|| {
+        con_sincronizacion(|sincronizacion| sincronizacion.device_id()).unwrap_or_default()
+    });
+    let mut visible = use_signal(|| {
+        DATABASE
+            .with(|f| f.get_setting(CLAVE_VISIBLE_EN_RED))
+            .unwrap_or_default()
+            == Some("1".to_string())
+    });
+    let mut codigo = use_signal(|| {
+        DATABASE
+            .with(|f| codigo_sync(f.as_ref()))
+            .unwrap_or_default()
+    });
+    let mut codigo_ajeno = use_signal(|| "".to_string());
+    let mut pares = use_signal(Vec::<Par>::new);
+    let mut direccion_manual = use_signal(|| "".to_string());
+    let mut mensaje = use_signal(|| "".to_string());
+    let mut url_servidor = use_signal(|| {
+        DATABASE
+            .with(|f| f.get_setting(CLAVE_URL_SERVIDOR))
+            .unwrap_or_default()
+            .unwrap_or_default()
+    });
+    let mut token_servidor = use_signal(|| {
+        DATABASE
+            .with(|f| f.get_setting(CLAVE_TOKEN_SERVIDOR))
+            .unwrap_or_default()
+            .unwrap_or_default()
+    });
+    let mut mensaje_servidor = use_signal(|| "".to_string());
+
+    let mut sincronizar =
+        move |direccion: SocketAddr| match con_sincronizacion(|sincronizacion| {
+            sincronizacion.sync_with(direccion)
+        }) {
+            Ok(ResumenSync {
+                dispositivo,
+                enviados,
+                recibidos,
+            }) => mensaje.set(tid!(
+                "sync_done",
+                device: dispositivo.chars().take(8).collect::<String>(),
+                sent: enviados,
+                received: recibidos
+            )),
+            Err(error) => mensaje.set(tid!("sync_error", error: error.to_string())),
+        };
+
+    rsx! {
+        div { id: "sincronizacion", class: "space-y-4 my-5 px-2",
+            if sincroniza_en_red {
+                p { class: "text-sm px-1",
+                    {tid!("sync_this_device", device: dispositivo.chars().take(8).collect::<String>())}
+                }
+                p { class: "text-sm px-1 font-mono",
+                    {tid!("sync_pairing_code", code: mostrar_codigo_sync(&codigo()))}
+                }
+                div { class: "flex gap-x-2",
+                    input {
+                        r#type: "text",
+                        class: "bg-gray-50 border border-gray-300 text-md rounded-lg block w-full p-2.5",
+                        placeholder: tid!("sync_pair_with"),
+                        value: codigo_ajeno,
+                        oninput: move |event| codigo_ajeno.set(event.value()),
+                    }
+                    button {
+                        class: "basis-1/6 text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                        onclick: move |_| {
+                            match DATABASE.with(|f| usar_codigo_sync(f.as_ref(), &codigo_ajeno.read())) {
+                                Ok(nuevo) => {
+                                    codigo.set(nuevo);
+                                    codigo_ajeno.set("".to_string());
+                                    mensaje.set(tid!("sync_paired"));
+                                }
+                                Err(_) => mensaje.set(tid!("sync_invalid_code")),
+                            }
+                        },
+                        MaterialIcon { name: "link", size: 24 }
+                    }
+                }
+                div { class: "flex items-center justify-between px-1",
+                    span { class: "text-sm", {tid!("sync_visible")} }
+                    Toggle {
+                        text_size_class: "text-sm",
+                        checked: visible(),
+                        value: "true",
+                        onchange: move |event: Event<FormData>| {
+                            let activo = event.checked();
+                            let valor = if activo { "1" } else { "0" };
+                            _ = DATABASE.with(|f| f.set_setting(CLAVE_VISIBLE_EN_RED, valor));
+                            let resultado = con_sincronizacion(|sincronizacion| {
+                                if activo {
+                                    sincronizacion.start_sync_server()
+                                } else {
+                                    sincronizacion.stop_sync_server();
+                                    Ok(())
+                                }
+                            });
+                            if let Err(error) = resultado {
+                                mensaje.set(tid!("sync_error", error: error.to_string()));
+                            }
+                            visible.set(activo);
+                        },
+                    }
+                }
+                div { class: "flex gap-x-2",
+                    button {
+                        class: "text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                        onclick: move |_| {
+                            match con_sincronizacion(|sincronizacion| sincronizacion.discover_peers()) {
+                                Ok(encontrados) => {
+                                    if encontrados.is_empty() {
+                                        mensaje.set(tid!("sync_no_devices"));
+                                    } else {
+                                        mensaje.set("".to_string());
+                                    }
+                                    pares.set(encontrados);
+                                }
+                                Err(error) => mensaje.set(tid!("sync_error", error: error.to_string())),
+                            }
+                        },
+                        {tid!("sync_search_devices")}
+                    }
+                }
+                div { class: "flex gap-x-2",
+                    input {
+                        r#type: "text",
+                        class: "bg-gray-50 border border-gray-300 text-md rounded-lg block w-full p-2.5",
+                        placeholder: tid!("sync_address"),
+                        value: direccion_manual,
+                        oninput: move |event| direccion_manual.set(event.value()),
+                    }
+                    button {
+                        class: "basis-1/6 text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                        onclick: move |_| {
+                            match leer_direccion(&direccion_manual.read()) {
+                                Some(direccion) => sincronizar(direccion),
+                                None => mensaje.set(tid!("sync_invalid_address")),
+                            }
+                        },
+                        MaterialIcon { name: "sync", size: 24 }
+                    }
+                }
+                if !mensaje().is_empty() {
+                    p { class: "text-sm px-1", "{mensaje}" }
+                }
+                for par in pares() {
+                    div {
+                        key: "{par.dispositivo}",
+                        class: "flex flex-row p-3 rounded-lg items-center justify-between bg-gray-300",
+                        span { class: "flex-1", {par.dispositivo.chars().take(8).collect::<String>()} }
+                        span { class: "text-sm px-2", "{par.direccion.ip()}" }
+                        button {
+                            r#type: "button",
+                            class: "text-blue-600 rounded-full px-5 text-center",
+                            onclick: move |_| sincronizar(par.direccion),
+                            MaterialIcon { name: "sync", size: 24 }
+                        }
+                    }
+                }
+            } else {
+                p { class: "text-sm px-1", {tid!("sync_local_unavailable")} }
+            }
+            h3 { class: "pt-4 text-sm font-medium px-1", {tid!("server_title")} }
+            input {
+                r#type: "url",
+                class: "bg-gray-50 border border-gray-300 text-md rounded-lg block w-full p-2.5",
+                placeholder: tid!("server_url"),
+                value: url_servidor,
+                oninput: move |event| url_servidor.set(event.value()),
+            }
+            div { class: "flex gap-x-2",
+                input {
+                    r#type: "password",
+                    class: "bg-gray-50 border border-gray-300 text-md rounded-lg block w-full p-2.5",
+                    placeholder: tid!("server_token"),
+                    value: token_servidor,
+                    oninput: move |event| token_servidor.set(event.value()),
+                }
+                button {
+                    class: "basis-1/6 text-white bg-blue-700 font-medium rounded-lg text-sm px-5 py-2.5 text-center",
+                    onclick: move |_| {
+                        let guardado = DATABASE
+                            .with(|f| {
+                                f.set_setting(CLAVE_URL_SERVIDOR, url_servidor.read().trim())?;
+                                f.set_setting(CLAVE_TOKEN_SERVIDOR, token_servidor.read().trim())
+                            });
+                        match guardado {
+                            Ok(()) => mensaje_servidor.set(tid!("server_saved")),
+                            Err(error) => mensaje_servidor.set(tid!("sync_error", error: error.to_string())),
+                        }
+                    },
+                    MaterialIcon { name: "save", size: 24 }
+                }
+            }
+            if !mensaje_servidor().is_empty() {
+                p { class: "text-sm px-1", "{mensaje_servidor}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::DATABASE;
+use crate::model::sinonimos::{Sinonimo, SinonimoForm};
+use dioxus::prelude::*;
+use dioxus_i18n::tid;
+use dioxus_material_icons::MaterialIcon;
+
+#[component]
+pub fn EditorSinonimos() -> Element {
+    let sinonimos = use_signal(|| DATABASE.with(|f| f.get_synonyms()).unwrap_or_default());
+
+    rsx! {
+        div { id: "sinonimos", class: "space-y-4 my-5 px-2",
+            p { class: "text-sm px-1", {tid!("synonyms_help")} }
+            div { class: "grid grid-cols-[1fr_1fr_auto] gap-x-2 px-1 text-xs",
+                span { {tid!("synonym_alias")} }
+                span { {tid!("synonym_canonical")} }
+                span {}
+            }
+            for sinonimo in sinonimos() {
+                FilaSinonimo { key: "{sinonimo.id}", sinonimo, sinonimos }
+            }
+            FilaSinonimo {
+                key: "nuevo-{sinonimos.len()}",
+                sinonimo: Sinonimo {
+                    id: 0,
+                    alias: String::new(),
+                    canonico: String::new(),
+                },
+                sinonimos,
+            }
+        }
+    }
+}
+
+#[component]
+fn FilaSinonimo(sinonimo: Sinonimo, mut sinonimos: Signal<Vec<Sinonimo>>) -> Element {
+    rsx! {
+        form {
+            class: "grid grid-cols-[1fr_1fr_auto] gap-x-2 items-center p-2 rounded-lg bg-gray-300",
+            onchange: move |event: Event<FormData>| {
+                let sinonimo = event.parsed_values::<SinonimoForm>().unwrap().into_sinonimo();
+                _ = DATABASE.with(|f| f.save_synonym(sinonimo));
+                sinonimos.set(DATABASE.with(|f| f.get_synonyms()).unwrap_or_default());
+            },
+            input { r#type: "hidden", name: "id", value: "{sinonimo.id}" }
+            input {
+                r#type: "text",
+                class: "min-w-0",
+                name: "alias",
+                placeholder: tid!("synonym_alias"),
+                value: sinonimo.alias,
+            }
+            input {
+                r#type: "text",
+                class: "min-w-0",
+                name: "canonico",
+                placeholder: tid!("synonym_canonical"),
+                value: sinonimo.canonico,
+            }
+            if sinonimo.id != 0 {
+                button {
+                    r#type: "button",
+                    class: "text-red-600 rounded-full text-center",
+                    onclick: move |_| {
+                        _ = DATABASE.with(|f| f.delete_synonym(sinonimo.id));
+                        sinonimos.set(DATABASE.with(|f| f.get_synonyms()).unwrap_or_default());
+                    },
+                    MaterialIcon { name: "delete" }
+                }
+            } else {
+                span {}
+            }
+        }
+    }
+}